@@ -8,14 +8,23 @@
 //! Since request executions are driven through futures, the agent also acts as
 //! a specialized task executor for tasks related to requests.
 
-use crate::{error::Error, handler::RequestHandler, task::WakerExt};
+use crate::{
+    config::client::set_altsvc_cache_file,
+    error::{Error, ErrorKind},
+    handler::RequestHandler,
+    task::WakerExt,
+};
 use async_channel::{Receiver, Sender};
 use crossbeam_utils::{atomic::AtomicCell, sync::WaitGroup};
 use curl::multi::{Events, Multi, Socket, SocketEvents};
 use futures_lite::future::block_on;
 use slab::Slab;
 use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
     io,
+    mem,
+    path::PathBuf,
     sync::{Arc, Mutex},
     task::Waker,
     thread,
@@ -24,7 +33,7 @@ use std::{
 
 use self::{selector::Selector, timer::Timer};
 
-mod selector;
+pub(crate) mod selector;
 mod timer;
 
 static NEXT_AGENT_ID: AtomicCell<usize> = AtomicCell::new(0);
@@ -38,6 +47,9 @@ pub(crate) struct AgentBuilder {
     max_connections: usize,
     max_connections_per_host: usize,
     connection_cache_size: usize,
+    thread_name: Option<String>,
+    thread_stack_size: Option<usize>,
+    altsvc_cache_file: Option<PathBuf>,
 }
 
 impl AgentBuilder {
@@ -56,23 +68,26 @@ impl AgentBuilder {
         self
     }
 
+    pub(crate) fn thread_name(mut self, name: String) -> Self {
+        self.thread_name = Some(name);
+        self
+    }
+
+    pub(crate) fn thread_stack_size(mut self, size: usize) -> Self {
+        self.thread_stack_size = Some(size);
+        self
+    }
+
+    pub(crate) fn altsvc_cache_file(mut self, path: PathBuf) -> Self {
+        self.altsvc_cache_file = Some(path);
+        self
+    }
+
     /// Spawn a new agent using the configuration in this builder and return a
     /// handle for communicating with the agent.
     pub(crate) fn spawn(&self) -> io::Result<Handle> {
         let create_start = Instant::now();
 
-        // Initialize libcurl, if necessary, on the current thread.
-        //
-        // Note that as of 0.4.30, the curl crate will attempt to do this for us
-        // on the main thread automatically at program start on most targets,
-        // but on other targets must still be initialized on the main thread. We
-        // do this here in the hope that the user builds an `HttpClient` on the
-        // main thread (as opposed to waiting for `Multi::new()` to do it for
-        // us below, which we _know_ is not on the main thread).
-        //
-        // See #189.
-        curl::init();
-
         let id = NEXT_AGENT_ID.fetch_add(1);
 
         // Create an I/O selector for driving curl's sockets.
@@ -86,6 +101,7 @@ impl AgentBuilder {
         let max_connections = self.max_connections;
         let max_connections_per_host = self.max_connections_per_host;
         let connection_cache_size = self.connection_cache_size;
+        let altsvc_cache_file = self.altsvc_cache_file.clone();
 
         // Create a span for the agent thread that outlives this method call,
         // but rather was caused by it.
@@ -94,6 +110,8 @@ impl AgentBuilder {
 
         let waker = selector.waker();
         let message_tx_clone = message_tx.clone();
+        let stats = Arc::new(AtomicCell::new(Stats::default()));
+        let stats_clone = stats.clone();
 
         let thread_main = move || {
             let _enter = agent_span.enter();
@@ -118,7 +136,15 @@ impl AgentBuilder {
                     .map_err(Error::from_any)?;
             }
 
-            let agent = AgentContext::new(multi, selector, message_tx_clone, message_rx)?;
+            let agent = AgentContext::new(
+                multi,
+                selector,
+                message_tx_clone,
+                message_rx,
+                stats_clone,
+                max_connections,
+                altsvc_cache_file,
+            )?;
 
             drop(wait_group_thread);
 
@@ -133,14 +159,21 @@ impl AgentBuilder {
             result
         };
 
+        let mut thread_builder = thread::Builder::new().name(
+            self.thread_name
+                .clone()
+                .unwrap_or_else(|| format!("isahc-agent-{}", id)),
+        );
+
+        if let Some(size) = self.thread_stack_size {
+            thread_builder = thread_builder.stack_size(size);
+        }
+
         let handle = Handle {
             message_tx,
             waker,
-            join_handle: Mutex::new(Some(
-                thread::Builder::new()
-                    .name(format!("isahc-agent-{}", id))
-                    .spawn(thread_main)?,
-            )),
+            stats,
+            join_handle: Mutex::new(Some(thread_builder.spawn(thread_main)?)),
         };
 
         // Block until the agent thread responds.
@@ -162,6 +195,9 @@ pub(crate) struct Handle {
     /// A waker that can wake up the agent thread while it is polling.
     waker: Waker,
 
+    /// Request counts published by the agent thread.
+    stats: Arc<AtomicCell<Stats>>,
+
     /// A join handle for the agent thread.
     join_handle: Mutex<Option<thread::JoinHandle<Result<(), Error>>>>,
 }
@@ -184,6 +220,25 @@ struct AgentContext {
     /// Contains all of the active requests.
     requests: Slab<curl::multi::Easy2Handle<RequestHandler>>,
 
+    /// Number of active requests at which requests with a priority are queued
+    /// instead of being started, or 0 for no limit.
+    max_active_requests: usize,
+
+    /// Requests waiting for an active request to complete before they can
+    /// begin, ordered by priority and then by the order they were received.
+    queued_requests: BinaryHeap<QueuedRequest>,
+
+    /// Incrementing counter used to order queued requests of equal priority.
+    queued_requests_counter: u64,
+
+    /// Request counts published to the agent handle.
+    stats: Arc<AtomicCell<Stats>>,
+
+    /// File to load the Alt-Svc cache of each request from when it begins.
+    /// Curl saves the cache back to the file when the request's handle is
+    /// dropped, which also happens on the agent thread.
+    altsvc_cache_file: Option<PathBuf>,
+
     /// Indicates if the thread has been requested to stop.
     close_requested: bool,
 
@@ -196,10 +251,27 @@ struct AgentContext {
     /// A timer we use to keep track of curl's timeouts.
     timer: Arc<Timer>,
 
+    /// Deadlines for receiving the response headers of active requests,
+    /// ordered by the earliest deadline first. Entries are not removed when a
+    /// request completes, so an entry might refer to a request that no longer
+    /// exists or whose headers have already been received.
+    headers_deadlines: BinaryHeap<Reverse<(Instant, usize)>>,
+
     /// Queue of socket registration updates from the multi handle.
     socket_updates: Receiver<(Socket, SocketEvents, usize)>,
 }
 
+/// Numbers of requests in an agent at a point in time.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Stats {
+    /// Number of requests currently being executed by curl.
+    pub(crate) active: usize,
+
+    /// Number of requests waiting for a connection, either in the agent queue
+    /// or in curl.
+    pub(crate) pending: usize,
+}
+
 /// A message sent from the main thread to the agent thread.
 #[derive(Debug)]
 enum Message {
@@ -209,6 +281,9 @@ enum Message {
     /// Begin executing a new request.
     Execute(EasyHandle),
 
+    /// Abort all active and queued requests.
+    CancelAll,
+
     /// Request to resume reading the request body for the request with the
     /// given ID.
     UnpauseRead(usize),
@@ -218,6 +293,45 @@ enum Message {
     UnpauseWrite(usize),
 }
 
+/// A request waiting in the agent queue.
+struct QueuedRequest {
+    priority: u8,
+    sequence: u64,
+
+    /// Point in time at which the request times out, counted from when it was
+    /// submitted rather than when it begins.
+    deadline: Option<Instant>,
+
+    request: EasyHandle,
+}
+
+impl QueuedRequest {
+    /// Higher priorities come first, then earlier requests.
+    fn key(&self) -> (u8, Reverse<u64>) {
+        (self.priority, Reverse(self.sequence))
+    }
+}
+
+impl PartialEq for QueuedRequest {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for QueuedRequest {}
+
+impl PartialOrd for QueuedRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedRequest {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
 #[derive(Debug)]
 enum JoinResult {
     AlreadyJoined,
@@ -232,6 +346,16 @@ impl Handle {
         self.send_message(Message::Execute(request))
     }
 
+    /// Abort all requests currently being executed or waiting to begin.
+    pub(crate) fn cancel_all(&self) -> Result<(), Error> {
+        self.send_message(Message::CancelAll)
+    }
+
+    /// Get the latest request counts published by the agent thread.
+    pub(crate) fn stats(&self) -> Stats {
+        self.stats.load()
+    }
+
     /// Send a message to the agent thread.
     ///
     /// If the agent is not connected, an error is returned.
@@ -288,6 +412,9 @@ impl AgentContext {
         selector: Selector,
         message_tx: Sender<Message>,
         message_rx: Receiver<Message>,
+        stats: Arc<AtomicCell<Stats>>,
+        max_active_requests: usize,
+        altsvc_cache_file: Option<PathBuf>,
     ) -> Result<Self, Error> {
         let timer = Arc::new(Timer::new());
         let (socket_updates_tx, socket_updates_rx) = async_channel::unbounded();
@@ -320,14 +447,141 @@ impl AgentContext {
             message_tx,
             message_rx,
             requests: Slab::new(),
+            max_active_requests,
+            queued_requests: BinaryHeap::new(),
+            queued_requests_counter: 0,
+            stats,
+            altsvc_cache_file,
             close_requested: false,
             waker: selector.waker(),
             selector,
             timer,
+            headers_deadlines: BinaryHeap::new(),
             socket_updates: socket_updates_rx,
         })
     }
 
+    /// Begin executing a request, or queue it if it has a priority and the
+    /// maximum number of active requests has been reached.
+    ///
+    /// Curl queues requests on its own when the connection limit is reached,
+    /// but only in the order they are added, so requests with a priority are
+    /// queued here instead to let higher priority requests skip ahead. Other
+    /// requests are left to curl, which can still multiplex them over existing
+    /// HTTP/2 connections, unless requests are already queued here that they
+    /// would otherwise overtake.
+    fn submit_request(&mut self, request: EasyHandle) -> Result<(), Error> {
+        let priority = request.get_ref().priority();
+
+        if self.max_active_requests > 0
+            && self.requests.len() >= self.max_active_requests
+            && (priority > 0 || !self.queued_requests.is_empty())
+        {
+            let sequence = self.queued_requests_counter;
+            self.queued_requests_counter += 1;
+            let deadline = request
+                .get_ref()
+                .timeout()
+                .map(|timeout| Instant::now() + timeout);
+
+            tracing::debug!(priority, "request limit reached, queueing request");
+
+            self.queued_requests.push(QueuedRequest {
+                priority,
+                sequence,
+                deadline,
+                request,
+            });
+
+            Ok(())
+        } else {
+            self.begin_request(request)
+        }
+    }
+
+    /// Publish the current request counts to the agent handle.
+    fn publish_stats(&self) {
+        // Requests queued by curl itself are waiting for a connection too.
+        let waiting = self
+            .requests
+            .iter()
+            .filter(|(_, request)| request.get_ref().is_waiting_for_connection())
+            .count();
+
+        self.stats.store(Stats {
+            active: self.requests.len() - waiting,
+            pending: self.queued_requests.len() + waiting,
+        });
+    }
+
+    /// Begin executing queued requests while there is room for them.
+    fn begin_queued_requests(&mut self) -> Result<(), Error> {
+        while self.requests.len() < self.max_active_requests {
+            let mut queued = match self.queued_requests.pop() {
+                Some(queued) => queued,
+                None => break,
+            };
+
+            // Nobody is waiting for the response anymore.
+            if queued.request.get_ref().is_future_canceled() {
+                continue;
+            }
+
+            // Curl starts the timeout over when the request begins, so only
+            // give it what is left of the original timeout.
+            if let Some(deadline) = queued.deadline {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+
+                // Curl treats a zero timeout as no timeout at all.
+                if remaining < Duration::from_millis(1) {
+                    tracing::debug!("request timed out while queued");
+                    queued
+                        .request
+                        .get_mut()
+                        .set_result(Err(ErrorKind::Timeout.into()));
+                    continue;
+                }
+
+                queued.request.timeout(remaining).map_err(Error::from_any)?;
+            }
+
+            self.begin_request(queued.request)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove queued requests whose future has been dropped, and fail queued
+    /// requests whose timeout passed before they could begin.
+    fn expire_queued_requests(&mut self) {
+        let now = Instant::now();
+        let is_expired = |queued: &QueuedRequest| {
+            queued.request.get_ref().is_future_canceled()
+                || queued.deadline.map(|deadline| deadline <= now).unwrap_or(false)
+        };
+
+        if !self.queued_requests.iter().any(is_expired) {
+            return;
+        }
+
+        let (expired, waiting): (Vec<_>, Vec<_>) = mem::take(&mut self.queued_requests)
+            .into_vec()
+            .into_iter()
+            .partition(is_expired);
+
+        self.queued_requests = waiting.into();
+
+        for mut queued in expired {
+            if !queued.request.get_ref().is_future_canceled() {
+                tracing::debug!("request timed out while queued");
+                queued
+                    .request
+                    .get_mut()
+                    .set_result(Err(ErrorKind::Timeout.into()));
+            }
+        }
+    }
+
     #[tracing::instrument(level = "trace", skip(self))]
     fn begin_request(&mut self, mut request: EasyHandle) -> Result<(), Error> {
         // Prepare an entry for storing this request while it executes.
@@ -363,6 +617,20 @@ impl AgentContext {
             },
         );
 
+        // Load the Alt-Svc cache here rather than when the handle is created,
+        // so that all reads and writes of the cache file happen on this thread
+        // and a request always starts from the latest saved cache.
+        if let Some(path) = self.altsvc_cache_file.as_ref() {
+            if let Err(e) = set_altsvc_cache_file(&mut request, path) {
+                request.get_mut().set_result(Err(Error::from_any(e)));
+                return Ok(());
+            }
+        }
+
+        if let Some(deadline) = request.get_ref().headers_deadline() {
+            self.headers_deadlines.push(Reverse((deadline, id)));
+        }
+
         // Register the request with curl.
         let mut handle = self.multi.add2(request).map_err(Error::from_any)?;
         handle.set_token(id).map_err(Error::from_any)?;
@@ -382,7 +650,32 @@ impl AgentContext {
         let handle = self.requests.remove(token);
         let mut handle = self.multi.remove2(handle).map_err(Error::from_any)?;
 
-        handle.get_mut().set_result(result.map_err(Error::from_any));
+        let result = result.map_err(|e| Error::from_transfer(e, &handle));
+        handle.get_mut().set_result(result);
+
+        self.begin_queued_requests()
+    }
+
+    /// Abort all active and queued requests, completing each of them with a
+    /// cancellation error.
+    fn cancel_all_requests(&mut self) -> Result<(), Error> {
+        tracing::debug!(
+            active = self.requests.len(),
+            queued = self.queued_requests.len(),
+            "canceling all requests"
+        );
+
+        for handle in self.requests.drain() {
+            let mut handle = self.multi.remove2(handle).map_err(Error::from_any)?;
+            handle.get_mut().set_result(Err(ErrorKind::Canceled.into()));
+        }
+
+        for mut queued in self.queued_requests.drain() {
+            queued
+                .request
+                .get_mut()
+                .set_result(Err(ErrorKind::Canceled.into()));
+        }
 
         Ok(())
     }
@@ -425,7 +718,8 @@ impl AgentContext {
 
         match message {
             Message::Close => self.close_requested = true,
-            Message::Execute(request) => self.begin_request(request)?,
+            Message::Execute(request) => self.submit_request(request)?,
+            Message::CancelAll => self.cancel_all_requests()?,
             Message::UnpauseRead(token) => {
                 if let Some(request) = self.requests.get(token) {
                     if let Err(e) = request.unpause_read() {
@@ -481,6 +775,8 @@ impl AgentContext {
                 break;
             }
 
+            self.publish_stats();
+
             // Block until activity is detected or the timeout passes.
             self.poll()?;
 
@@ -497,11 +793,50 @@ impl AgentContext {
             for (token, result) in multi_messages.drain(..) {
                 self.complete_request(token, result)?;
             }
+
+            self.expire_headers_deadlines()?;
+            self.expire_queued_requests();
+            self.publish_stats();
         }
 
         tracing::debug!("agent shutting down");
 
         self.requests.clear();
+        self.queued_requests.clear();
+        self.publish_stats();
+
+        Ok(())
+    }
+
+    /// Abort any requests that have not received their response headers
+    /// before their headers deadline.
+    fn expire_headers_deadlines(&mut self) -> Result<(), Error> {
+        let now = Instant::now();
+
+        while let Some(&Reverse((deadline, token))) = self.headers_deadlines.peek() {
+            if deadline > now {
+                break;
+            }
+
+            self.headers_deadlines.pop();
+
+            // The token might have been reused by another request since this
+            // deadline was registered, so double check that the deadline
+            // belongs to the current request.
+            let is_expired = self
+                .requests
+                .get(token)
+                .and_then(|handle| handle.get_ref().headers_deadline())
+                == Some(deadline);
+
+            if is_expired {
+                tracing::debug!(id = token, "response headers were not received in time");
+                self.complete_request(
+                    token,
+                    Err(curl::Error::new(curl_sys::CURLE_OPERATION_TIMEDOUT)),
+                )?;
+            }
+        }
 
         Ok(())
     }
@@ -513,7 +848,17 @@ impl AgentContext {
 
         // Get the latest timeout value from curl that we should use, limited to
         // a maximum we chose.
-        let poll_timeout = timeout.map(|t| t.min(WAIT_TIMEOUT)).unwrap_or(WAIT_TIMEOUT);
+        let mut poll_timeout = timeout.map(|t| t.min(WAIT_TIMEOUT)).unwrap_or(WAIT_TIMEOUT);
+
+        // Also wake up in time to enforce the next headers deadline.
+        if let Some(Reverse((deadline, _))) = self.headers_deadlines.peek() {
+            poll_timeout = poll_timeout.min(deadline.saturating_duration_since(now));
+        }
+
+        // Likewise for the timeouts of queued requests.
+        if let Some(deadline) = self.queued_requests.iter().filter_map(|q| q.deadline).min() {
+            poll_timeout = poll_timeout.min(deadline.saturating_duration_since(now));
+        }
 
         // Block until either an I/O event occurs on a socket, the timeout is
         // reached, or the agent handle interrupts us.
@@ -204,7 +204,12 @@ impl Selector {
     }
 }
 
-fn poller_add(poller: &Poller, socket: Socket, readable: bool, writable: bool) -> io::Result<()> {
+pub(crate) fn poller_add(
+    poller: &Poller,
+    socket: Socket,
+    readable: bool,
+    writable: bool,
+) -> io::Result<()> {
     // If this errors, we retry the operation as a modification instead. This is
     // because this new socket might re-use a file descriptor that was
     // previously closed, but is still registered with the poller. Retrying the
@@ -231,7 +236,7 @@ fn poller_add(poller: &Poller, socket: Socket, readable: bool, writable: bool) -
     Ok(())
 }
 
-fn poller_modify(
+pub(crate) fn poller_modify(
     poller: &Poller,
     socket: Socket,
     readable: bool,
@@ -259,7 +264,7 @@ fn poller_modify(
     Ok(())
 }
 
-fn is_bad_socket_error(error: &io::Error) -> bool {
+pub(crate) fn is_bad_socket_error(error: &io::Error) -> bool {
     // OS-specific error codes that aren't mapped to an `std::io::ErrorKind`.
     const EBADF: i32 = 9;
     const ERROR_INVALID_HANDLE: i32 = 6;
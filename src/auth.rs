@@ -1,9 +1,15 @@
 //! Types for working with HTTP authentication methods.
 
 use crate::config::{proxy::Proxy, request::SetOpt};
+use once_cell::sync::Lazy;
 use std::{
+    env,
+    ffi::CString,
     fmt,
+    fs,
     ops::{BitOr, BitOrAssign},
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 
 /// Credentials consisting of a username and a secret (password) that can be
@@ -120,6 +126,11 @@ impl Authentication {
         (self.0 & other.0) == other.0
     }
 
+    /// Check whether no authentication schemes are enabled.
+    pub(crate) const fn is_none(&self) -> bool {
+        self.0 == 0
+    }
+
     fn as_auth(&self) -> curl::easy::Auth {
         let mut auth = curl::easy::Auth::new();
 
@@ -189,9 +200,179 @@ impl SetOpt for Proxy<Authentication> {
     }
 }
 
+/// Controls whether credentials are read from a
+/// [`.netrc`](https://everything.curl.dev/usingcurl/netrc) file.
+///
+/// Credentials found in a netrc file are only sent if one or more
+/// authentication methods are also enabled using
+/// [`Configurable::authentication`](crate::config::Configurable::authentication).
+/// Credentials set explicitly using
+/// [`Configurable::credentials`](crate::config::Configurable::credentials)
+/// always take precedence over the netrc file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Netrc {
+    /// Do not read the netrc file. This is the default.
+    Ignored,
+
+    /// Use credentials from the netrc file if it contains an entry for the
+    /// host, falling back to any credentials in the request URI otherwise.
+    Optional,
+
+    /// Require credentials from the netrc file, ignoring any credentials in
+    /// the request URI. If the netrc file does not contain an entry for the
+    /// host, the request fails with
+    /// [`ErrorKind::InvalidCredentials`](crate::error::ErrorKind::InvalidCredentials).
+    Required,
+}
+
+impl SetOpt for Netrc {
+    fn set_opt<H>(&self, easy: &mut curl::easy::Easy2<H>) -> Result<(), curl::Error> {
+        easy.netrc(match self {
+            Netrc::Ignored => curl::easy::NetRc::Ignored,
+            Netrc::Optional => curl::easy::NetRc::Optional,
+            Netrc::Required => curl::easy::NetRc::Required,
+        })
+    }
+}
+
+/// A netrc file to read credentials from.
+///
+/// The file is parsed once when this is created, so that the hosts it has
+/// entries for can be checked for each request without reading it again.
+#[derive(Clone)]
+pub(crate) struct NetrcFile {
+    path: PathBuf,
+    entries: Arc<NetrcEntries>,
+}
+
+impl NetrcFile {
+    /// Read the netrc file at the given path. A missing or unreadable file
+    /// has no entries.
+    pub(crate) fn new(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .map(|contents| NetrcEntries::parse(&contents))
+            .unwrap_or_default();
+
+        Self {
+            path,
+            entries: Arc::new(entries),
+        }
+    }
+
+    /// Get the netrc file to use if no custom location is set, which is
+    /// `.netrc` in the user's home directory.
+    ///
+    /// This is resolved and read only once. The path is passed to curl
+    /// explicitly, so that curl reads the same file that was checked.
+    pub(crate) fn default_file() -> Option<&'static Self> {
+        static DEFAULT: Lazy<Option<NetrcFile>> = Lazy::new(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| NetrcFile::new(Path::new(&home).join(".netrc")))
+        });
+
+        DEFAULT.as_ref()
+    }
+
+    /// Check whether this netrc file contains an entry that applies to the
+    /// given host.
+    pub(crate) fn has_entry_for(&self, host: &str) -> bool {
+        self.entries.contains(host)
+    }
+}
+
+impl fmt::Debug for NetrcFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NetrcFile").field(&self.path).finish()
+    }
+}
+
+impl SetOpt for NetrcFile {
+    #[allow(unsafe_code)]
+    fn set_opt<H>(&self, easy: &mut curl::easy::Easy2<H>) -> Result<(), curl::Error> {
+        let path = self
+            .path
+            .to_str()
+            .and_then(|path| CString::new(path).ok())
+            .ok_or_else(|| curl::Error::new(curl_sys::CURLE_BAD_FUNCTION_ARGUMENT))?;
+
+        // Curl copies the string, so it does not need to outlive this call.
+        unsafe {
+            match curl_sys::curl_easy_setopt(
+                easy.raw(),
+                curl_sys::CURLOPT_NETRC_FILE,
+                path.as_ptr(),
+            ) {
+                curl_sys::CURLE_OK => Ok(()),
+                code => Err(curl::Error::new(code)),
+            }
+        }
+    }
+}
+
+/// The hosts that a netrc file has entries for.
+#[derive(Debug, Default)]
+struct NetrcEntries {
+    machines: Vec<String>,
+    default: bool,
+}
+
+impl NetrcEntries {
+    /// Scan the contents of a netrc file for `machine` and `default` entries.
+    fn parse(contents: &str) -> Self {
+        let mut entries = Self::default();
+        let mut lines = contents.lines();
+
+        while let Some(line) = lines.next() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let mut tokens = line.split_whitespace();
+
+            while let Some(token) = tokens.next() {
+                match token {
+                    "default" => entries.default = true,
+                    "machine" => {
+                        if let Some(name) = tokens.next() {
+                            entries.machines.push(name.trim_matches('"').to_owned());
+                        }
+                    }
+                    // Skip values so that they are not mistaken for keywords.
+                    "login" | "password" | "account" => {
+                        tokens.next();
+                    }
+                    // Macro definitions continue until the next blank line.
+                    "macdef" => {
+                        for line in &mut lines {
+                            if line.trim().is_empty() {
+                                break;
+                            }
+                        }
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        entries
+    }
+
+    /// Check whether there is a `machine` entry matching the given host, or a
+    /// `default` entry.
+    fn contains(&self, host: &str) -> bool {
+        self.default
+            || self
+                .machines
+                .iter()
+                .any(|machine| machine.eq_ignore_ascii_case(host))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Authentication;
+    use super::{Authentication, NetrcEntries};
 
     #[test]
     fn auth_default() {
@@ -221,4 +402,27 @@ mod tests {
         assert!(!auth.contains(Authentication::basic()));
         assert!(auth.contains(Authentication::digest()));
     }
+
+    #[test]
+    fn netrc_entry_lookup() {
+        let contents = "\
+# machine commented.example.org
+machine example.org login clark password machine
+macdef init
+machine macro.example.org
+
+machine Other.Example.org
+    login bruce
+    password batman
+";
+
+        let entries = NetrcEntries::parse(contents);
+
+        assert!(entries.contains("example.org"));
+        assert!(entries.contains("other.example.org"));
+        assert!(!entries.contains("commented.example.org"));
+        assert!(!entries.contains("macro.example.org"));
+        assert!(!entries.contains("clark"));
+        assert!(NetrcEntries::parse("default login anonymous").contains("anywhere.org"));
+    }
 }
@@ -0,0 +1,163 @@
+//! Request bodies that are written to rather than read from.
+
+use super::{AsyncBody, Body};
+use futures_lite::io::{AsyncWrite, BlockOn};
+use sluice::pipe::{pipe, PipeWriter};
+use std::{
+    fmt,
+    io::{self, Write},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+impl Body {
+    /// Create a streaming body along with a writer that produces its contents.
+    ///
+    /// Bytes written to the returned [`BodyWriter`] become the contents of the
+    /// body, which is useful when the code producing a request body naturally
+    /// writes its output rather than exposing a reader. Dropping the writer
+    /// signals the end of the body. Since the length is unknown, [chunked
+    /// transfer encoding](https://tools.ietf.org/html/rfc7230#section-4.1)
+    /// might be used to send the request.
+    ///
+    /// The body has a small internal buffer, so writes block until the request
+    /// consumes enough of the body. This means the writer must be used from a
+    /// different thread than the one sending the request.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Body, Request};
+    /// use std::{io::Write, thread};
+    ///
+    /// let (mut writer, body) = Body::channel();
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 0..10 {
+    ///         writeln!(writer, "line {}", i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let response = Request::post("https://httpbin.org/post")
+    ///     .body(body)?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    pub fn channel() -> (BodyWriter, Self) {
+        let (reader, writer) = pipe();
+
+        (BodyWriter(BlockOn::new(writer)), Self::from_pipe(reader))
+    }
+}
+
+impl AsyncBody {
+    /// Create a streaming body along with a writer that produces its contents.
+    ///
+    /// This is the asynchronous equivalent of [`Body::channel`]. Bytes written
+    /// to the returned [`AsyncBodyWriter`] become the contents of the body,
+    /// and closing or dropping the writer signals the end of the body.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use futures_lite::{future::zip, io::AsyncWriteExt};
+    /// use isahc::{prelude::*, AsyncBody, Request};
+    ///
+    /// # async fn run() -> Result<(), isahc::Error> {
+    /// let (mut writer, body) = AsyncBody::channel();
+    ///
+    /// let producer = async move {
+    ///     writer.write_all(b"hello world").await?;
+    ///     writer.close().await
+    /// };
+    ///
+    /// let request = Request::post("https://httpbin.org/post").body(body)?;
+    /// let (result, response) = zip(producer, request.send_async()).await;
+    /// result?;
+    /// response?;
+    /// # Ok(()) }
+    /// ```
+    pub fn channel() -> (AsyncBodyWriter, Self) {
+        let (reader, writer) = pipe();
+
+        (AsyncBodyWriter(writer), Self::from_reader(reader))
+    }
+}
+
+/// The writing half of a body created using [`Body::channel`].
+///
+/// Writing fails with [`io::ErrorKind::BrokenPipe`] once the body has been
+/// dropped, such as when the request fails.
+pub struct BodyWriter(BlockOn<PipeWriter>);
+
+impl Write for BodyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl fmt::Debug for BodyWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyWriter").finish()
+    }
+}
+
+/// The writing half of a body created using [`AsyncBody::channel`].
+///
+/// Writing fails with [`io::ErrorKind::BrokenPipe`] once the body has been
+/// dropped, such as when the request fails.
+pub struct AsyncBodyWriter(PipeWriter);
+
+impl AsyncWrite for AsyncBodyWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
+
+impl fmt::Debug for AsyncBodyWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncBodyWriter").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Read, thread};
+
+    static_assertions::assert_impl_all!(BodyWriter: Send, Sync);
+    static_assertions::assert_impl_all!(AsyncBodyWriter: Send, Sync);
+
+    #[test]
+    fn written_bytes_are_read_from_body() {
+        let (mut writer, mut body) = Body::channel();
+
+        let thread = thread::spawn(move || {
+            writer.write_all(b"hello ").unwrap();
+            writer.write_all(b"world").unwrap();
+        });
+
+        let mut buf = String::new();
+        body.read_to_string(&mut buf).unwrap();
+        thread.join().unwrap();
+
+        assert_eq!(buf, "hello world");
+        assert_eq!(body.len(), None);
+    }
+}
@@ -0,0 +1,189 @@
+//! Compression of request bodies.
+
+use super::{AsyncBody, Inner};
+use crate::config::Encoding;
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
+use futures_lite::{io::AsyncRead, ready};
+use std::{
+    io::{self, Write},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+impl AsyncBody {
+    /// Compress this body using the given encoding.
+    ///
+    /// Bodies stored in memory are compressed immediately so that the
+    /// compressed body remains rewindable and of known length. Reader bodies
+    /// are compressed on the fly as they are read and become unsized.
+    pub(crate) fn compress(self, encoding: Encoding) -> io::Result<Self> {
+        Ok(match self.0 {
+            Inner::Empty => Self::empty(),
+            Inner::Buffer(cursor) => {
+                let mut encoder = Encoder::new(encoding);
+                encoder.write_all(cursor.get_ref())?;
+
+                Self::from(encoder.finish()?)
+            }
+            Inner::Reader(reader, _) => Self::from_reader(CompressReader {
+                inner: reader,
+                encoder: Encoder::new(encoding),
+                finished: false,
+            }),
+            Inner::BufReader(reader, _) => Self::from_reader(CompressReader {
+                inner: Box::pin(reader),
+                encoder: Encoder::new(encoding),
+                finished: false,
+            }),
+        })
+    }
+}
+
+/// A compression encoder writing into an in-memory buffer.
+enum Encoder {
+    Gzip(GzEncoder<Vec<u8>>),
+    Deflate(ZlibEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Gzip => Encoder::Gzip(GzEncoder::new(Vec::new(), Compression::default())),
+            Encoding::Deflate => {
+                Encoder::Deflate(ZlibEncoder::new(Vec::new(), Compression::default()))
+            }
+        }
+    }
+
+    /// Get a mutable reference to the compressed output produced so far.
+    fn output_mut(&mut self) -> &mut Vec<u8> {
+        match self {
+            Encoder::Gzip(encoder) => encoder.get_mut(),
+            Encoder::Deflate(encoder) => encoder.get_mut(),
+        }
+    }
+
+    /// Write any remaining compressed output, including the stream trailer.
+    fn try_finish(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Gzip(encoder) => encoder.try_finish(),
+            Encoder::Deflate(encoder) => encoder.try_finish(),
+        }
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            Encoder::Gzip(encoder) => encoder.finish(),
+            Encoder::Deflate(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            Encoder::Deflate(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Gzip(encoder) => encoder.flush(),
+            Encoder::Deflate(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// An async reader that compresses the bytes read from an inner reader.
+struct CompressReader {
+    inner: Pin<Box<dyn AsyncRead + Send + Sync>>,
+    encoder: Encoder,
+    finished: bool,
+}
+
+impl AsyncRead for CompressReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut chunk = [0; 8192];
+
+        loop {
+            // Return any compressed output that is ready first.
+            let output = self.encoder.output_mut();
+
+            if !output.is_empty() {
+                let len = output.len().min(buf.len());
+                buf[..len].copy_from_slice(&output[..len]);
+                output.drain(..len);
+
+                return Poll::Ready(Ok(len));
+            }
+
+            if self.finished {
+                return Poll::Ready(Ok(0));
+            }
+
+            // Feed more uncompressed data into the encoder.
+            match ready!(self.inner.as_mut().poll_read(cx, &mut chunk))? {
+                0 => {
+                    self.encoder.try_finish()?;
+                    self.finished = true;
+                }
+                len => self.encoder.write_all(&chunk[..len])?,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::{GzDecoder, ZlibDecoder};
+    use futures_lite::{future::block_on, AsyncReadExt};
+    use std::io::Read;
+
+    fn read_to_end(mut body: AsyncBody) -> Vec<u8> {
+        let mut buf = Vec::new();
+        block_on(body.read_to_end(&mut buf)).unwrap();
+        buf
+    }
+
+    #[test]
+    fn compress_memory_body_with_gzip() {
+        let body = AsyncBody::from("hello world".repeat(100))
+            .compress(Encoding::Gzip)
+            .unwrap();
+
+        assert!(body.len().unwrap() < 1100);
+
+        let mut decoded = String::new();
+        GzDecoder::new(&read_to_end(body)[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+
+        assert_eq!(decoded, "hello world".repeat(100));
+    }
+
+    #[test]
+    fn compress_reader_body_with_deflate() {
+        let data = "hello world".repeat(10_000);
+        let body = AsyncBody::from_reader_sized(futures_lite::io::Cursor::new(data.clone()), 0)
+            .compress(Encoding::Deflate)
+            .unwrap();
+
+        assert_eq!(body.len(), None);
+
+        let mut decoded = String::new();
+        ZlibDecoder::new(&read_to_end(body)[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+
+        assert_eq!(decoded, data);
+    }
+}
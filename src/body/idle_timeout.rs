@@ -0,0 +1,146 @@
+//! Timing out response body reads that stall for too long.
+
+use super::Body;
+use crate::task::{self, TimerEntry};
+use futures_lite::{future::block_on, io::AsyncRead};
+use std::{
+    fmt,
+    io::{self, Read},
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+/// A body wrapper that fails reads once no data has been received for a given
+/// amount of time.
+///
+/// Created by
+/// [`ResponseExt::with_idle_timeout`](crate::ResponseExt::with_idle_timeout).
+pub struct IdleTimeout<T> {
+    inner: T,
+    idle: Idle,
+}
+
+impl<T> IdleTimeout<T> {
+    pub(crate) fn new(inner: T, timeout: Duration) -> Self {
+        Self {
+            inner,
+            idle: Idle {
+                timeout,
+                deadline: None,
+                wakeup: None,
+            },
+        }
+    }
+
+    /// Get a reference to the wrapped body.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped body.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwrap this wrapper, returning the wrapped body.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for IdleTimeout<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.idle.poll_read(&mut this.inner, cx, buf)
+    }
+}
+
+impl Read for IdleTimeout<Body> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let idle = &mut self.idle;
+
+        // Only response bodies received from a server can stall, and those are
+        // always backed by an asynchronous stream.
+        match self.inner.async_reader_mut() {
+            Some(reader) => block_on(futures_lite::future::poll_fn(|cx| {
+                idle.poll_read(reader, cx, buf)
+            })),
+            None => self.inner.read(buf),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IdleTimeout<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdleTimeout")
+            .field("inner", &self.inner)
+            .field("timeout", &self.idle.timeout)
+            .finish()
+    }
+}
+
+/// Tracks the deadline for the next chunk of data to arrive.
+struct Idle {
+    timeout: Duration,
+
+    /// Time by which more data must be received, set once reading begins.
+    deadline: Option<Instant>,
+
+    /// Registration with the shared timer that wakes up a pending read once
+    /// the deadline passes, along with the waker that was registered.
+    wakeup: Option<(Waker, TimerEntry)>,
+}
+
+impl Idle {
+    fn poll_read<R: AsyncRead + Unpin + ?Sized>(
+        &mut self,
+        reader: &mut R,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let timeout = self.timeout;
+        let deadline = *self
+            .deadline
+            .get_or_insert_with(|| Instant::now() + timeout);
+
+        match Pin::new(reader).poll_read(cx, buf) {
+            Poll::Ready(result) => {
+                self.deadline = None;
+                self.wakeup = None;
+                Poll::Ready(result)
+            }
+            Poll::Pending => {
+                if Instant::now() >= deadline {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "no response body data received within idle timeout",
+                    )));
+                }
+
+                let registered = self
+                    .wakeup
+                    .as_ref()
+                    .map(|(waker, entry)| {
+                        entry.deadline() == deadline && waker.will_wake(cx.waker())
+                    })
+                    .unwrap_or(false);
+
+                if !registered {
+                    // Drop the old registration first so that the timer does
+                    // not have to hold on to both.
+                    self.wakeup = None;
+
+                    let entry = task::wake_at(deadline, cx.waker().clone())?;
+                    self.wakeup = Some((cx.waker().clone(), entry));
+                }
+
+                Poll::Pending
+            }
+        }
+    }
+}
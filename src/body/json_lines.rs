@@ -0,0 +1,157 @@
+//! Streaming of newline-delimited JSON request bodies.
+
+use super::{AsyncBody, Body};
+use futures_lite::io::AsyncRead;
+use serde::Serialize;
+use std::{
+    io::{self, Read},
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+};
+
+impl Body {
+    /// Create a streaming body that serializes each item from the given
+    /// iterator as JSON, one item per line.
+    ///
+    /// This produces [newline-delimited JSON](http://ndjson.org), as accepted
+    /// by many bulk ingestion APIs. Items are serialized lazily as the body is
+    /// read, so the entire payload never needs to be held in memory at once.
+    /// The body will have an unknown length, so [chunked transfer
+    /// encoding](https://tools.ietf.org/html/rfc7230#section-4.1) will be used
+    /// to send it.
+    ///
+    /// If an item fails to serialize, reading the body fails with an error of
+    /// kind [`InvalidData`](io::ErrorKind::InvalidData).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Body, Request};
+    ///
+    /// let response = Request::post("https://example.org/bulk")
+    ///     .header("content-type", "application/x-ndjson")
+    ///     .body(Body::from_json_lines((0..1000).map(|id| {
+    ///         serde_json::json!({ "id": id })
+    ///     })))?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the [`json`](index.html#json)
+    /// feature is enabled.
+    pub fn from_json_lines<I>(items: I) -> Self
+    where
+        I: IntoIterator,
+        I::IntoIter: Send + 'static,
+        I::Item: Serialize,
+    {
+        Self::from_reader(JsonLines::new(items.into_iter()))
+    }
+}
+
+impl AsyncBody {
+    /// Create a streaming body that serializes each item from the given
+    /// iterator as JSON, one item per line.
+    ///
+    /// See [`Body::from_json_lines`] for details.
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the [`json`](index.html#json)
+    /// feature is enabled.
+    pub fn from_json_lines<I>(items: I) -> Self
+    where
+        I: IntoIterator,
+        I::IntoIter: Send + 'static,
+        I::Item: Serialize,
+    {
+        Self::from_reader(JsonLines::new(items.into_iter()))
+    }
+}
+
+/// A reader that serializes items from an iterator into JSON lines on demand.
+struct JsonLines<I> {
+    // Wrapped in a mutex so that bodies can be created from iterators that
+    // are not `Sync`.
+    items: Mutex<I>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl<I> JsonLines<I> {
+    fn new(items: I) -> Self {
+        Self {
+            items: Mutex::new(items),
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl<I> Read for JsonLines<I>
+where
+    I: Iterator,
+    I::Item: Serialize,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.buffer.len() {
+            self.buffer.clear();
+            self.position = 0;
+
+            match self.items.get_mut().unwrap().next() {
+                Some(item) => {
+                    serde_json::to_writer(&mut self.buffer, &item)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    self.buffer.push(b'\n');
+                }
+                None => return Ok(0),
+            }
+        }
+
+        let len = (&self.buffer[self.position..]).read(buf)?;
+        self.position += len;
+
+        Ok(len)
+    }
+}
+
+// The iterator is never pinned, so this is fine regardless of what it is.
+impl<I> Unpin for JsonLines<I> {}
+
+impl<I> AsyncRead for JsonLines<I>
+where
+    I: Iterator,
+    I::Item: Serialize,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.get_mut().read(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_are_serialized_one_per_line() {
+        let mut body = Body::from_json_lines(vec![
+            serde_json::json!({"a": 1}),
+            serde_json::json!("two"),
+            serde_json::json!([3]),
+        ]);
+
+        assert_eq!(body.len(), None);
+
+        let mut contents = String::new();
+        body.read_to_string(&mut contents).unwrap();
+
+        assert_eq!(contents, "{\"a\":1}\n\"two\"\n[3]\n");
+    }
+}
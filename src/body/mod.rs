@@ -1,17 +1,30 @@
 //! Provides types for working with request and response bodies.
 
-use futures_lite::io::{AsyncRead, BlockOn};
+use futures_lite::io::{AsyncBufRead, AsyncRead, BufReader};
 use std::{
-    borrow::Cow,
     fmt,
-    io::{self, Cursor, Read},
+    io::{self, BufRead, Cursor, Read},
+    mem,
+    ops::Deref,
     pin::Pin,
     str,
+    sync::Arc,
     task::{Context, Poll},
 };
 
+mod channel;
+#[cfg(feature = "request-compression")]
+mod compress;
+mod idle_timeout;
+#[cfg(feature = "json")]
+mod json_lines;
+mod sniff;
 mod sync;
 
+#[allow(unreachable_pub)]
+pub use channel::{AsyncBodyWriter, BodyWriter};
+#[allow(unreachable_pub)]
+pub use idle_timeout::IdleTimeout;
 #[allow(unreachable_pub)]
 pub use sync::Body;
 
@@ -27,6 +40,12 @@ pub use sync::Body;
 /// anything that implements [`AsyncRead`], which [`AsyncBody`] itself also
 /// implements.
 ///
+/// [`AsyncBody`] also implements [`AsyncBufRead`], so there is no need to wrap
+/// a body in a buffered reader in order to read it line by line. Response
+/// bodies are already buffered internally and are read without any extra
+/// copying, while bodies created from an arbitrary reader allocate a buffer
+/// the first time a buffered read is performed.
+///
 /// For synchronous requests, use [`Body`] instead.
 pub struct AsyncBody(Inner);
 
@@ -36,10 +55,55 @@ enum Inner {
     Empty,
 
     /// A body stored in memory.
-    Buffer(Cursor<Cow<'static, [u8]>>),
+    Buffer(Cursor<Bytes>),
 
     /// An asynchronous reader.
     Reader(Pin<Box<dyn AsyncRead + Send + Sync>>, Option<u64>),
+
+    /// An asynchronous buffered reader.
+    BufReader(Pin<Box<dyn AsyncBufRead + Send + Sync>>, Option<u64>),
+}
+
+/// Bytes of a body stored in memory.
+#[derive(Clone)]
+enum Bytes {
+    /// Bytes that live forever and never need to be copied.
+    Static(&'static [u8]),
+
+    /// Bytes owned by a single body.
+    Owned(Vec<u8>),
+
+    /// Bytes shared between bodies created from the same [`ReusableBody`].
+    Shared(Arc<[u8]>),
+}
+
+impl Bytes {
+    /// Get a shared reference to these bytes, copying them only if they
+    /// aren't already shared.
+    fn share(&self) -> Arc<[u8]> {
+        match self {
+            Self::Shared(bytes) => bytes.clone(),
+            bytes => Arc::from(&bytes[..]),
+        }
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Static(bytes) => bytes,
+            Self::Owned(bytes) => bytes,
+            Self::Shared(bytes) => bytes,
+        }
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
 }
 
 impl AsyncBody {
@@ -76,7 +140,7 @@ impl AsyncBody {
         B: AsRef<[u8]> + 'static,
     {
         match_type! {
-            <bytes as Cursor<Cow<'static, [u8]>>> => Self(Inner::Buffer(bytes)),
+            <bytes as Bytes> => Self(Inner::Buffer(Cursor::new(bytes))),
             <bytes as &'static [u8]> => Self::from_static_impl(bytes),
             <bytes as &'static str> => Self::from_static_impl(bytes.as_bytes()),
             <bytes as Vec<u8>> => Self::from(bytes),
@@ -87,7 +151,7 @@ impl AsyncBody {
 
     #[inline]
     fn from_static_impl(bytes: &'static [u8]) -> Self {
-        Self(Inner::Buffer(Cursor::new(Cow::Borrowed(bytes))))
+        Self(Inner::Buffer(Cursor::new(Bytes::Static(bytes))))
     }
 
     /// Create a streaming body that reads from the given reader.
@@ -119,6 +183,15 @@ impl AsyncBody {
         Self(Inner::Reader(Box::pin(read), Some(length)))
     }
 
+    /// Create a streaming body that reads from the given buffered reader,
+    /// with an optional known length.
+    pub(crate) fn from_buf_reader<R>(read: R, length: Option<u64>) -> Self
+    where
+        R: AsyncBufRead + Send + Sync + 'static,
+    {
+        Self(Inner::BufReader(Box::pin(read), length))
+    }
+
     /// Report if this body is empty.
     ///
     /// This is not necessarily the same as checking for `self.len() ==
@@ -150,6 +223,7 @@ impl AsyncBody {
             Inner::Empty => Some(0),
             Inner::Buffer(bytes) => Some(bytes.get_ref().len() as u64),
             Inner::Reader(_, len) => *len,
+            Inner::BufReader(_, len) => *len,
         }
     }
 
@@ -162,7 +236,36 @@ impl AsyncBody {
                 cursor.set_position(0);
                 true
             }
-            Inner::Reader(_, _) => false,
+            Inner::Reader(_, _) | Inner::BufReader(_, _) => false,
+        }
+    }
+
+    /// Create a copy of this body, if the body is repeatable. Returns `None`
+    /// if the body is a stream created from a reader and cannot be copied.
+    ///
+    /// The copy always starts from the beginning of the content, regardless of
+    /// how much of this body has already been read. Bodies created from a
+    /// static buffer are copied without copying the underlying bytes.
+    pub fn try_clone(&self) -> Option<Self> {
+        match &self.0 {
+            Inner::Empty => Some(Self(Inner::Empty)),
+            Inner::Buffer(cursor) => Some(Self(Inner::Buffer(Cursor::new(
+                cursor.get_ref().clone(),
+            )))),
+            Inner::Reader(_, _) | Inner::BufReader(_, _) => None,
+        }
+    }
+
+    /// Create a [`ReusableBody`] containing the contents of this body, if the
+    /// body is repeatable. Returns `None` if the body is a stream created from
+    /// a reader.
+    ///
+    /// See [`Body::reusable`] for details.
+    pub fn reusable(&self) -> Option<ReusableBody> {
+        match &self.0 {
+            Inner::Empty => Some(ReusableBody::empty()),
+            Inner::Buffer(cursor) => Some(ReusableBody::new(cursor.get_ref())),
+            Inner::Reader(_, _) | Inner::BufReader(_, _) => None,
         }
     }
 
@@ -177,10 +280,11 @@ impl AsyncBody {
         match self.0 {
             Inner::Empty => sync::Body::empty(),
             Inner::Buffer(cursor) => sync::Body::from_bytes_static(cursor.into_inner()),
-            Inner::Reader(reader, Some(len)) => {
-                sync::Body::from_reader_sized(BlockOn::new(reader), len)
-            }
-            Inner::Reader(reader, None) => sync::Body::from_reader(BlockOn::new(reader)),
+            // Streaming bodies keep the asynchronous body rather than just its
+            // reader, so that features like idle timeouts can still wait on
+            // the stream without blocking. Reading and the reported length
+            // are the same as reading the reader directly.
+            inner => sync::Body::from_async(Self(inner)),
         }
     }
 }
@@ -195,6 +299,37 @@ impl AsyncRead for AsyncBody {
             Inner::Empty => Poll::Ready(Ok(0)),
             Inner::Buffer(cursor) => Poll::Ready(cursor.read(buf)),
             Inner::Reader(read, _) => AsyncRead::poll_read(read.as_mut(), cx, buf),
+            Inner::BufReader(read, _) => AsyncRead::poll_read(read.as_mut(), cx, buf),
+        }
+    }
+}
+
+impl AsyncBufRead for AsyncBody {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+
+        // Plain readers need a buffer to support buffered reads, so add one
+        // the first time it is needed.
+        if let Inner::Reader(_, _) = this.0 {
+            if let Inner::Reader(reader, len) = mem::replace(&mut this.0, Inner::Empty) {
+                this.0 = Inner::BufReader(Box::pin(BufReader::new(reader)), len);
+            }
+        }
+
+        match &mut this.0 {
+            Inner::Empty => Poll::Ready(Ok(&[])),
+            Inner::Buffer(cursor) => Poll::Ready(cursor.fill_buf()),
+            Inner::Reader(_, _) => unreachable!(),
+            Inner::BufReader(read, _) => read.as_mut().poll_fill_buf(cx),
+        }
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        match &mut self.get_mut().0 {
+            Inner::Empty => {}
+            Inner::Buffer(cursor) => cursor.consume(amt),
+            Inner::Reader(_, _) => {}
+            Inner::BufReader(read, _) => read.as_mut().consume(amt),
         }
     }
 }
@@ -213,7 +348,7 @@ impl From<()> for AsyncBody {
 
 impl From<Vec<u8>> for AsyncBody {
     fn from(body: Vec<u8>) -> Self {
-        Self(Inner::Buffer(Cursor::new(Cow::Owned(body))))
+        Self(Inner::Buffer(Cursor::new(Bytes::Owned(body))))
     }
 }
 
@@ -253,12 +388,79 @@ impl fmt::Debug for AsyncBody {
     }
 }
 
+/// A request body stored in memory that can be sent any number of times
+/// without copying its contents.
+///
+/// Created by [`Body::reusable`] or [`AsyncBody::reusable`]. Each call to
+/// [`ReusableBody::body`] or [`ReusableBody::async_body`] returns a new body
+/// that shares the same underlying bytes, which makes it cheap to send the same
+/// payload in many requests.
+#[derive(Clone)]
+pub struct ReusableBody(Option<Arc<[u8]>>);
+
+impl ReusableBody {
+    const fn empty() -> Self {
+        Self(None)
+    }
+
+    fn new(bytes: &Bytes) -> Self {
+        Self(Some(bytes.share()))
+    }
+
+    /// Get the size of the body.
+    pub fn len(&self) -> u64 {
+        self.0.as_ref().map(|bytes| bytes.len() as u64).unwrap_or(0)
+    }
+
+    /// Report if this body is empty.
+    ///
+    /// Like [`Body::is_empty`], this only returns `true` if the body was
+    /// created from an empty body, and not for a body of zero length.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Create a new synchronous body from this reusable body.
+    pub fn body(&self) -> Body {
+        match &self.0 {
+            Some(bytes) => Body::from_bytes_static(Bytes::Shared(bytes.clone())),
+            None => Body::empty(),
+        }
+    }
+
+    /// Create a new asynchronous body from this reusable body.
+    pub fn async_body(&self) -> AsyncBody {
+        match &self.0 {
+            Some(bytes) => AsyncBody(Inner::Buffer(Cursor::new(Bytes::Shared(bytes.clone())))),
+            None => AsyncBody::empty(),
+        }
+    }
+}
+
+impl From<&'_ ReusableBody> for Body {
+    fn from(body: &ReusableBody) -> Self {
+        body.body()
+    }
+}
+
+impl From<&'_ ReusableBody> for AsyncBody {
+    fn from(body: &ReusableBody) -> Self {
+        body.async_body()
+    }
+}
+
+impl fmt::Debug for ReusableBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ReusableBody({})", self.len())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use futures_lite::{
         future::{block_on, zip},
-        io::AsyncReadExt,
+        io::{AsyncBufReadExt, AsyncReadExt},
     };
 
     static_assertions::assert_impl_all!(AsyncBody: Send, Sync);
@@ -295,6 +497,23 @@ mod tests {
         assert_eq!(body.len(), Some(0));
     }
 
+    #[test]
+    fn buffered_read_from_reader() {
+        block_on(async {
+            let mut body = AsyncBody::from_reader_sized(&b"hello\nworld\n"[..], 12);
+            let mut line = String::new();
+
+            assert_eq!(body.read_line(&mut line).await.unwrap(), 6);
+            assert_eq!(line, "hello\n");
+            assert_eq!(body.len(), Some(12));
+
+            // Mixing unbuffered reads with buffered reads does not lose data.
+            let mut rest = String::new();
+            body.read_to_string(&mut rest).await.unwrap();
+            assert_eq!(rest, "world\n");
+        });
+    }
+
     #[test]
     fn reset_memory_body() {
         block_on(async {
@@ -317,6 +536,28 @@ mod tests {
         assert_eq!(body.reset(), false);
     }
 
+    #[test]
+    fn clone_memory_body_from_start() {
+        block_on(async {
+            let mut body = AsyncBody::from("hello world");
+            let mut buf = String::new();
+
+            body.read_to_string(&mut buf).await.unwrap();
+
+            let mut copy = body.try_clone().unwrap();
+            buf.clear();
+            assert_eq!(copy.read_to_string(&mut buf).await.unwrap(), 11);
+            assert_eq!(buf, "hello world");
+        });
+    }
+
+    #[test]
+    fn cannot_clone_reader() {
+        let body = AsyncBody::from_reader(futures_lite::io::empty());
+
+        assert!(body.try_clone().is_none());
+    }
+
     #[test]
     fn sync_memory_into_async() {
         let (body, writer) = Body::from("hello world").into_async();
@@ -0,0 +1,99 @@
+//! Guessing the media type of a body from its contents.
+
+use super::AsyncBody;
+use futures_lite::io::AsyncBufReadExt;
+use std::io;
+
+/// Known magic numbers at the start of a file, and the media types they
+/// indicate.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"BM", "image/bmp"),
+    (b"\x00\x00\x01\x00", "image/x-icon"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b\x08", "application/gzip"),
+    (b"\x00asm", "application/wasm"),
+    (b"OggS\x00", "application/ogg"),
+    (b"ID3", "audio/mpeg"),
+    (b"fLaC", "audio/flac"),
+    (b"\x1a\x45\xdf\xa3", "video/webm"),
+    (b"wOFF", "font/woff"),
+    (b"wOF2", "font/woff2"),
+    (b"<?xml", "application/xml"),
+];
+
+impl AsyncBody {
+    /// Guess the media type of this body by looking at the bytes at the start
+    /// of its contents, without consuming them.
+    ///
+    /// Bodies created from a reader will be buffered as a result.
+    pub(crate) async fn sniff_content_type(&mut self) -> io::Result<Option<&'static str>> {
+        Ok(sniff(self.fill_buf().await?))
+    }
+}
+
+/// Guess the media type of some content from its first few bytes.
+fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    for (signature, media_type) in SIGNATURES {
+        if bytes.starts_with(signature) {
+            return Some(media_type);
+        }
+    }
+
+    // Formats with a container header followed by a format identifier.
+    if bytes.len() >= 12 {
+        match (&bytes[..4], &bytes[4..8], &bytes[8..12]) {
+            (b"RIFF", _, b"WEBP") => return Some("image/webp"),
+            (b"RIFF", _, b"WAVE") => return Some("audio/wav"),
+            (_, b"ftyp", b"isom") | (_, b"ftyp", b"mp41") | (_, b"ftyp", b"mp42") => {
+                return Some("video/mp4");
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future::block_on;
+
+    #[test]
+    fn sniff_known_signatures() {
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\x00\x00"), Some("image/png"));
+        assert_eq!(sniff(b"\xff\xd8\xff\xe0"), Some("image/jpeg"));
+        assert_eq!(sniff(b"GIF89a..."), Some("image/gif"));
+        assert_eq!(sniff(b"%PDF-1.7"), Some("application/pdf"));
+        assert_eq!(sniff(b"RIFF\x00\x00\x00\x00WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff(b"RIFF\x00\x00\x00\x00WAVEfmt "), Some("audio/wav"));
+        assert_eq!(sniff(b"\x00\x00\x00\x18ftypmp42"), Some("video/mp4"));
+    }
+
+    #[test]
+    fn sniff_unknown_content() {
+        assert_eq!(sniff(b""), None);
+        assert_eq!(sniff(b"hello world"), None);
+        assert_eq!(sniff(b"RIFF\x00\x00"), None);
+    }
+
+    #[test]
+    fn sniffing_reader_does_not_consume_body() {
+        block_on(async {
+            let mut body = AsyncBody::from_reader(&b"GIF87a rest of image"[..]);
+
+            assert_eq!(body.sniff_content_type().await.unwrap(), Some("image/gif"));
+
+            let mut contents = Vec::new();
+            futures_lite::io::copy(&mut body, &mut contents)
+                .await
+                .unwrap();
+            assert_eq!(contents, b"GIF87a rest of image");
+        });
+    }
+}
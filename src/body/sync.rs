@@ -1,8 +1,10 @@
-use super::AsyncBody;
-use futures_lite::{future::yield_now, io::AsyncWriteExt};
-use sluice::pipe::{pipe, PipeWriter};
+use super::{AsyncBody, Bytes, ReusableBody};
+use futures_lite::{
+    future::yield_now,
+    io::{AsyncRead, AsyncWriteExt, BlockOn},
+};
+use sluice::pipe::{pipe, PipeReader, PipeWriter};
 use std::{
-    borrow::Cow,
     fmt,
     fs::File,
     io::{Cursor, ErrorKind, Read, Result},
@@ -21,8 +23,16 @@ pub struct Body(Inner);
 
 enum Inner {
     Empty,
-    Buffer(Cursor<Cow<'static, [u8]>>),
+    Buffer(Cursor<Bytes>),
     Reader(Box<dyn Read + Send + Sync>, Option<u64>),
+
+    /// The reading half of an asynchronous pipe, which can be handed to an
+    /// asynchronous request as-is.
+    Pipe(BlockOn<PipeReader>),
+
+    /// An asynchronous body, such as a response body, read by blocking. Kept
+    /// as-is so that [`IdleTimeout`](super::IdleTimeout) can poll it.
+    Async(BlockOn<AsyncBody>),
 }
 
 impl Body {
@@ -59,7 +69,7 @@ impl Body {
         B: AsRef<[u8]> + 'static,
     {
         match_type! {
-            <bytes as Cursor<Cow<'static, [u8]>>> => Self(Inner::Buffer(bytes)),
+            <bytes as Bytes> => Self(Inner::Buffer(Cursor::new(bytes))),
             <bytes as Vec<u8>> => Self::from(bytes),
             <bytes as String> => Self::from(bytes.into_bytes()),
             bytes => Self::from(bytes.as_ref().to_vec()),
@@ -95,6 +105,25 @@ impl Body {
         Self(Inner::Reader(Box::new(reader), Some(length)))
     }
 
+    /// Create a streaming body that reads from the given pipe.
+    pub(super) fn from_pipe(reader: PipeReader) -> Self {
+        Self(Inner::Pipe(BlockOn::new(reader)))
+    }
+
+    /// Create a body that reads from the given asynchronous body by blocking.
+    pub(super) fn from_async(body: AsyncBody) -> Self {
+        Self(Inner::Async(BlockOn::new(body)))
+    }
+
+    /// Get the asynchronous stream that this body reads from, if any.
+    pub(super) fn async_reader_mut(&mut self) -> Option<&mut (dyn AsyncRead + Unpin)> {
+        match &mut self.0 {
+            Inner::Pipe(reader) => Some(reader.get_mut()),
+            Inner::Async(body) => Some(body.get_mut()),
+            _ => None,
+        }
+    }
+
     /// Report if this body is empty.
     ///
     /// This is not necessarily the same as checking for `self.len() ==
@@ -126,6 +155,8 @@ impl Body {
             Inner::Empty => Some(0),
             Inner::Buffer(bytes) => Some(bytes.get_ref().len() as u64),
             Inner::Reader(_, len) => *len,
+            Inner::Pipe(_) => None,
+            Inner::Async(body) => body.get_ref().len(),
         }
     }
 
@@ -142,6 +173,65 @@ impl Body {
         }
     }
 
+    /// Create a copy of this body, if the body is repeatable. Returns `None`
+    /// if the body is a stream created from a reader and cannot be copied.
+    ///
+    /// The copy always starts from the beginning of the content, regardless of
+    /// how much of this body has already been read. Bodies created from a
+    /// static buffer are copied without copying the underlying bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::Body;
+    ///
+    /// let body = Body::from("hello world");
+    /// assert!(body.try_clone().is_some());
+    ///
+    /// let body = Body::from_reader(std::io::empty());
+    /// assert!(body.try_clone().is_none());
+    /// ```
+    pub fn try_clone(&self) -> Option<Self> {
+        match &self.0 {
+            Inner::Empty => Some(Self(Inner::Empty)),
+            Inner::Buffer(cursor) => Some(Self(Inner::Buffer(Cursor::new(
+                cursor.get_ref().clone(),
+            )))),
+            Inner::Reader(_, _) | Inner::Pipe(_) | Inner::Async(_) => None,
+        }
+    }
+
+    /// Create a [`ReusableBody`] containing the contents of this body, if the
+    /// body is repeatable. Returns `None` if the body is a stream created from
+    /// a reader.
+    ///
+    /// Unlike [`Body::try_clone`], the bytes of the body are copied at most
+    /// once, after which any number of bodies can be created from the returned
+    /// [`ReusableBody`] without copying. This is useful for sending the same
+    /// payload in many requests.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Body, Request};
+    ///
+    /// let body = Body::from(vec![0; 1024]).reusable().unwrap();
+    ///
+    /// for _ in 0..3 {
+    ///     Request::post("https://httpbin.org/post")
+    ///         .body(body.body())?
+    ///         .send()?;
+    /// }
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    pub fn reusable(&self) -> Option<ReusableBody> {
+        match &self.0 {
+            Inner::Empty => Some(ReusableBody::empty()),
+            Inner::Buffer(cursor) => Some(ReusableBody::new(cursor.get_ref())),
+            Inner::Reader(_, _) | Inner::Pipe(_) | Inner::Async(_) => None,
+        }
+    }
+
     /// Convert this body into an asynchronous one.
     ///
     /// Turning a synchronous operation into an asynchronous one can be quite
@@ -172,6 +262,8 @@ impl Body {
                     }),
                 )
             }
+            Inner::Pipe(reader) => (AsyncBody::from_reader(reader.into_inner()), None),
+            Inner::Async(body) => (body.into_inner(), None),
         }
     }
 }
@@ -182,6 +274,8 @@ impl Read for Body {
             Inner::Empty => Ok(0),
             Inner::Buffer(cursor) => cursor.read(buf),
             Inner::Reader(reader, _) => reader.read(buf),
+            Inner::Pipe(reader) => reader.read(buf),
+            Inner::Async(body) => body.read(buf),
         }
     }
 }
@@ -200,7 +294,7 @@ impl From<()> for Body {
 
 impl From<Vec<u8>> for Body {
     fn from(body: Vec<u8>) -> Self {
-        Self(Inner::Buffer(Cursor::new(Cow::Owned(body))))
+        Self(Inner::Buffer(Cursor::new(Bytes::Owned(body))))
     }
 }
 
@@ -336,4 +430,25 @@ mod tests {
 
         assert_eq!(body.reset(), false);
     }
+
+    #[test]
+    fn clone_memory_body() {
+        let mut body = Body::from(b"hello world".to_vec());
+        let mut copy = body.try_clone().unwrap();
+
+        assert_eq!(copy.len(), Some(11));
+
+        let mut buf = String::new();
+        body.read_to_string(&mut buf).unwrap();
+        copy.read_to_string(&mut buf).unwrap();
+
+        assert_eq!(buf, "hello worldhello world");
+    }
+
+    #[test]
+    fn cannot_clone_reader() {
+        let body = Body::from_reader(std::io::empty());
+
+        assert!(body.try_clone().is_none());
+    }
 }
@@ -2,14 +2,35 @@ use super::{
     dns::{DnsCache, ResolveMap},
     request::SetOpt,
 };
-use std::time::Duration;
+use std::{collections::HashMap, ffi::CString, time::Duration};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub(crate) struct ClientConfig {
     pub(crate) connection_cache_ttl: Option<Duration>,
+    pub(crate) max_connection_age: Option<Duration>,
     pub(crate) close_connections: bool,
     pub(crate) dns_cache: Option<DnsCache>,
     pub(crate) dns_resolve: Option<ResolveMap>,
+    pub(crate) retry_on_connection_reset: bool,
+    pub(crate) close_on_body_error: bool,
+
+    /// Proxies to use for specific URI schemes, keyed by lowercase scheme.
+    pub(crate) scheme_proxies: HashMap<String, Option<http::Uri>>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            connection_cache_ttl: None,
+            max_connection_age: None,
+            close_connections: false,
+            dns_cache: None,
+            dns_resolve: None,
+            retry_on_connection_reset: true,
+            close_on_body_error: true,
+            scheme_proxies: HashMap::new(),
+        }
+    }
 }
 
 impl SetOpt for ClientConfig {
@@ -18,6 +39,10 @@ impl SetOpt for ClientConfig {
             easy.maxage_conn(ttl)?;
         }
 
+        if let Some(age) = self.max_connection_age {
+            set_max_lifetime_conn(easy, age)?;
+        }
+
         if let Some(cache) = self.dns_cache.as_ref() {
             cache.set_opt(easy)?;
         }
@@ -29,3 +54,49 @@ impl SetOpt for ClientConfig {
         easy.forbid_reuse(self.close_connections)
     }
 }
+
+#[allow(unsafe_code)]
+fn set_max_lifetime_conn<H>(
+    easy: &mut curl::easy::Easy2<H>,
+    max_lifetime: Duration,
+) -> Result<(), curl::Error> {
+    const CURLOPT_MAXLIFETIME_CONN: curl_sys::CURLoption = curl_sys::CURLOPTTYPE_LONG + 314;
+
+    // Curl only accepts whole seconds and treats zero as no limit, so round up
+    // to at least one second.
+    let secs = (max_lifetime.as_secs() + u64::from(max_lifetime.subsec_nanos() > 0)).max(1);
+
+    unsafe {
+        match curl_sys::curl_easy_setopt(
+            easy.raw(),
+            CURLOPT_MAXLIFETIME_CONN,
+            secs as std::os::raw::c_long,
+        ) {
+            curl_sys::CURLE_OK => Ok(()),
+            code => Err(curl::Error::new(code)),
+        }
+    }
+}
+
+/// Load the Alt-Svc cache of a handle from the given file.
+#[allow(unsafe_code)]
+pub(crate) fn set_altsvc_cache_file<H>(
+    easy: &mut curl::easy::Easy2<H>,
+    path: &std::path::Path,
+) -> Result<(), curl::Error> {
+    const CURLOPT_ALTSVC: curl_sys::CURLoption = curl_sys::CURLOPTTYPE_OBJECTPOINT + 287;
+
+    let path = path
+        .to_str()
+        .and_then(|path| CString::new(path).ok())
+        .ok_or_else(|| curl::Error::new(curl_sys::CURLE_BAD_FUNCTION_ARGUMENT))?;
+
+    // Curl copies the string and reads the file immediately, and writes the
+    // cache back to the file when the handle is cleaned up.
+    unsafe {
+        match curl_sys::curl_easy_setopt(easy.raw(), CURLOPT_ALTSVC, path.as_ptr()) {
+            curl_sys::CURLE_OK => Ok(()),
+            code => Err(curl::Error::new(code)),
+        }
+    }
+}
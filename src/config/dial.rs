@@ -52,6 +52,9 @@ enum Inner {
 
     #[cfg(unix)]
     UnixSocket(std::path::PathBuf),
+
+    #[cfg(target_os = "linux")]
+    AbstractUnixSocket(Vec<u8>),
 }
 
 impl Dialer {
@@ -102,6 +105,29 @@ impl Dialer {
     pub fn unix_socket(path: impl Into<std::path::PathBuf>) -> Self {
         Self(Inner::UnixSocket(path.into()))
     }
+
+    /// Connect to a Unix socket in the Linux abstract socket namespace.
+    ///
+    /// The name given should not include the leading null byte that
+    /// distinguishes abstract socket addresses from filesystem paths, as it is
+    /// added automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::config::Dialer;
+    ///
+    /// # #[cfg(target_os = "linux")]
+    /// let dialer = Dialer::abstract_unix_socket("my-service");
+    /// ```
+    ///
+    /// # Availability
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn abstract_unix_socket(name: impl Into<Vec<u8>>) -> Self {
+        Self(Inner::AbstractUnixSocket(name.into()))
+    }
 }
 
 impl Default for Dialer {
@@ -185,6 +211,15 @@ impl SetOpt for Dialer {
             _ => None,
         })?;
 
+        // Curl stores both kinds of Unix socket in the same option, so this
+        // must come after setting the path above.
+        #[cfg(target_os = "linux")]
+        {
+            if let Inner::AbstractUnixSocket(name) = &self.0 {
+                easy.abstract_unix_socket(name)?;
+            }
+        }
+
         Ok(())
     }
 }
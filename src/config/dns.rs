@@ -71,13 +71,32 @@ impl ResolveMap {
     }
 
     /// Add a DNS mapping for a given host and port pair.
+    ///
+    /// Adding more than one address for the same host and port pair maps the
+    /// pair to all of the given addresses, which are tried in the order they
+    /// were added, unless
+    /// [`Configurable::dns_shuffle_addresses`](super::Configurable::dns_shuffle_addresses)
+    /// is enabled.
+    ///
+    /// Note that in earlier versions, adding another address for the same host
+    /// and port pair replaced the previous address instead. To replace an
+    /// address, build a new map without it.
     pub fn add<H, A>(mut self, host: H, port: u16, addr: A) -> Self
     where
         H: AsRef<str>,
         A: Into<IpAddr>,
     {
-        self.0
-            .push(format!("{}:{}:{}", host.as_ref(), port, addr.into()));
+        let prefix = format!("{}:{}:", host.as_ref(), port);
+        let addr = addr.into();
+
+        match self.0.iter_mut().find(|entry| entry.starts_with(&prefix)) {
+            Some(entry) => {
+                entry.push(',');
+                entry.push_str(&addr.to_string());
+            }
+            None => self.0.push(format!("{}{}", prefix, addr)),
+        }
+
         self
     }
 }
@@ -0,0 +1,94 @@
+//! Configuration for copying request extensions onto responses.
+
+use http::Extensions;
+use std::any::TypeId;
+
+/// A function that copies a single extension type from one set of extensions
+/// to another, if present.
+type Copier = fn(&Extensions, &mut Extensions);
+
+/// The set of request extension types that should be copied onto the
+/// corresponding response.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PropagatedExtensions(Vec<(TypeId, Copier)>);
+
+impl PropagatedExtensions {
+    /// Register an extension type to be propagated.
+    pub(crate) fn add<T>(&mut self)
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let type_id = TypeId::of::<T>();
+
+        // Registering the same type more than once has no additional effect.
+        if !self.0.iter().any(|(id, _)| *id == type_id) {
+            self.0.push((type_id, copy::<T>));
+        }
+    }
+
+    /// Register all extension types registered in another set.
+    pub(crate) fn extend(&mut self, other: &Self) {
+        for &(type_id, copier) in &other.0 {
+            if !self.0.iter().any(|(id, _)| *id == type_id) {
+                self.0.push((type_id, copier));
+            }
+        }
+    }
+
+    /// Copy all registered extension types present in `from` into `to`.
+    pub(crate) fn copy(&self, from: &Extensions, to: &mut Extensions) {
+        for (_, copier) in &self.0 {
+            copier(from, to);
+        }
+    }
+}
+
+fn copy<T>(from: &Extensions, to: &mut Extensions)
+where
+    T: Clone + Send + Sync + 'static,
+{
+    if let Some(value) = from.get::<T>() {
+        to.insert(value.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct A(u32);
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct B(u32);
+
+    #[test]
+    fn only_registered_types_are_copied() {
+        let mut propagated = PropagatedExtensions::default();
+        propagated.add::<A>();
+
+        let mut from = Extensions::new();
+        from.insert(A(1));
+        from.insert(B(2));
+
+        let mut to = Extensions::new();
+        propagated.copy(&from, &mut to);
+
+        assert_eq!(to.get::<A>(), Some(&A(1)));
+        assert_eq!(to.get::<B>(), None);
+    }
+
+    #[test]
+    fn extending_combines_registered_types() {
+        let mut propagated = PropagatedExtensions::default();
+        propagated.add::<A>();
+
+        let mut other = PropagatedExtensions::default();
+        other.add::<A>();
+        other.add::<B>();
+
+        propagated.extend(&other);
+
+        assert_eq!(propagated.0.len(), 2);
+    }
+}
@@ -14,22 +14,34 @@
 // handle.
 
 use self::{proxy::Proxy, request::SetOpt};
-use crate::auth::{Authentication, Credentials};
+use crate::auth::{Authentication, Credentials, Netrc, NetrcFile};
 use curl::easy::Easy2;
-use std::{net::IpAddr, time::Duration};
+use std::{
+    fmt,
+    net::IpAddr,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 pub(crate) mod client;
 pub(crate) mod dial;
 pub(crate) mod dns;
+pub(crate) mod extensions;
 pub(crate) mod proxy;
 pub(crate) mod redirect;
 pub(crate) mod request;
 pub(crate) mod ssl;
+pub(crate) mod tcp;
+pub(crate) mod throttle;
 
+#[cfg(feature = "unstable-raw-handle")]
+pub use crate::handler::RequestHandler;
 pub use dial::{Dialer, DialerParseError};
 pub use dns::{DnsCache, ResolveMap};
-pub use redirect::RedirectPolicy;
-pub use ssl::{CaCertificate, ClientCertificate, PrivateKey, SslOption};
+pub use redirect::{PostRedirectPolicy, RedirectPolicy};
+pub use ssl::{CaCertificate, ClientCertificate, PrivateKey, RevocationCheck, SslOption};
+pub use throttle::ThrottlePolicy;
 
 /// Provides additional methods when building a request for configuring various
 /// execution-related options on how the request should be sent.
@@ -80,6 +92,156 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Specify a point in time by which a complete request/response cycle must
+    /// be finished before being aborted.
+    ///
+    /// This behaves like [`Configurable::timeout`], except that the limit is
+    /// given as an absolute deadline rather than relative to the start of the
+    /// request. The time remaining until the deadline is computed when the
+    /// request is sent, which is convenient when several requests share a
+    /// single time budget. If a timeout is also set, whichever expires first
+    /// applies.
+    ///
+    /// If the deadline has already passed when the request is sent, the
+    /// request fails immediately with a
+    /// [`Timeout`](crate::error::ErrorKind::Timeout) error without being
+    /// sent.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// // Both requests must be done within 10 seconds in total.
+    /// let deadline = Instant::now() + Duration::from_secs(10);
+    ///
+    /// let first = Request::get("https://example.org/first")
+    ///     .deadline(deadline)
+    ///     .body(())?
+    ///     .send()?;
+    ///
+    /// let second = Request::get("https://example.org/second")
+    ///     .deadline(deadline)
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn deadline(self, deadline: Instant) -> Self {
+        self.with_config(move |config| {
+            config.deadline = Some(deadline);
+        })
+    }
+
+    /// Specify a maximum amount of time to wait for the response headers to be
+    /// received before aborting the request.
+    ///
+    /// Unlike [`Configurable::timeout`], this timeout only covers the time from
+    /// the start of the request until the headers of the response have been
+    /// received. Once the headers have arrived, reading the response body is
+    /// not bounded by this timeout, which makes it suitable for long downloads
+    /// or streaming responses where only the time to first response should be
+    /// limited. Both timeouts may be used together.
+    ///
+    /// If the response headers are not received in time, the request is
+    /// aborted with a [`Timeout`](crate::error::ErrorKind::Timeout) error.
+    ///
+    /// If not set, no headers timeout will be enforced.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    /// use std::time::Duration;
+    ///
+    /// // Wait at most 5 seconds for the server to respond, but allow the body
+    /// // to take as long as it needs.
+    /// let mut response = Request::get("https://httpbin.org/drip?duration=10")
+    ///     .headers_timeout(Duration::from_secs(5))
+    ///     .body(())?
+    ///     .send()?;
+    ///
+    /// response.consume()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn headers_timeout(self, timeout: Duration) -> Self {
+        self.with_config(move |config| {
+            config.headers_timeout = Some(timeout);
+        })
+    }
+
+    /// Configure whether the connection used by a request should be closed once
+    /// the request is complete instead of being returned to the connection
+    /// cache for reuse.
+    ///
+    /// This is mostly useful on individual requests: setting this to `true`
+    /// makes sure no later request reuses the connection, such as after
+    /// logging out of a session, without having to disable connection reuse
+    /// for the entire client. Setting this to `false` allows the connection to
+    /// be reused even if the client was configured not to cache connections
+    /// using [`HttpClientBuilder::connection_cache_size`](crate::HttpClientBuilder::connection_cache_size).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::post("https://example.org/logout")
+    ///     .close_connection(true)
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn close_connection(self, close: bool) -> Self {
+        self.with_config(move |config| {
+            config.close_connection = Some(close);
+        })
+    }
+
+    /// Set a maximum total size in bytes for the response headers.
+    ///
+    /// The size includes the status line and the line endings of each header,
+    /// and is counted across all responses received during the request,
+    /// including informational responses and trailers. If the server sends
+    /// more than this, the request is aborted with a
+    /// [`ResponseHeadersTooLarge`](crate::error::ErrorKind::ResponseHeadersTooLarge)
+    /// error. This is useful as a defense against servers flooding the client
+    /// with headers.
+    ///
+    /// If not set, only curl's own limit of 300 KiB applies.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, HttpClient};
+    ///
+    /// let client = HttpClient::builder()
+    ///     .max_response_header_size(64 * 1024)
+    ///     .max_response_headers(100)
+    ///     .build()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn max_response_header_size(self, bytes: usize) -> Self {
+        self.with_config(move |config| {
+            config.max_response_header_size = Some(bytes);
+        })
+    }
+
+    /// Set a maximum number of response headers.
+    ///
+    /// Like [`Configurable::max_response_header_size`], the number is counted
+    /// across all responses received during the request. If the server sends
+    /// more headers than this, the request is aborted with a
+    /// [`ResponseHeadersTooLarge`](crate::error::ErrorKind::ResponseHeadersTooLarge)
+    /// error.
+    ///
+    /// If not set, the number of headers is not limited.
+    fn max_response_headers(self, count: usize) -> Self {
+        self.with_config(move |config| {
+            config.max_response_headers = Some(count);
+        })
+    }
+
     /// Set a timeout for establishing connections to a host.
     ///
     /// If not set, a default connect timeout of 300 seconds will be used.
@@ -130,6 +292,49 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Set the application protocols to advertise to the server using ALPN
+    /// during the TLS handshake.
+    ///
+    /// Some servers select their behavior based on the protocols offered, for
+    /// example by only speaking HTTP/1.1 to clients that do not offer `h2`.
+    /// The protocols curl offers are tied to the HTTP versions it is allowed to
+    /// use, so only the identifiers `h2` and `http/1.1` are supported:
+    ///
+    /// - If `h2` is not in the list, the request is restricted to HTTP/1.1 and
+    ///   only `http/1.1` is offered. This overrides
+    ///   [`Configurable::version_negotiation`].
+    /// - If neither identifier is in the list, ALPN is disabled entirely.
+    /// - If `h2` is in the list, the protocols offered are whatever the
+    ///   version negotiation allows, which always includes `http/1.1` when
+    ///   falling back to HTTP/1.1 is allowed.
+    ///
+    /// Other identifiers are ignored. The protocol selected by the server can
+    /// be inspected using
+    /// [`ResponseExt::negotiated_alpn`](crate::ResponseExt::negotiated_alpn).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("https://example.org")
+    ///     .alpn_protocols(vec!["http/1.1"])
+    ///     .body(())?
+    ///     .send()?;
+    ///
+    /// println!("{:?}", response.negotiated_alpn());
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn alpn_protocols<I, T>(self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.with_config(move |config| {
+            config.alpn_protocols = Some(protocols.into_iter().map(T::into).collect());
+        })
+    }
+
     /// Set a policy for automatically following server redirects.
     ///
     /// The default is to not follow redirects.
@@ -159,6 +364,35 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Set how `POST` requests are redirected by a `301` or `302` response.
+    ///
+    /// By default, such redirects are followed using a `GET` request, as is
+    /// common practice. Use [`PostRedirectPolicy::PreservePost`] to send the
+    /// same `POST` request to the new location instead. This only has an
+    /// effect if a [`RedirectPolicy`] that follows redirects is also set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{
+    ///     config::{PostRedirectPolicy, RedirectPolicy},
+    ///     prelude::*,
+    ///     Request,
+    /// };
+    ///
+    /// let response = Request::post("https://legacy.example.org/submit")
+    ///     .redirect_policy(RedirectPolicy::Follow)
+    ///     .post_redirect_policy(PostRedirectPolicy::PreservePost)
+    ///     .body("name=value")?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn post_redirect_policy(self, policy: PostRedirectPolicy) -> Self {
+        self.with_config(move |config| {
+            config.post_redirect_policy = Some(policy);
+        })
+    }
+
     /// Update the `Referer` header automatically when following redirects.
     fn auto_referer(self) -> Self {
         self.with_config(move |config| {
@@ -166,12 +400,189 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Set a function to inspect each redirect before it is followed, and
+    /// optionally reject it.
+    ///
+    /// The function is called with the URI of the request that was redirected
+    /// and the URI it is being redirected to. If it returns `false`, the
+    /// redirect is not followed and the request fails with
+    /// [`ErrorKind::RedirectRejected`](crate::error::ErrorKind::RedirectRejected).
+    /// This only has an effect if a [`RedirectPolicy`] that follows redirects
+    /// is also set.
+    ///
+    /// This is useful for preventing server-side request forgery when
+    /// requesting user-supplied URIs, for example by refusing redirects to
+    /// private or link-local addresses.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{config::RedirectPolicy, prelude::*, Request};
+    ///
+    /// let response = Request::get("https://example.org")
+    ///     .redirect_policy(RedirectPolicy::Follow)
+    ///     .on_redirect(|_from, to| to.scheme_str() == Some("https"))
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn on_redirect<F>(self, callback: F) -> Self
+    where
+        F: Fn(&http::Uri, &http::Uri) -> bool + Send + Sync + 'static,
+    {
+        self.with_config(move |config| {
+            config.on_redirect = Some(RedirectCallback(Arc::new(callback)));
+        })
+    }
+
+    /// Set the value of the `Referer` header to send with the request.
+    ///
+    /// If [`auto_referer`](Configurable::auto_referer) is also enabled, this
+    /// is only used for the initial request; requests made while following
+    /// redirects use the URI of the previous request instead. A `Referer`
+    /// header set explicitly on the request takes precedence over this
+    /// setting.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("https://example.org/page")
+    ///     .referer("https://example.org/")
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn referer(self, referer: impl Into<String>) -> Self {
+        let referer = referer.into();
+
+        self.with_config(move |config| {
+            config.referer = Some(referer);
+        })
+    }
+
+    /// Send the request path exactly as given, without normalizing it.
+    ///
+    /// By default, `.` and `..` segments in the request path are resolved
+    /// before the request is sent, so a request for `/a/../b` is sent as `/b`.
+    /// Some APIs such as object stores allow dot segments as part of literal
+    /// keys, which would be changed by this normalization. Enabling this option
+    /// sends the path verbatim instead.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("https://example.org/bucket/../key")
+    ///     .path_as_is(true)
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn path_as_is(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.path_as_is = Some(enable);
+        })
+    }
+
+    /// Set whether to guess the `Content-Type` of request bodies that do not
+    /// have one set explicitly.
+    ///
+    /// When enabled, if a request with a body does not have a `Content-Type`
+    /// header, the first few bytes of the body are inspected for the magic
+    /// numbers of common file formats, such as PNG or PDF. If a known format is
+    /// recognized, then the corresponding `Content-Type` header is added to
+    /// the request. Otherwise the request is sent without one. Bodies created
+    /// from a reader (such as a [`File`](std::fs::File)) are buffered in order
+    /// to do this, and file names are not taken into account.
+    ///
+    /// This is disabled by default, so that the headers sent are predictable.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Body, Request};
+    /// use std::fs::File;
+    ///
+    /// let response = Request::put("https://example.org/upload/image")
+    ///     .guess_content_type(true)
+    ///     .body(Body::from(File::open("image.png")?))?
+    ///     .send()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn guess_content_type(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.guess_content_type = Some(enable);
+        })
+    }
+
+    /// Set whether requests for `file:` URIs are allowed, answered by reading
+    /// the named local file.
+    ///
+    /// The response has a `200 OK` status and the file contents as its body,
+    /// or a `404 Not Found` status if there is no such file. Only `GET` and
+    /// `HEAD` requests are supported, and the host must be `localhost`.
+    ///
+    /// This is disabled by default, and `file:` URIs are rejected with an
+    /// [`InvalidRequest`](crate::error::ErrorKind::InvalidRequest) error.
+    /// Only enable this if the URIs being requested are trusted, since
+    /// otherwise anyone supplying a URI can read files on the local machine.
+    /// Redirects to `file:` URIs are never followed, regardless of this
+    /// setting.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let mut response = Request::get("file://localhost/etc/hosts")
+    ///     .allow_file_uris(true)
+    ///     .body(())?
+    ///     .send()?;
+    /// println!("{}", response.text()?);
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn allow_file_uris(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.allow_file_uris = Some(enable);
+        })
+    }
+
     /// Set a cookie jar to use to accept, store, and supply cookies for
     /// incoming responses and outgoing requests.
     ///
     /// A cookie jar can be shared across multiple requests or with an entire
     /// client, allowing cookies to be persisted across multiple requests.
     ///
+    /// A cookie jar set on an individual request is used instead of the
+    /// client's cookie jar for that request. This allows a single client to be
+    /// shared between multiple independent cookie sessions, such as when
+    /// acting on behalf of several accounts at once.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{cookies::CookieJar, prelude::*, HttpClient, Request};
+    ///
+    /// let client = HttpClient::builder()
+    ///     .cookie_jar(CookieJar::new())
+    ///     .build()?;
+    ///
+    /// // Keep the cookies for this account separate from the client's jar.
+    /// let account_jar = CookieJar::new();
+    ///
+    /// let response = client.send(
+    ///     Request::get("https://example.org/login")
+    ///         .cookie_jar(account_jar.clone())
+    ///         .body(())?,
+    /// )?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    ///
     /// # Availability
     ///
     /// This method is only available when the [`cookies`](index.html#cookies)
@@ -200,6 +611,76 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Ask the server not to compress the response body.
+    ///
+    /// When enabled, requests are sent with `Accept-Encoding: identity` unless
+    /// an `Accept-Encoding` header is set explicitly, and
+    /// [automatic decompression](Configurable::automatic_decompression) is
+    /// disabled, so the response body is returned exactly as it was sent by
+    /// the server. This is useful when relaying responses as is, such as in a
+    /// proxy or cache. By default, all supported encodings are advertised
+    /// instead.
+    ///
+    /// Servers are not required to honor this, so a response might still have
+    /// a `Content-Encoding` other than `identity`, in which case its body is
+    /// returned still encoded.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("https://example.org")
+    ///     .accept_encoding_identity(true)
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn accept_encoding_identity(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.accept_encoding_identity = Some(enable);
+        })
+    }
+
+    /// Set the character encoding to use when decoding a response body as
+    /// text, if the response does not specify one.
+    ///
+    /// The charset given in the `Content-Type` response header, or a byte
+    /// order mark at the start of the body, always take precedence over this
+    /// setting. The charset can be given as any label recognized by the
+    /// [Encoding Standard](https://encoding.spec.whatwg.org/#names-and-labels),
+    /// such as `windows-1252`. Unknown labels are ignored.
+    ///
+    /// The default is UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, HttpClient};
+    ///
+    /// let client = HttpClient::builder()
+    ///     .default_charset("windows-1252")
+    ///     .build()?;
+    ///
+    /// let text = client.get("https://example.org")?.text()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the
+    /// [`text-decoding`](../index.html#text-decoding) feature is enabled.
+    #[cfg(feature = "text-decoding")]
+    fn default_charset(self, charset: impl Into<String>) -> Self {
+        let charset = crate::text::DefaultCharset(charset.into());
+
+        self.with_config(move |config| {
+            config.default_charset = Some(charset);
+        })
+    }
+
     /// Set one or more default HTTP authentication methods to attempt to use
     /// when authenticating with the server.
     ///
@@ -238,6 +719,52 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Configure whether credentials should be read from a `.netrc` file.
+    ///
+    /// By default the netrc file is ignored. The file is read from `.netrc` in
+    /// the user's home directory, as given by the `HOME` environment variable
+    /// (or `USERPROFILE` on Windows). Use [`Configurable::netrc_file`] to read
+    /// from a different location.
+    ///
+    /// Credentials explicitly set using [`Configurable::credentials`] always
+    /// take precedence over credentials from the netrc file. Like any other
+    /// credentials, this setting will do nothing unless you also set one or
+    /// more authentication methods using [`Configurable::authentication`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{
+    ///     auth::{Authentication, Netrc},
+    ///     prelude::*,
+    ///     HttpClient,
+    /// };
+    ///
+    /// let client = HttpClient::builder()
+    ///     .authentication(Authentication::basic())
+    ///     .netrc(Netrc::Optional)
+    ///     .build()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn netrc(self, netrc: Netrc) -> Self {
+        self.with_config(move |config| {
+            config.netrc = Some(netrc);
+        })
+    }
+
+    /// Set a custom location of the `.netrc` file to read credentials from.
+    ///
+    /// This setting will do nothing unless reading the netrc file is enabled
+    /// using [`Configurable::netrc`]. The file is read once when this is
+    /// called to find out which hosts it has entries for, so with
+    /// [`Netrc::Required`](crate::auth::Netrc::Required), entries added to
+    /// the file afterwards are not noticed.
+    fn netrc_file(self, path: impl Into<PathBuf>) -> Self {
+        self.with_config(move |config| {
+            config.netrc_file = Some(NetrcFile::new(path.into()));
+        })
+    }
+
     /// Enable TCP keepalive with a given probe interval.
     fn tcp_keepalive(self, interval: Duration) -> Self {
         self.with_config(move |config| {
@@ -245,10 +772,206 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Keep idle connections alive by sending probes after the given interval
+    /// of inactivity, and then repeatedly at the same interval.
+    ///
+    /// This is intended for long-lived connections, such as HTTP/2 connections
+    /// carrying streaming responses, that pass through proxies or firewalls
+    /// which drop connections that have been idle for too long.
+    ///
+    /// Curl cannot send HTTP/2 `PING` frames on connections driven by Isahc,
+    /// so TCP keepalive probes are used instead, regardless of the negotiated
+    /// HTTP version. Intermediaries that only consider HTTP traffic when
+    /// detecting idle connections may therefore still close them. If
+    /// [`Configurable::tcp_keepalive`] is also set, its probe interval is used
+    /// instead.
+    ///
+    /// The interval is rounded down to whole seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, HttpClient};
+    /// use std::time::Duration;
+    ///
+    /// let client = HttpClient::builder()
+    ///     .http2_keepalive(Duration::from_secs(30))
+    ///     .build()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn http2_keepalive(self, interval: Duration) -> Self {
+        self.with_config(move |config| {
+            config.http2_keepalive = Some(interval);
+        })
+    }
+
+    /// Set the `TCP_USER_TIMEOUT` option on connect, which is the maximum
+    /// amount of time that sent data may remain unacknowledged before the
+    /// connection is considered dead and closed.
+    ///
+    /// This detects dead peers much faster than TCP keepalive alone, which is
+    /// useful for long-lived connections on unreliable networks. Since
+    /// connections are reused, the timeout applies for the lifetime of a new
+    /// connection, including any later requests that reuse it.
+    ///
+    /// This option is only supported on Linux, and does nothing on other
+    /// platforms. The timeout is set in whole milliseconds.
+    fn tcp_user_timeout(self, timeout: Duration) -> Self {
+        self.with_config(move |config| {
+            config.tcp_user_timeout = Some(tcp::TcpUserTimeout(timeout));
+        })
+    }
+
     /// Enables the `TCP_NODELAY` option on connect.
+    ///
+    /// This is equivalent to `tcp_nodelay_enabled(true)`.
     fn tcp_nodelay(self) -> Self {
+        self.tcp_nodelay_enabled(true)
+    }
+
+    /// Enable or disable the `TCP_NODELAY` option on connect.
+    ///
+    /// Setting this on a request overrides the value configured on the client,
+    /// which makes it possible to disable `TCP_NODELAY` for an individual
+    /// request, such as a bulk upload where latency does not matter, while
+    /// keeping it enabled for all other requests.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, HttpClient, Request};
+    /// use std::time::Duration;
+    ///
+    /// let client = HttpClient::builder()
+    ///     .tcp_keepalive(Duration::from_secs(60))
+    ///     .tcp_nodelay()
+    ///     .build()?;
+    ///
+    /// let request = Request::put("https://example.org/upload")
+    ///     .tcp_nodelay_enabled(false)
+    ///     .body(vec![0; 1024 * 1024])?;
+    ///
+    /// let response = client.send(request)?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn tcp_nodelay_enabled(self, enable: bool) -> Self {
         self.with_config(move |config| {
-            config.tcp_nodelay = Some(true);
+            config.tcp_nodelay = Some(enable);
+        })
+    }
+
+    /// Send a [PROXY protocol](https://www.haproxy.org/download/1.8/doc/proxy-protocol.txt)
+    /// version 1 header at the start of each new connection.
+    ///
+    /// The header tells the server the original source and destination
+    /// addresses of the connection, and is expected by servers sitting behind
+    /// load balancers such as HAProxy that preserve client addresses this way.
+    /// Only enable this when talking to such a server, since a server that
+    /// does not expect the header will reject the connection.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("http://backend.example.org")
+    ///     .haproxy_protocol(true)
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn haproxy_protocol(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.haproxy_protocol = Some(enable);
+        })
+    }
+
+    /// Accept responses with the given non-standard status lines as if they
+    /// were `HTTP/1.0 200` responses.
+    ///
+    /// Some servers, such as SHOUTcast-style streaming media servers, respond
+    /// with a status line like `ICY 200 OK` that would otherwise be rejected as
+    /// an invalid response. Each alias is matched against the beginning of the
+    /// status line. The original status line can be retrieved using
+    /// [`ResponseExt::raw_status_line`](crate::ResponseExt::raw_status_line).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("http://radio.example.org:8000/stream")
+    ///     .http_200_aliases(vec!["ICY 200 OK"])
+    ///     .body(())?
+    ///     .send()?;
+    ///
+    /// println!("{:?}", response.raw_status_line());
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn http_200_aliases<I, T>(self, aliases: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.with_config(move |config| {
+            config.http_200_aliases = Some(aliases.into_iter().map(T::into).collect());
+        })
+    }
+
+    /// Set the priority of the request when waiting for a connection.
+    ///
+    /// If the client has a limit on the number of connections set with
+    /// [`HttpClientBuilder::max_connections`](crate::HttpClientBuilder::max_connections)
+    /// and as many requests as the limit are executing, requests with a
+    /// priority wait until an active request completes. Waiting requests with
+    /// a higher priority are started before those with a lower priority, and
+    /// requests with the same priority are started in the order they were
+    /// sent. This has no effect on requests waiting because of
+    /// [`HttpClientBuilder::max_connections_per_host`](crate::HttpClientBuilder::max_connections_per_host).
+    ///
+    /// The default priority is `0`, the lowest. Requests with the default
+    /// priority are only held back like this while requests with a priority
+    /// are waiting, so that they do not overtake them. Otherwise they are
+    /// started right away and wait for a connection in the order they were
+    /// sent, which allows them to share an existing HTTP/2 connection.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("https://example.org/interactive")
+    ///     .priority(10)
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn priority(self, priority: u8) -> Self {
+        self.with_config(move |config| {
+            config.priority = Some(priority);
+        })
+    }
+
+    /// Allow or prevent curl from using signals.
+    ///
+    /// By default signals are not used, which is the only safe choice when
+    /// requests are executed on a background thread like Isahc does. Without
+    /// signals, DNS lookups using the system resolver cannot be timed out
+    /// unless curl is built with an asynchronous resolver such as c-ares.
+    ///
+    /// Enabling signals lets curl interrupt slow DNS lookups using `SIGALRM`,
+    /// but it is **not** thread safe: the signal may be delivered to any thread
+    /// in the process and curl may jump out of a lookup in a way that corrupts
+    /// state belonging to other threads. Only enable this if your program is
+    /// effectively single-threaded and nothing else in it installs a `SIGALRM`
+    /// handler.
+    ///
+    /// The default value is `false`.
+    fn use_signals(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.use_signals = Some(enable);
         })
     }
 
@@ -307,6 +1030,70 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Configure DNS caching for this request only.
+    ///
+    /// The DNS cache itself is shared by all requests sent by a client, and is
+    /// normally configured using
+    /// [`HttpClientBuilder::dns_cache`](crate::HttpClientBuilder::dns_cache).
+    /// This option instead changes how long a cached entry may be used for
+    /// this request. In particular, [`DnsCache::Disable`] forces the host of
+    /// this request to be resolved again, while other requests keep using the
+    /// cache as normal.
+    ///
+    /// Note that curl also evicts any entries considered too old by this
+    /// request from the shared cache, so other requests may need to resolve
+    /// their hosts again afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{config::DnsCache, prelude::*, Request};
+    ///
+    /// let response = Request::get("https://example.org")
+    ///     .dns_cache(DnsCache::Disable)
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn dns_cache<C>(self, cache: C) -> Self
+    where
+        C: Into<DnsCache>,
+    {
+        let cache = cache.into();
+
+        self.with_config(move |config| {
+            config.dns_cache = Some(cache);
+        })
+    }
+
+    /// Shuffle the addresses that a host name resolves to before connecting.
+    ///
+    /// Normally the addresses are tried in the order returned by the resolver,
+    /// so connections to a host with several addresses usually all go to the
+    /// first one. When enabled, the order is randomized each time the host is
+    /// resolved, which spreads new connections across all of the addresses as
+    /// a basic form of client-side load balancing. Note that addresses are
+    /// taken from the DNS cache when possible, and that existing connections
+    /// are reused regardless of this option.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, HttpClient};
+    ///
+    /// let client = HttpClient::builder()
+    ///     .dns_shuffle_addresses(true)
+    ///     .build()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn dns_shuffle_addresses(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.dns_shuffle_addresses = Some(enable);
+        })
+    }
+
     /// Specify a socket to connect to instead of the using the host and port
     /// defined in the request URI.
     ///
@@ -354,6 +1141,34 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Connect to a Unix socket in the Linux abstract socket namespace instead
+    /// of using the host and port defined in the request URI.
+    ///
+    /// This is a shorthand for [`Configurable::dial`] with
+    /// [`Dialer::abstract_unix_socket`]. The name given should not include the
+    /// leading null byte that distinguishes abstract socket addresses from
+    /// filesystem paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// # #[cfg(target_os = "linux")]
+    /// let request = Request::get("http://localhost/version")
+    ///     .abstract_unix_socket("my-service")
+    ///     .body(())?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    ///
+    /// # Availability
+    ///
+    /// This method is only available on Linux.
+    #[cfg(target_os = "linux")]
+    fn abstract_unix_socket(self, name: impl Into<Vec<u8>>) -> Self {
+        self.dial(Dialer::abstract_unix_socket(name))
+    }
+
     /// Set a proxy to use for requests.
     ///
     /// The proxy protocol is specified by the URI scheme.
@@ -411,55 +1226,174 @@ pub trait Configurable: request::WithRequestConfig {
     ///     // Disable proxy for specified hosts.
     ///     .proxy_blacklist(vec!["a.com", "b.org"])
     ///     .build()?;
-    /// # Ok::<(), isahc::Error>(())
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn proxy_blacklist<I, T>(self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.with_config(move |config| {
+            config.proxy_blacklist = Some(hosts.into_iter().map(T::into).collect());
+        })
+    }
+
+    /// Set one or more HTTP authentication methods to attempt to use when
+    /// authenticating with a proxy.
+    ///
+    /// Depending on the authentication schemes enabled, you will also need to
+    /// set credentials to use for authentication using
+    /// [`Configurable::proxy_credentials`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::{
+    ///     auth::{Authentication, Credentials},
+    ///     prelude::*,
+    ///     HttpClient,
+    /// };
+    ///
+    /// let client = HttpClient::builder()
+    ///     .proxy("http://proxy:80".parse::<http::Uri>()?)
+    ///     .proxy_authentication(Authentication::basic())
+    ///     .proxy_credentials(Credentials::new("clark", "qwerty"))
+    ///     .build()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn proxy_authentication(self, authentication: Authentication) -> Self {
+        self.with_config(move |config| {
+            config.proxy_authentication = Some(Proxy(authentication));
+        })
+    }
+
+    /// Set the credentials to use for proxy authentication.
+    ///
+    /// This setting will do nothing unless you also set one or more proxy
+    /// authentication methods using
+    /// [`Configurable::proxy_authentication`].
+    fn proxy_credentials(self, credentials: Credentials) -> Self {
+        self.with_config(move |config| {
+            config.proxy_credentials = Some(Proxy(credentials));
+        })
+    }
+
+    /// Set custom headers to send to the proxy.
+    ///
+    /// These headers are separate from the regular request headers and are
+    /// sent on the `CONNECT` request used to open a tunnel through an HTTP
+    /// proxy, such as for HTTPS requests. For plain HTTP requests sent through
+    /// a proxy, the proxy receives the request itself, so these headers are
+    /// added to it. This can be useful for authenticating with a proxy that
+    /// uses a custom scheme.
+    ///
+    /// If `connect_only` is `true`, the headers are never sent on requests
+    /// tunneled through the proxy to the origin server. If `false`, they are
+    /// added to tunneled requests too, unless the request already sets a
+    /// header with the same name. They are never added to requests that do
+    /// not go through a proxy, such as those to a host in the proxy
+    /// blacklist.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{http::HeaderMap, prelude::*, HttpClient};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("x-proxy-token", "secret".parse()?);
+    ///
+    /// let client = HttpClient::builder()
+    ///     .proxy(Some("http://proxy:8080".parse()?))
+    ///     .proxy_headers(headers, true)
+    ///     .build()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn proxy_headers(self, headers: http::HeaderMap, connect_only: bool) -> Self {
+        self.with_config(move |config| {
+            config.proxy_headers = Some(proxy::ProxyHeaders {
+                headers,
+                connect_only,
+            });
+        })
+    }
+
+    /// Set a custom SSL/TLS client certificate to present to an HTTPS proxy.
+    ///
+    /// This is the equivalent of [`Configurable::ssl_client_certificate`] for
+    /// the TLS connection to the proxy itself, which is separate from the TLS
+    /// connection to the origin server that may be tunneled through it. It has
+    /// no effect unless the proxy URI uses the `https` scheme.
+    ///
+    /// The default value is none.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{
+    ///     config::{ClientCertificate, PrivateKey},
+    ///     prelude::*,
+    ///     HttpClient,
+    /// };
+    ///
+    /// let client = HttpClient::builder()
+    ///     .proxy(Some("https://proxy:8443".parse()?))
+    ///     .proxy_ssl_client_certificate(ClientCertificate::pem_file(
+    ///         "proxy-client.pem",
+    ///         PrivateKey::pem_file("proxy-key.pem", None),
+    ///     ))
+    ///     .build()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    fn proxy_blacklist<I, T>(self, hosts: I) -> Self
-    where
-        I: IntoIterator<Item = T>,
-        T: Into<String>,
-    {
+    fn proxy_ssl_client_certificate(self, certificate: ClientCertificate) -> Self {
         self.with_config(move |config| {
-            config.proxy_blacklist = Some(hosts.into_iter().map(T::into).collect());
+            config.proxy_ssl_client_certificate = Some(Proxy(certificate));
         })
     }
 
-    /// Set one or more HTTP authentication methods to attempt to use when
-    /// authenticating with a proxy.
+    /// Set a custom SSL/TLS CA certificate bundle to use for verifying the
+    /// certificate of an HTTPS proxy.
     ///
-    /// Depending on the authentication schemes enabled, you will also need to
-    /// set credentials to use for authentication using
-    /// [`Configurable::proxy_credentials`].
+    /// This is the equivalent of [`Configurable::ssl_ca_certificate`] for the
+    /// TLS connection to the proxy itself. It has no effect unless the proxy
+    /// URI uses the `https` scheme.
+    ///
+    /// The default value is none.
     ///
     /// # Examples
     ///
     /// ```
-    /// use isahc::{
-    ///     auth::{Authentication, Credentials},
-    ///     prelude::*,
-    ///     HttpClient,
-    /// };
+    /// use isahc::{config::CaCertificate, prelude::*, HttpClient};
     ///
     /// let client = HttpClient::builder()
-    ///     .proxy("http://proxy:80".parse::<http::Uri>()?)
-    ///     .proxy_authentication(Authentication::basic())
-    ///     .proxy_credentials(Credentials::new("clark", "qwerty"))
+    ///     .proxy(Some("https://proxy:8443".parse()?))
+    ///     .proxy_ssl_ca_certificate(CaCertificate::file("proxy-ca.pem"))
     ///     .build()?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    fn proxy_authentication(self, authentication: Authentication) -> Self {
+    fn proxy_ssl_ca_certificate(self, certificate: CaCertificate) -> Self {
         self.with_config(move |config| {
-            config.proxy_authentication = Some(Proxy(authentication));
+            config.proxy_ssl_ca_certificate = Some(Proxy(certificate));
         })
     }
 
-    /// Set the credentials to use for proxy authentication.
+    /// Set various options that control SSL/TLS behavior when connecting to an
+    /// HTTPS proxy.
     ///
-    /// This setting will do nothing unless you also set one or more proxy
-    /// authentication methods using
-    /// [`Configurable::proxy_authentication`].
-    fn proxy_credentials(self, credentials: Credentials) -> Self {
+    /// This is the equivalent of [`Configurable::ssl_options`] for the TLS
+    /// connection to the proxy itself. It has no effect unless the proxy URI
+    /// uses the `https` scheme. Certificate revocation options are only
+    /// applied by SSL/TLS engines that support them.
+    ///
+    /// The default value is [`SslOption::NONE`].
+    ///
+    /// # Warning
+    ///
+    /// You should think very carefully before using this method. Using *any*
+    /// options that alter how certificates are validated can introduce
+    /// significant security vulnerabilities.
+    fn proxy_ssl_options(self, options: SslOption) -> Self {
         self.with_config(move |config| {
-            config.proxy_credentials = Some(Proxy(credentials));
+            config.proxy_ssl_options = Some(Proxy(options));
         })
     }
 
@@ -481,6 +1415,41 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Set the preferred size of the buffer used for receiving the response
+    /// body, in bytes.
+    ///
+    /// A larger buffer can improve throughput on high-bandwidth, high-latency
+    /// links by reducing the number of reads needed to receive a response.
+    /// This is only a request; curl may use a smaller buffer for some
+    /// transfers. Values outside of the range supported by curl (1 KiB to 10
+    /// MiB) are clamped to the nearest supported size.
+    ///
+    /// The default is 16 KiB.
+    fn download_buffer_size(self, size: usize) -> Self {
+        self.with_config(move |config| {
+            config.download_buffer_size = Some(size);
+        })
+    }
+
+    /// Set the initial HTTP/2 flow-control window size to advertise for each
+    /// response stream, in bytes.
+    ///
+    /// A larger window allows the server to send more of the response body
+    /// before waiting for the client to acknowledge it, which can improve
+    /// throughput of large downloads over high-latency links.
+    ///
+    /// This is only a request, and is ignored (with a debug log message) if
+    /// the version of curl in use does not support it. Current versions of
+    /// curl do not, and instead manage the window themselves: each stream
+    /// starts with a 64 KiB window that grows up to 10 MiB as the response
+    /// body is read. This option has no effect on requests that do not use
+    /// HTTP/2.
+    fn request_initial_window_size(self, size: u32) -> Self {
+        self.with_config(move |config| {
+            config.request_initial_window_size = Some(size);
+        })
+    }
+
     /// Set a custom SSL/TLS client certificate to use for client connections.
     ///
     /// If a format is not supported by the underlying SSL/TLS engine, an error
@@ -556,6 +1525,46 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Add a CA certificate bundle to trust in addition to the default ones,
+    /// rather than replacing them.
+    ///
+    /// This is useful when connecting both to public servers and to servers
+    /// using certificates issued by an internal CA. The given certificates are
+    /// trusted on top of the bundle set with
+    /// [`Configurable::ssl_ca_certificate`], or the system CA bundle if none is
+    /// set. Calling this multiple times adds multiple bundles, though setting
+    /// any on a request replaces those set on the client.
+    ///
+    /// The certificates must be PEM-encoded. They are read from disk once,
+    /// when the client is built or when the first HTTPS request using them is
+    /// sent, and are only used for HTTPS requests.
+    ///
+    /// # Notes
+    ///
+    /// The system CA bundle can only be kept when it is a file, which is the
+    /// case on most Unix-like systems. On other platforms, such as Windows and
+    /// macOS, whether the native certificate store is still used depends on
+    /// the SSL/TLS engine, and it may be replaced instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::{config::CaCertificate, prelude::*, HttpClient};
+    ///
+    /// let client = HttpClient::builder()
+    ///     .ssl_extra_ca_certificate(CaCertificate::file("internal-ca.pem"))
+    ///     .build()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn ssl_extra_ca_certificate(self, certificate: CaCertificate) -> Self {
+        self.with_config(move |config| {
+            config
+                .ssl_extra_ca_certificates
+                .get_or_insert_with(Default::default)
+                .push(certificate);
+        })
+    }
+
     /// Set a list of ciphers to use for SSL/TLS connections.
     ///
     /// The list of valid cipher names is dependent on the underlying SSL/TLS
@@ -613,6 +1622,80 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Configure how to check whether server certificates have been revoked.
+    ///
+    /// This is a clearer alternative to
+    /// [`SslOption::DANGER_ACCEPT_REVOKED_CERTS`] and takes precedence over it
+    /// if both are set. Revocation checking is currently only supported by
+    /// Schannel (the native Windows SSL library); other SSL/TLS engines ignore
+    /// this option.
+    ///
+    /// The default is unset and will result in the SSL/TLS engine default
+    /// being used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::{config::RevocationCheck, prelude::*, HttpClient};
+    ///
+    /// let client = HttpClient::builder()
+    ///     .ssl_revocation(RevocationCheck::BestEffort)
+    ///     .build()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn ssl_revocation(self, check: RevocationCheck) -> Self {
+        self.with_config(move |config| {
+            config.ssl_revocation = Some(check);
+        })
+    }
+
+    /// Enable or disable caching of TLS sessions for resumption.
+    ///
+    /// When enabled, session IDs and tickets from previous TLS handshakes are
+    /// remembered and offered to the server when a new connection is made to
+    /// the same host, allowing an abbreviated handshake that makes reconnecting
+    /// faster. You may wish to disable this if requests sent by the same client
+    /// should not be linkable to one another by the server, such as when
+    /// sending requests on behalf of multiple tenants.
+    ///
+    /// Whether a connection benefited from session resumption can be estimated
+    /// by comparing [`Metrics::secure_connect_time`](crate::Metrics::secure_connect_time)
+    /// across requests.
+    ///
+    /// The default is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::{prelude::*, HttpClient};
+    ///
+    /// let client = HttpClient::builder()
+    ///     .ssl_session_cache(false)
+    ///     .build()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn ssl_session_cache(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.ssl_session_cache = Some(enable);
+        })
+    }
+
+    /// Enable or disable collecting information about the certificate chain
+    /// presented by the server during the TLS handshake.
+    ///
+    /// When enabled, the certificates can be inspected using
+    /// [`ResponseExt::peer_certificates`](crate::ResponseExt::peer_certificates).
+    /// This can be useful for logging which certificate was presented or for
+    /// diagnosing certificate pinning problems.
+    ///
+    /// Certificate information is not supported by all SSL/TLS engines. By
+    /// default this is disabled.
+    fn collect_certificate_info(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.collect_certificate_info = Some(enable);
+        })
+    }
+
     /// Enable or disable sending HTTP header names in Title-Case instead of
     /// lowercase form.
     ///
@@ -629,6 +1712,77 @@ pub trait Configurable: request::WithRequestConfig {
         })
     }
 
+    /// Send only the headers that were set explicitly, plus those required to
+    /// frame the request.
+    ///
+    /// By default, curl and Isahc add a few headers to each request unless
+    /// they are already set, such as `Accept`, `Accept-Encoding` and
+    /// `User-Agent`, and curl may also add `Content-Type` and `Expect` when
+    /// sending a request body. When enabled, none of these are added, so that
+    /// the request contains exactly the headers you set yourself. This is
+    /// useful for APIs that sign the full set of request headers.
+    ///
+    /// Headers that describe the request itself are still sent as needed,
+    /// namely `Host`, and `Content-Length` or `Transfer-Encoding` when the
+    /// request has a body. Automatic decompression of responses still works,
+    /// but servers will only compress responses if you set an
+    /// `Accept-Encoding` header yourself.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("https://api.example.org/orders")
+    ///     .minimal_headers(true)
+    ///     .header("x-signature", "...")
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn minimal_headers(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.minimal_headers = Some(enable);
+        })
+    }
+
+    /// Require the length of the request body to be known before sending it.
+    ///
+    /// When a request body has an unknown length, such as a body created from
+    /// a reader using [`Body::from_reader`](crate::Body::from_reader), it is
+    /// normally sent using `Transfer-Encoding: chunked`. Some servers, such as
+    /// strict HTTP/1.0 servers, do not support chunked encoding at all. When
+    /// enabled, sending a body of unknown length fails immediately with
+    /// [`ErrorKind::RequestBodyLengthRequired`](crate::error::ErrorKind::RequestBodyLengthRequired)
+    /// instead, without connecting to the server.
+    ///
+    /// A body has a known length if it was created from bytes or using
+    /// [`Body::from_reader_sized`](crate::Body::from_reader_sized), or if the
+    /// request has a `Content-Length` header. To send a body of unknown length
+    /// to such a server, read it into memory first.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Body, Request};
+    /// use std::fs::File;
+    ///
+    /// let response = Request::post("http://legacy.example.org/upload")
+    ///     .require_content_length(true)
+    ///     .body(Body::from_reader(File::open("upload.bin")?))?
+    ///     .send()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn require_content_length(self, enable: bool) -> Self {
+        self.with_config(move |config| {
+            config.require_content_length = Some(enable);
+        })
+    }
+
     /// Enable or disable comprehensive per-request metrics collection.
     ///
     /// When enabled, detailed timing metrics will be tracked while a request is
@@ -649,6 +1803,195 @@ pub trait Configurable: request::WithRequestConfig {
             config.enable_metrics = Some(enable);
         })
     }
+
+    /// Attach a label to requests for correlating them in logs and errors.
+    ///
+    /// The tag is recorded on the tracing span Isahc creates for each request,
+    /// so it appears alongside any log events emitted while the request is in
+    /// progress. It is also made available on the response using
+    /// [`ResponseExt::tag`](crate::ResponseExt::tag), and on any error returned
+    /// for the request using [`Error::tag`](crate::Error::tag).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("https://example.org")
+    ///     .tag("fetch-homepage")
+    ///     .body(())?
+    ///     .send()?;
+    ///
+    /// assert_eq!(response.tag(), Some("fetch-homepage"));
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn tag(self, tag: impl Into<String>) -> Self {
+        self.with_config(move |config| {
+            config.tag = Some(tag.into());
+        })
+    }
+
+    /// Copy extensions of the given type from the request onto the response
+    /// returned for it.
+    ///
+    /// By default, extensions attached to a request are not visible on the
+    /// response. Registering an extension type with this method allows
+    /// correlation data such as a request ID to survive the round trip. Only
+    /// types registered this way are copied, so Isahc's own response extensions
+    /// are never overwritten by accident. This method can be called multiple
+    /// times to register multiple types. Types registered on a request are
+    /// copied in addition to those registered on the client.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// struct RequestId(u64);
+    ///
+    /// let response = Request::get("https://example.org")
+    ///     .propagate_extension::<RequestId>()
+    ///     .extension(RequestId(42))
+    ///     .body(())?
+    ///     .send()?;
+    ///
+    /// println!("{:?}", response.extensions().get::<RequestId>());
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn propagate_extension<T>(self) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.with_config(|config| {
+            config
+                .propagate_extensions
+                .get_or_insert_with(Default::default)
+                .add::<T>();
+        })
+    }
+
+    /// Set a function that produces trailer headers to send after the request
+    /// body.
+    ///
+    /// The function is invoked once the request body has been fully written,
+    /// which makes it possible to send values computed while streaming the
+    /// body, such as a content digest. Trailer headers can only be sent when
+    /// the request body is uploaded using chunked transfer encoding, which is
+    /// the case for bodies of unknown length. For all other requests the
+    /// function is never invoked.
+    ///
+    /// It is recommended to also declare the names of any trailer headers that
+    /// will be sent in a `Trailer` request header.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{http::HeaderMap, prelude::*, Body, Request};
+    ///
+    /// let response = Request::put("https://httpbin.org/put")
+    ///     .header("Trailer", "Digest")
+    ///     .request_trailers(|| {
+    ///         let mut trailers = HeaderMap::new();
+    ///         trailers.insert("Digest", "md5=XrY7u+Ae7tCTyyK7j1rNww==".parse().unwrap());
+    ///         trailers
+    ///     })
+    ///     .body(Body::from_reader(b"hello world".as_ref()))?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn request_trailers<F>(self, trailers: F) -> Self
+    where
+        F: Fn() -> http::HeaderMap + Send + Sync + 'static,
+    {
+        self.with_config(move |config| {
+            config.request_trailers = Some(RequestTrailers(Arc::new(trailers)));
+        })
+    }
+
+    /// Set a function to configure the underlying curl handle of a request
+    /// directly.
+    ///
+    /// The function is invoked with the curl easy handle for each request
+    /// after Isahc has applied all of its own configuration, just before the
+    /// request is sent. This allows setting curl options that Isahc does not
+    /// otherwise expose. Any error returned by the function aborts the request
+    /// and is returned to the caller as a curl error.
+    ///
+    /// Options set this way may override or conflict with the configuration
+    /// applied by Isahc, and replacing any of the callbacks installed on the
+    /// handle will break the request in unexpected ways. Only one function can
+    /// be set; setting another replaces the previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Resolve host names using DNS-over-HTTPS.
+    /// let response = Request::get("https://example.org")
+    ///     .with_raw_handle(|easy| easy.doh_url(Some("https://dns.google/dns-query")))
+    ///     .body(())?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the
+    /// [`unstable-raw-handle`](../index.html#unstable-raw-handle) feature is
+    /// enabled. This is an unstable API that exposes implementation details of
+    /// Isahc, and may change or break between patch releases.
+    #[cfg(feature = "unstable-raw-handle")]
+    fn with_raw_handle<F>(self, f: F) -> Self
+    where
+        F: Fn(&mut Easy2<RequestHandler>) -> Result<(), curl::Error> + Send + Sync + 'static,
+    {
+        self.with_config(move |config| {
+            config.raw_handle = Some(RawHandleHook(Arc::new(f)));
+        })
+    }
+
+    /// Compress request bodies using the given content encoding before
+    /// sending them.
+    ///
+    /// When enabled, the request body is compressed and the `Content-Encoding`
+    /// request header is set to the chosen encoding. Since the compressed size
+    /// of a streaming body cannot be known ahead of time, any `Content-Length`
+    /// header is removed and such bodies are sent using chunked transfer
+    /// encoding instead. Bodies already held in memory are compressed up front
+    /// and sent with an accurate length.
+    ///
+    /// Requests without a body, or that already have a `Content-Encoding`
+    /// header set, are sent unchanged.
+    ///
+    /// Note that not all servers accept compressed request bodies, so only
+    /// enable this if you know the server supports it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{config::Encoding, prelude::*, Request};
+    ///
+    /// let response = Request::post("https://httpbin.org/post")
+    ///     .header("Content-Type", "application/json")
+    ///     .request_body_compression(Encoding::Gzip)
+    ///     .body(r#"{"speed": "fast"}"#)?
+    ///     .send()?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the
+    /// [`request-compression`](../index.html#request-compression) feature is
+    /// enabled.
+    #[cfg(feature = "request-compression")]
+    fn request_body_compression(self, encoding: Encoding) -> Self {
+        self.with_config(move |config| {
+            config.request_body_compression = Some(encoding);
+        })
+    }
 }
 
 /// A strategy for selecting what HTTP versions should be used when
@@ -662,6 +2005,12 @@ pub trait Configurable: request::WithRequestConfig {
 /// machine may not have an HTTP/2 implementation. Using static linking and the
 /// [`http2`](../index.html#http2) crate feature can help guarantee that HTTP/2
 /// will be available to use.
+///
+/// Different requests sent by the same client may use different strategies.
+/// A pooled connection is only reused by a request if the HTTP version spoken
+/// on that connection is permitted by the request's strategy, so for example
+/// a request using [`VersionNegotiation::http2`] will never be sent over a
+/// connection previously established for HTTP/1.1, and vice versa.
 #[derive(Clone, Debug)]
 pub struct VersionNegotiation {
     flag: curl::easy::HttpVersion,
@@ -740,6 +2089,22 @@ impl VersionNegotiation {
     }
 }
 
+impl From<http::Version> for VersionNegotiation {
+    /// Get the strategy that only uses the given HTTP version.
+    ///
+    /// HTTP/0.9 is treated the same as HTTP/1.0. Any other unknown version
+    /// results in [`VersionNegotiation::latest_compatible`].
+    fn from(version: http::Version) -> Self {
+        match version {
+            http::Version::HTTP_09 | http::Version::HTTP_10 => Self::http10(),
+            http::Version::HTTP_11 => Self::http11(),
+            http::Version::HTTP_2 => Self::http2(),
+            http::Version::HTTP_3 => Self::http3(),
+            _ => Self::latest_compatible(),
+        }
+    }
+}
+
 impl SetOpt for VersionNegotiation {
     fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
         if let Err(e) = easy.http_version(self.flag) {
@@ -841,6 +2206,39 @@ impl SetOpt for NetworkInterface {
     }
 }
 
+/// A content encoding that can be used to compress request bodies.
+///
+/// See [`Configurable::request_body_compression`] for details.
+///
+/// # Availability
+///
+/// This type is only available when the
+/// [`request-compression`](../index.html#request-compression) feature is
+/// enabled.
+#[cfg(feature = "request-compression")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// The gzip format, as defined in [RFC
+    /// 1952](https://tools.ietf.org/html/rfc1952).
+    Gzip,
+
+    /// The zlib-wrapped deflate format, as defined in [RFC
+    /// 1950](https://tools.ietf.org/html/rfc1950).
+    Deflate,
+}
+
+#[cfg(feature = "request-compression")]
+impl Encoding {
+    /// Get the name of this encoding as used in the `Content-Encoding` header.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+}
+
 /// Supported IP versions that can be used.
 #[derive(Clone, Debug)]
 pub enum IpVersion {
@@ -872,3 +2270,41 @@ impl SetOpt for IpVersion {
         })
     }
 }
+
+/// A function deciding whether a redirect should be followed.
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct RedirectCallback(
+    pub(crate) Arc<dyn Fn(&http::Uri, &http::Uri) -> bool + Send + Sync>,
+);
+
+impl fmt::Debug for RedirectCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedirectCallback").finish()
+    }
+}
+
+/// A function producing trailer headers to send after a request body.
+#[derive(Clone)]
+pub(crate) struct RequestTrailers(pub(crate) Arc<dyn Fn() -> http::HeaderMap + Send + Sync>);
+
+impl fmt::Debug for RequestTrailers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestTrailers").finish()
+    }
+}
+
+/// A function for configuring a curl handle directly.
+#[cfg(feature = "unstable-raw-handle")]
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct RawHandleHook(
+    pub(crate) Arc<dyn Fn(&mut Easy2<RequestHandler>) -> Result<(), curl::Error> + Send + Sync>,
+);
+
+#[cfg(feature = "unstable-raw-handle")]
+impl fmt::Debug for RawHandleHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawHandleHook").finish()
+    }
+}
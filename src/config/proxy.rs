@@ -1,6 +1,8 @@
 use super::SetOpt;
-use curl::easy::Easy2;
-use std::iter::FromIterator;
+use crate::parsing::header_to_curl_string;
+use curl::easy::{Easy2, List};
+use http::HeaderMap;
+use std::{env, fmt, iter::FromIterator};
 
 /// Decorator for marking certain configurations to apply to a proxy rather than
 /// the origin itself.
@@ -26,8 +28,113 @@ impl FromIterator<String> for Blacklist {
     }
 }
 
+impl Blacklist {
+    /// Check whether requests to the given host bypass the proxy.
+    pub(crate) fn matches(&self, host: &str) -> bool {
+        no_proxy_matches(&self.skip, host)
+    }
+}
+
 impl SetOpt for Blacklist {
     fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
         easy.noproxy(&self.skip)
     }
 }
+
+/// Get the proxy that curl would use for the given URI scheme from the
+/// environment, if any.
+pub(crate) fn proxy_from_env(scheme: &str) -> Option<String> {
+    let mut names = vec![format!("{}_proxy", scheme)];
+
+    // Curl only looks at the lowercase variable for plain HTTP, since
+    // `HTTP_PROXY` can be set by CGI servers from a request header.
+    if scheme != "http" {
+        names.push(format!("{}_PROXY", scheme.to_ascii_uppercase()));
+    }
+
+    names.push(String::from("all_proxy"));
+    names.push(String::from("ALL_PROXY"));
+
+    names
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Check whether the given host is excluded from using a proxy by the
+/// `no_proxy` environment variable, as curl does.
+pub(crate) fn no_proxy_from_env_matches(host: &str) -> bool {
+    env::var("no_proxy")
+        .or_else(|_| env::var("NO_PROXY"))
+        .map(|list| no_proxy_matches(&list, host))
+        .unwrap_or(false)
+}
+
+/// Check whether a host matches a comma-separated list of host names that
+/// bypass the proxy. A name matches the host itself and any of its subdomains,
+/// and `*` matches all hosts.
+fn no_proxy_matches(list: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    list.split(',')
+        .map(|entry| entry.trim().trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            entry == "*"
+                || host.eq_ignore_ascii_case(entry)
+                || host.len() > entry.len()
+                    && host.as_bytes()[host.len() - entry.len() - 1] == b'.'
+                    && host[host.len() - entry.len()..].eq_ignore_ascii_case(entry)
+        })
+}
+
+/// Custom headers to send to a proxy, separately from the headers sent to the
+/// origin server.
+///
+/// See
+/// [`Configurable::proxy_headers`](crate::config::Configurable::proxy_headers).
+#[derive(Clone)]
+pub(crate) struct ProxyHeaders {
+    pub(crate) headers: HeaderMap,
+
+    /// If false, the headers are also sent on requests tunneled through the
+    /// proxy.
+    pub(crate) connect_only: bool,
+}
+
+// Proxy headers often carry credentials, so only their names are shown.
+impl fmt::Debug for ProxyHeaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyHeaders")
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("connect_only", &self.connect_only)
+            .finish()
+    }
+}
+
+impl SetOpt for ProxyHeaders {
+    fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
+        let mut list = List::new();
+
+        for (name, value) in self.headers.iter() {
+            list.append(&header_to_curl_string(name, value, false))?;
+        }
+
+        easy.proxy_headers(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_proxy_matches_hosts_and_subdomains() {
+        assert!(no_proxy_matches("example.org", "example.org"));
+        assert!(no_proxy_matches("foo, .example.org", "api.Example.org"));
+        assert!(no_proxy_matches("*", "example.org"));
+        assert!(no_proxy_matches("::1", "[::1]"));
+        assert!(!no_proxy_matches("example.org", "badexample.org"));
+        assert!(!no_proxy_matches("", "example.org"));
+    }
+}
@@ -21,3 +21,33 @@ impl Default for RedirectPolicy {
         RedirectPolicy::None
     }
 }
+
+/// Describes how `POST` requests are treated when following a `301 Moved
+/// Permanently` or `302 Found` redirect.
+///
+/// The default is [`PostRedirectPolicy::Spec`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PostRedirectPolicy {
+    /// Follow the redirect using a `GET` request, as browsers and curl do.
+    ///
+    /// [RFC 7231](https://tools.ietf.org/html/rfc7231#section-6.4.2) allows
+    /// changing the method to `GET` for these status codes for historical
+    /// reasons. `307 Temporary Redirect` and `308 Permanent Redirect` always
+    /// preserve the method and body.
+    ///
+    /// This is the default policy.
+    Spec,
+
+    /// Follow the redirect using a `POST` request with the same body as the
+    /// original request. This is like curl's `CURL_REDIR_POST_301` and
+    /// `CURL_REDIR_POST_302` flags, and is sometimes needed for legacy APIs.
+    ///
+    /// A `303 See Other` redirect is still followed using a `GET` request.
+    PreservePost,
+}
+
+impl Default for PostRedirectPolicy {
+    fn default() -> Self {
+        PostRedirectPolicy::Spec
+    }
+}
@@ -19,7 +19,7 @@ pub(crate) trait SetOpt {
 
 // Define this struct inside a macro to reduce some boilerplate.
 macro_rules! define_request_config {
-    ($($field:ident: $t:ty,)*) => {
+    ($($(#[$attr:meta])* $field:ident: $t:ty,)*) => {
         /// Configuration for an HTTP request.
         ///
         /// This struct is not exposed directly, but rather is interacted with
@@ -27,6 +27,7 @@ macro_rules! define_request_config {
         #[derive(Clone, Debug, Default)]
         pub struct RequestConfig {
             $(
+                $(#[$attr])*
                 pub(crate) $field: $t,
             )*
         }
@@ -50,6 +51,7 @@ macro_rules! define_request_config {
             /// config.
             pub(crate) fn merge(&mut self, defaults: &Self) {
                 $(
+                    $(#[$attr])*
                     if self.$field.is_none() {
                         if let Some(value) = defaults.$field.as_ref() {
                             self.$field = Some(value.clone());
@@ -57,40 +59,140 @@ macro_rules! define_request_config {
                     }
                 )*
             }
+
+            /// Describe each value that is set in this configuration or in the
+            /// given defaults, noting where the value came from.
+            pub(crate) fn describe(&self, defaults: &Self) -> Vec<String> {
+                let mut options = Vec::new();
+
+                $(
+                    $(#[$attr])*
+                    {
+                        if let Some(value) = self.$field.as_ref() {
+                            options.push(format!(
+                                "{}: {} (request)",
+                                stringify!($field),
+                                redact_userinfo(format!("{:?}", value))
+                            ));
+                        } else if let Some(value) = defaults.$field.as_ref() {
+                            options.push(format!(
+                                "{}: {} (client)",
+                                stringify!($field),
+                                redact_userinfo(format!("{:?}", value))
+                            ));
+                        }
+                    }
+                )*
+
+                options
+            }
         }
     };
 }
 
+/// Hide any userinfo in URIs found in a description of a configuration value,
+/// such as the credentials of a proxy.
+///
+/// Other secrets, such as passwords and private keys, are already hidden by
+/// the `Debug` implementations of the types holding them.
+fn redact_userinfo(mut description: String) -> String {
+    let mut start = 0;
+
+    while let Some(index) = description[start..].find("://") {
+        let authority_start = start + index + 3;
+        let authority_end = description[authority_start..]
+            .find(|c: char| c == '/' || c == '"' || c == ')' || c.is_whitespace())
+            .map_or(description.len(), |i| authority_start + i);
+
+        if let Some(at) = description[authority_start..authority_end].rfind('@') {
+            description.replace_range(authority_start..authority_start + at, "*****");
+        }
+
+        start = authority_start;
+    }
+
+    description
+}
+
 define_request_config! {
     // Used by curl
     timeout: Option<Duration>,
     connect_timeout: Option<Duration>,
     low_speed_timeout: Option<(u32, Duration)>,
     version_negotiation: Option<VersionNegotiation>,
+    alpn_protocols: Option<Vec<String>>,
     automatic_decompression: Option<bool>,
     authentication: Option<Authentication>,
     credentials: Option<Credentials>,
+    netrc: Option<Netrc>,
+    netrc_file: Option<NetrcFile>,
     tcp_keepalive: Option<Duration>,
+    http2_keepalive: Option<Duration>,
     tcp_nodelay: Option<bool>,
+    tcp_user_timeout: Option<tcp::TcpUserTimeout>,
+    haproxy_protocol: Option<bool>,
+    use_signals: Option<bool>,
+    close_connection: Option<bool>,
+    referer: Option<String>,
+    path_as_is: Option<bool>,
     interface: Option<NetworkInterface>,
     ip_version: Option<IpVersion>,
+    dns_cache: Option<dns::DnsCache>,
+    dns_shuffle_addresses: Option<bool>,
     dial: Option<Dialer>,
     proxy: Option<Option<http::Uri>>,
     proxy_blacklist: Option<proxy::Blacklist>,
     proxy_authentication: Option<Proxy<Authentication>>,
     proxy_credentials: Option<Proxy<Credentials>>,
+    proxy_headers: Option<proxy::ProxyHeaders>,
+    proxy_ssl_client_certificate: Option<Proxy<ClientCertificate>>,
+    proxy_ssl_ca_certificate: Option<Proxy<CaCertificate>>,
+    proxy_ssl_options: Option<Proxy<SslOption>>,
     max_upload_speed: Option<u64>,
     max_download_speed: Option<u64>,
+    download_buffer_size: Option<usize>,
+    request_initial_window_size: Option<u32>,
     ssl_client_certificate: Option<ClientCertificate>,
     ssl_ca_certificate: Option<CaCertificate>,
+    ssl_extra_ca_certificates: Option<ssl::ExtraCaCertificates>,
     ssl_ciphers: Option<ssl::Ciphers>,
     ssl_options: Option<SslOption>,
+    ssl_revocation: Option<ssl::RevocationCheck>,
+    ssl_session_cache: Option<bool>,
+    collect_certificate_info: Option<bool>,
     enable_metrics: Option<bool>,
 
     // Used by interceptors
     redirect_policy: Option<RedirectPolicy>,
+    post_redirect_policy: Option<PostRedirectPolicy>,
     auto_referer: Option<bool>,
+    on_redirect: Option<RedirectCallback>,
     title_case_headers: Option<bool>,
+    minimal_headers: Option<bool>,
+    require_content_length: Option<bool>,
+    accept_encoding_identity: Option<bool>,
+
+    // Used by the request handler
+    headers_timeout: Option<Duration>,
+    max_response_header_size: Option<usize>,
+    max_response_headers: Option<usize>,
+    http_200_aliases: Option<Vec<String>>,
+    priority: Option<u8>,
+
+    // Used by the client before sending
+    deadline: Option<Instant>,
+    explicit_proxy: Option<bool>,
+    tag: Option<String>,
+    guess_content_type: Option<bool>,
+    allow_file_uris: Option<bool>,
+    propagate_extensions: Option<extensions::PropagatedExtensions>,
+    request_trailers: Option<RequestTrailers>,
+    #[cfg(feature = "unstable-raw-handle")]
+    raw_handle: Option<RawHandleHook>,
+    #[cfg(feature = "request-compression")]
+    request_body_compression: Option<Encoding>,
+    #[cfg(feature = "text-decoding")]
+    default_charset: Option<crate::text::DefaultCharset>,
 }
 
 impl SetOpt for RequestConfig {
@@ -112,6 +214,33 @@ impl SetOpt for RequestConfig {
             negotiation.set_opt(easy)?;
         }
 
+        // Curl derives the advertised ALPN identifiers from the HTTP versions
+        // it is allowed to use, so restrict those to match.
+        if let Some(protocols) = self.alpn_protocols.as_ref() {
+            let h2 = protocols.iter().any(|p| p == "h2");
+            let http11 = protocols.iter().any(|p| p == "http/1.1");
+
+            if !h2 && !http11 {
+                // Not exposed by the curl crate or curl-sys.
+                const CURLOPT_SSL_ENABLE_ALPN: curl_sys::CURLoption =
+                    curl_sys::CURLOPTTYPE_LONG + 226;
+
+                #[allow(unsafe_code)]
+                unsafe {
+                    match curl_sys::curl_easy_setopt(
+                        easy.raw(),
+                        CURLOPT_SSL_ENABLE_ALPN,
+                        0 as std::os::raw::c_long,
+                    ) {
+                        curl_sys::CURLE_OK => {}
+                        code => return Err(curl::Error::new(code)),
+                    }
+                }
+            } else if !h2 {
+                easy.http_version(curl::easy::HttpVersion::V11)?;
+            }
+        }
+
         #[allow(unsafe_code)]
         {
             if let Some(enable) = self.automatic_decompression {
@@ -144,19 +273,99 @@ impl SetOpt for RequestConfig {
             credentials.set_opt(easy)?;
         }
 
+        if let Some(netrc) = self.netrc.as_ref() {
+            netrc.set_opt(easy)?;
+        }
+
+        // Curl is pointed at the default netrc file explicitly, so that it
+        // reads the same file that the client checks for entries.
+        if let Some(file) = self.netrc_file.as_ref().or_else(|| {
+            self.netrc
+                .filter(|netrc| *netrc != Netrc::Ignored)
+                .and_then(|_| NetrcFile::default_file())
+        }) {
+            file.set_opt(easy)?;
+        }
+
         if let Some(interval) = self.tcp_keepalive {
             easy.tcp_keepalive(true)?;
             easy.tcp_keepintvl(interval)?;
         }
 
+        // An explicitly configured TCP keepalive takes precedence.
+        if let (Some(interval), None) = (self.http2_keepalive, self.tcp_keepalive) {
+            easy.tcp_keepalive(true)?;
+            easy.tcp_keepidle(interval)?;
+            easy.tcp_keepintvl(interval)?;
+        }
+
         if let Some(enable) = self.tcp_nodelay {
             easy.tcp_nodelay(enable)?;
         }
 
+        if let Some(timeout) = self.tcp_user_timeout.as_ref() {
+            timeout.set_opt(easy)?;
+        }
+
+        if let Some(enable) = self.haproxy_protocol {
+            // Not exposed by the curl crate or curl-sys.
+            const CURLOPT_HAPROXYPROTOCOL: curl_sys::CURLoption = curl_sys::CURLOPTTYPE_LONG + 274;
+
+            #[allow(unsafe_code)]
+            unsafe {
+                match curl_sys::curl_easy_setopt(
+                    easy.raw(),
+                    CURLOPT_HAPROXYPROTOCOL,
+                    enable as std::os::raw::c_long,
+                ) {
+                    curl_sys::CURLE_OK => {}
+                    code => return Err(curl::Error::new(code)),
+                }
+            }
+        }
+
+        if let Some(enable) = self.use_signals {
+            easy.signal(enable)?;
+        }
+
+        if let Some(close) = self.close_connection {
+            easy.forbid_reuse(close)?;
+        }
+
+        if let Some(referer) = self.referer.as_ref() {
+            easy.referer(referer)?;
+        }
+
+        if let Some(enable) = self.path_as_is {
+            easy.path_as_is(enable)?;
+        }
+
         if let Some(interface) = self.interface.as_ref() {
             interface.set_opt(easy)?;
         }
 
+        if let Some(cache) = self.dns_cache.as_ref() {
+            cache.set_opt(easy)?;
+        }
+
+        if let Some(enable) = self.dns_shuffle_addresses {
+            // Not exposed by the curl crate or curl-sys.
+            const CURLOPT_DNS_SHUFFLE_ADDRESSES: curl_sys::CURLoption =
+                curl_sys::CURLOPTTYPE_LONG + 275;
+
+            #[allow(unsafe_code)]
+            unsafe {
+                match curl_sys::curl_easy_setopt(
+                    easy.raw(),
+                    CURLOPT_DNS_SHUFFLE_ADDRESSES,
+                    enable as std::os::raw::c_long,
+                ) {
+                    curl_sys::CURLE_OK => {}
+                    code => return Err(curl::Error::new(code)),
+                }
+            }
+        }
+
         if let Some(version) = self.ip_version.as_ref() {
             version.set_opt(easy)?;
         }
@@ -172,6 +381,10 @@ impl SetOpt for RequestConfig {
             }
         }
 
+        if let Some(headers) = self.proxy_headers.as_ref() {
+            headers.set_opt(easy)?;
+        }
+
         if let Some(blacklist) = self.proxy_blacklist.as_ref() {
             blacklist.set_opt(easy)?;
         }
@@ -184,6 +397,18 @@ impl SetOpt for RequestConfig {
             credentials.set_opt(easy)?;
         }
 
+        if let Some(cert) = self.proxy_ssl_client_certificate.as_ref() {
+            cert.set_opt(easy)?;
+        }
+
+        if let Some(cert) = self.proxy_ssl_ca_certificate.as_ref() {
+            cert.set_opt(easy)?;
+        }
+
+        if let Some(options) = self.proxy_ssl_options.as_ref() {
+            options.set_opt(easy)?;
+        }
+
         if let Some(max) = self.max_upload_speed {
             easy.max_send_speed(max)?;
         }
@@ -192,6 +417,20 @@ impl SetOpt for RequestConfig {
             easy.max_recv_speed(max)?;
         }
 
+        if let Some(size) = self.download_buffer_size {
+            easy.buffer_size(clamp_download_buffer_size(size))?;
+        }
+
+        // No version of curl so far allows the HTTP/2 stream window size to be
+        // configured. Instead, it starts each stream with a small window and
+        // grows it as the response body is consumed.
+        if let Some(size) = self.request_initial_window_size {
+            tracing::debug!(
+                size,
+                "setting the HTTP/2 initial window size is not supported by curl, ignoring"
+            );
+        }
+
         if let Some(cert) = self.ssl_client_certificate.as_ref() {
             cert.set_opt(easy)?;
         }
@@ -204,8 +443,20 @@ impl SetOpt for RequestConfig {
             ciphers.set_opt(easy)?;
         }
 
-        if let Some(options) = self.ssl_options.as_ref() {
-            options.set_opt(easy)?;
+        if self.ssl_options.is_some() || self.ssl_revocation.is_some() {
+            ssl::SslSettings {
+                options: self.ssl_options.unwrap_or_default(),
+                revocation: self.ssl_revocation,
+            }
+            .set_opt(easy)?;
+        }
+
+        if let Some(enable) = self.ssl_session_cache {
+            easy.ssl_sessionid_cache(enable)?;
+        }
+
+        if let Some(enable) = self.collect_certificate_info {
+            easy.certinfo(enable)?;
         }
 
         if let Some(enable) = self.enable_metrics {
@@ -215,3 +466,34 @@ impl SetOpt for RequestConfig {
         Ok(())
     }
 }
+
+/// Clamp a download buffer size to the range of sizes accepted by curl.
+fn clamp_download_buffer_size(size: usize) -> usize {
+    // Corresponds to CURL_MIN_READ_SIZE and CURL_MAX_READ_SIZE.
+    const MIN: usize = 1024;
+    const MAX: usize = 10 * 1024 * 1024;
+
+    let clamped = if size < MIN { MIN } else { size.min(MAX) };
+
+    if clamped != size {
+        tracing::debug!(
+            "download buffer size of {} bytes is out of range, using {} bytes instead",
+            size,
+            clamped
+        );
+    }
+
+    clamped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn download_buffer_size_is_clamped() {
+        assert_eq!(clamp_download_buffer_size(0), 1024);
+        assert_eq!(clamp_download_buffer_size(64 * 1024), 64 * 1024);
+        assert_eq!(clamp_download_buffer_size(usize::MAX), 10 * 1024 * 1024);
+    }
+}
@@ -1,24 +1,43 @@
 //! Configuration options related to SSL/TLS.
 
-use super::SetOpt;
+use super::{proxy::Proxy, SetOpt};
 use curl::easy::{Easy2, SslOpt};
+use once_cell::sync::Lazy;
 use std::{
+    fmt,
+    fs,
+    io,
     iter::FromIterator,
     ops::{BitOr, BitOrAssign},
-    path::PathBuf,
+    os::raw::c_long,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 enum PathOrBlob {
     Path(PathBuf),
     Blob(Vec<u8>),
 }
 
+// Blobs may contain private keys, so only their size is shown.
+impl fmt::Debug for PathOrBlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(path) => f.debug_tuple("Path").field(path).finish(),
+            Self::Blob(bytes) => f
+                .debug_tuple("Blob")
+                .field(&format_args!("{} bytes", bytes.len()))
+                .finish(),
+        }
+    }
+}
+
 /// A client certificate for SSL/TLS client validation.
 ///
 /// Note that this isn't merely an X.509 certificate, but rather a certificate
 /// and private key pair.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct ClientCertificate {
     /// Name of the cert format.
     format: &'static str,
@@ -33,6 +52,17 @@ pub struct ClientCertificate {
     password: Option<String>,
 }
 
+impl fmt::Debug for ClientCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCertificate")
+            .field("format", &self.format)
+            .field("data", &self.data)
+            .field("private_key", &self.private_key)
+            .field("password", &self.password.as_ref().map(|_| "*****"))
+            .finish()
+    }
+}
+
 impl ClientCertificate {
     /// Use a PEM-encoded certificate stored in the given byte buffer.
     ///
@@ -181,8 +211,29 @@ impl SetOpt for ClientCertificate {
     }
 }
 
+impl SetOpt for Proxy<ClientCertificate> {
+    fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
+        easy.proxy_sslcert_type(self.0.format)?;
+
+        match &self.0.data {
+            PathOrBlob::Path(path) => easy.proxy_sslcert(path_to_str(path)?),
+            PathOrBlob::Blob(bytes) => easy.proxy_sslcert_blob(bytes.as_slice()),
+        }?;
+
+        if let Some(key) = self.0.private_key.as_ref() {
+            Proxy(key.clone()).set_opt(easy)?;
+        }
+
+        if let Some(password) = self.0.password.as_ref() {
+            easy.proxy_key_password(password)?;
+        }
+
+        Ok(())
+    }
+}
+
 /// A private key file.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct PrivateKey {
     /// Key format name.
     format: &'static str,
@@ -194,6 +245,16 @@ pub struct PrivateKey {
     password: Option<String>,
 }
 
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("format", &self.format)
+            .field("data", &self.data)
+            .field("password", &self.password.as_ref().map(|_| "*****"))
+            .finish()
+    }
+}
+
 impl PrivateKey {
     /// Use a PEM-encoded private key stored in the given byte buffer.
     ///
@@ -281,12 +342,33 @@ impl SetOpt for PrivateKey {
     }
 }
 
-/// A public CA certificate bundle file.
-#[derive(Clone, Debug)]
+impl SetOpt for Proxy<PrivateKey> {
+    fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
+        easy.proxy_sslkey_type(self.0.format)?;
+
+        match &self.0.data {
+            PathOrBlob::Path(path) => easy.proxy_sslkey(path_to_str(path)?),
+            PathOrBlob::Blob(bytes) => easy.proxy_sslkey_blob(bytes.as_slice()),
+        }?;
+
+        if let Some(password) = self.0.password.as_ref() {
+            easy.proxy_key_password(password)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A public CA certificate bundle file or directory.
+#[derive(Clone, Debug, PartialEq)]
 pub struct CaCertificate {
-    /// Path to the certificate bundle file. Currently only file paths are
-    /// supported.
+    /// Path to the certificate bundle file or directory. Currently only paths
+    /// are supported.
     path: PathBuf,
+
+    /// Whether the path is a directory of certificates rather than a single
+    /// bundle file.
+    is_dir: bool,
 }
 
 impl CaCertificate {
@@ -299,16 +381,187 @@ impl CaCertificate {
     pub fn file(ca_bundle_path: impl Into<PathBuf>) -> Self {
         Self {
             path: ca_bundle_path.into(),
+            is_dir: false,
+        }
+    }
+
+    /// Get CA certificates from a path to a directory of certificates.
+    ///
+    /// The directory must contain PEM-encoded certificates named by their
+    /// subject name hash, as prepared by OpenSSL's `c_rehash` utility. Many
+    /// Linux distributions store their system certificates in this form, such
+    /// as in `/etc/ssl/certs`.
+    ///
+    /// The directory is not loaded or validated here. If the directory does
+    /// not exist, an error will be returned when attempting to send a request.
+    /// Certificate directories are not supported by all SSL/TLS engines; in
+    /// particular they are generally only supported when using OpenSSL.
+    pub fn dir(ca_path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: ca_path.into(),
+            is_dir: true,
         }
     }
 }
 
 impl SetOpt for CaCertificate {
     fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
-        easy.cainfo(&self.path)
+        if self.is_dir {
+            // Some SSL/TLS engines silently ignore a directory that does not
+            // exist, so check for it ourselves.
+            if !self.path.is_dir() {
+                return Err(curl::Error::new(curl_sys::CURLE_SSL_CACERT_BADFILE));
+            }
+
+            easy.capath(&self.path)
+        } else {
+            easy.cainfo(&self.path)
+        }
+    }
+}
+
+impl SetOpt for Proxy<CaCertificate> {
+    fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
+        if self.0.is_dir {
+            if !self.0.path.is_dir() {
+                return Err(curl::Error::new(curl_sys::CURLE_SSL_CACERT_BADFILE));
+            }
+
+            easy.proxy_capath(&self.0.path)
+        } else {
+            easy.proxy_cainfo(path_to_str(&self.0.path)?)
+        }
+    }
+}
+
+impl CaCertificate {
+    /// Read the PEM-encoded certificates this refers to into memory.
+    fn read_pem(&self) -> io::Result<Vec<u8>> {
+        if !self.is_dir {
+            return fs::read(&self.path);
+        }
+
+        let mut pem = Vec::new();
+
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+
+            if path.is_file() {
+                pem.extend_from_slice(&fs::read(path)?);
+                pem.push(b'\n');
+            }
+        }
+
+        Ok(pem)
     }
 }
 
+/// Additional CA certificates to trust on top of the base CA certificates.
+#[derive(Clone, Default)]
+pub(crate) struct ExtraCaCertificates {
+    certificates: Vec<CaCertificate>,
+
+    /// Combined bundles that have already been built. Shared between clones
+    /// so that the files are only read once, rather than for every request.
+    bundles: Arc<BundleCache>,
+}
+
+/// A CA certificate bundle held in memory.
+type Bundle = Arc<Vec<u8>>;
+
+/// Combined CA bundles, keyed by the base CA certificate they were built on.
+type BundleCache = Mutex<Vec<(Option<CaCertificate>, Bundle)>>;
+
+impl ExtraCaCertificates {
+    pub(crate) fn push(&mut self, certificate: CaCertificate) {
+        self.certificates.push(certificate);
+
+        // Any bundles built so far are missing the new certificate.
+        self.bundles = Default::default();
+    }
+
+    /// Get a single CA bundle containing these certificates, in addition to
+    /// the given base CA certificate or the system certificate bundle if
+    /// none.
+    ///
+    /// Curl only accepts a single CA bundle, so the base bundle and the extra
+    /// certificates are concatenated into one in memory. Certificate
+    /// directories configured with `CURLOPT_CAPATH` remain in effect.
+    pub(crate) fn bundle(&self, base: Option<&CaCertificate>) -> io::Result<Bundle> {
+        let mut bundles = self.bundles.lock().unwrap();
+
+        if let Some((_, bundle)) = bundles.iter().find(|(b, _)| b.as_ref() == base) {
+            return Ok(bundle.clone());
+        }
+
+        let mut bundle = match base {
+            Some(base) if !base.is_dir => base.read_pem()?,
+            Some(_) => Vec::new(),
+            None => SYSTEM_CA_BUNDLE.clone().unwrap_or_default(),
+        };
+
+        for cert in &self.certificates {
+            bundle.push(b'\n');
+            bundle.extend_from_slice(&cert.read_pem()?);
+        }
+
+        let bundle = Arc::new(bundle);
+        bundles.push((base.cloned(), bundle.clone()));
+
+        Ok(bundle)
+    }
+
+    /// Configure curl to trust these certificates in addition to the given
+    /// base CA certificate.
+    pub(crate) fn apply<H>(
+        &self,
+        base: Option<&CaCertificate>,
+        easy: &mut Easy2<H>,
+    ) -> Result<(), curl::Error> {
+        let bundle = self
+            .bundle(base)
+            .map_err(|_| curl::Error::new(curl_sys::CURLE_SSL_CACERT_BADFILE))?;
+
+        easy.ssl_cainfo_blob(&bundle)
+    }
+}
+
+impl fmt::Debug for ExtraCaCertificates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ExtraCaCertificates")
+            .field(&self.certificates)
+            .finish()
+    }
+}
+
+/// Contents of the system CA certificate bundle that curl uses by default, if
+/// it is a file we can find.
+static SYSTEM_CA_BUNDLE: Lazy<Option<Vec<u8>>> = Lazy::new(|| {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let path = openssl_probe::probe().cert_file?;
+
+        match fs::read(&path) {
+            Ok(bundle) => Some(bundle),
+            Err(e) => {
+                tracing::debug!("failed to read system CA bundle {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+        None
+    }
+});
+
+/// Some proxy SSL/TLS options in curl only accept paths as strings.
+fn path_to_str(path: &Path) -> Result<&str, curl::Error> {
+    path.to_str()
+        .ok_or_else(|| curl::Error::new(curl_sys::CURLE_BAD_FUNCTION_ARGUMENT))
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct Ciphers(String);
 
@@ -353,6 +606,13 @@ impl SslOption {
 
     /// Disables hostname verification on certificates.
     ///
+    /// Unlike [`SslOption::DANGER_ACCEPT_INVALID_CERTS`], the certificate chain
+    /// presented by the server is still validated against the trusted
+    /// certificate authorities; only the check that the certificate was issued
+    /// for the host being connected to is skipped. This can be useful when
+    /// connecting to a server by IP address that presents a valid certificate
+    /// for its domain name.
+    ///
     /// # Warning
     ///
     /// You should think very carefully before you use this method. If hostname
@@ -390,18 +650,97 @@ impl BitOrAssign for SslOption {
 
 impl SetOpt for SslOption {
     fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
+        SslSettings {
+            options: *self,
+            revocation: None,
+        }
+        .set_opt(easy)
+    }
+}
+
+impl SetOpt for Proxy<SslOption> {
+    fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
+        let settings = SslSettings {
+            options: self.0,
+            revocation: None,
+        };
+
+        easy.proxy_ssl_options(&settings.ssl_opt())?;
+        easy.proxy_ssl_verify_peer(!self.0.contains(SslOption::DANGER_ACCEPT_INVALID_CERTS))?;
+        easy.proxy_ssl_verify_host(!self.0.contains(SslOption::DANGER_ACCEPT_INVALID_HOSTS))
+    }
+}
+
+/// Strategy for checking whether a server certificate has been revoked.
+///
+/// Revocation checks are only performed by SSL/TLS engines that support them,
+/// which currently primarily means Schannel (the native Windows SSL library).
+/// Other engines will ignore this setting.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RevocationCheck {
+    /// Do not check whether certificates have been revoked.
+    Disabled,
+
+    /// Check whether certificates have been revoked, but ignore failures caused
+    /// by missing or offline revocation distribution points.
+    BestEffort,
+
+    /// Check whether certificates have been revoked, and fail the connection
+    /// if the revocation status cannot be determined.
+    Hard,
+}
+
+impl RevocationCheck {
+    /// Get the curl SSL option bits corresponding to this mode.
+    fn bits(self) -> c_long {
+        match self {
+            Self::Disabled => curl_sys::CURLSSLOPT_NO_REVOKE,
+            Self::BestEffort => curl_sys::CURLSSLOPT_REVOKE_BEST_EFFORT,
+            Self::Hard => 0,
+        }
+    }
+}
+
+/// All SSL/TLS flags that need to be applied together, since curl accepts
+/// them as a single bitmask.
+pub(crate) struct SslSettings {
+    pub(crate) options: SslOption,
+    pub(crate) revocation: Option<RevocationCheck>,
+}
+
+impl SslSettings {
+    fn bits(&self) -> c_long {
+        if let Some(revocation) = self.revocation {
+            // An explicit revocation check mode takes precedence over the
+            // danger flag.
+            revocation.bits()
+        } else if self.options.contains(SslOption::DANGER_ACCEPT_REVOKED_CERTS) {
+            curl_sys::CURLSSLOPT_NO_REVOKE
+        } else {
+            0
+        }
+    }
+
+    fn ssl_opt(&self) -> SslOpt {
+        let bits = self.bits();
         let mut opt = SslOpt::new();
-        opt.no_revoke(self.contains(Self::DANGER_ACCEPT_REVOKED_CERTS));
+        opt.no_revoke(bits & curl_sys::CURLSSLOPT_NO_REVOKE != 0);
+        opt.revoke_best_effort(bits & curl_sys::CURLSSLOPT_REVOKE_BEST_EFFORT != 0);
+        opt
+    }
+}
 
-        easy.ssl_options(&opt)?;
-        easy.ssl_verify_peer(!self.contains(Self::DANGER_ACCEPT_INVALID_CERTS))?;
-        easy.ssl_verify_host(!self.contains(Self::DANGER_ACCEPT_INVALID_HOSTS))
+impl SetOpt for SslSettings {
+    fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
+        easy.ssl_options(&self.ssl_opt())?;
+        easy.ssl_verify_peer(!self.options.contains(SslOption::DANGER_ACCEPT_INVALID_CERTS))?;
+        easy.ssl_verify_host(!self.options.contains(SslOption::DANGER_ACCEPT_INVALID_HOSTS))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SslOption;
+    use super::*;
 
     #[test]
     fn default_ssl_options() {
@@ -424,4 +763,57 @@ mod tests {
         assert!(!options.contains(SslOption::DANGER_ACCEPT_INVALID_CERTS));
         assert!(options.contains(SslOption::DANGER_ACCEPT_INVALID_HOSTS));
     }
+
+    #[test]
+    fn revocation_check_bits() {
+        let bits = |revocation| {
+            SslSettings {
+                options: SslOption::NONE,
+                revocation: Some(revocation),
+            }
+            .bits()
+        };
+
+        assert_eq!(bits(RevocationCheck::Disabled), curl_sys::CURLSSLOPT_NO_REVOKE);
+        assert_eq!(
+            bits(RevocationCheck::BestEffort),
+            curl_sys::CURLSSLOPT_REVOKE_BEST_EFFORT
+        );
+        assert_eq!(bits(RevocationCheck::Hard), 0);
+    }
+
+    #[test]
+    fn revocation_check_overrides_danger_flag() {
+        let settings = SslSettings {
+            options: SslOption::DANGER_ACCEPT_REVOKED_CERTS,
+            revocation: None,
+        };
+
+        assert_eq!(settings.bits(), curl_sys::CURLSSLOPT_NO_REVOKE);
+
+        let settings = SslSettings {
+            options: SslOption::DANGER_ACCEPT_REVOKED_CERTS,
+            revocation: Some(RevocationCheck::Hard),
+        };
+
+        assert_eq!(settings.bits(), 0);
+    }
+
+    #[test]
+    fn ca_certificate_dir_is_read_as_concatenated_pem() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.pem"), "a").unwrap();
+        fs::write(dir.path().join("b.pem"), "b").unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+
+        let pem = CaCertificate::dir(dir.path()).read_pem().unwrap();
+        let mut lines = String::from_utf8(pem)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        lines.sort();
+
+        assert_eq!(lines, vec!["a", "b"]);
+    }
 }
@@ -0,0 +1,94 @@
+//! Configuration of TCP socket options not exposed by curl directly.
+
+use super::SetOpt;
+use curl::easy::Easy2;
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+use std::os::raw::{c_int, c_void};
+
+/// Not exposed by curl-sys.
+#[cfg(target_os = "linux")]
+type SockoptCallback =
+    extern "C" fn(*mut c_void, curl_sys::curl_socket_t, curl_sys::curlsocktype) -> c_int;
+
+/// Not exposed by curl-sys.
+#[cfg(target_os = "linux")]
+const CURL_SOCKOPT_OK: c_int = 0;
+
+/// Sets the `TCP_USER_TIMEOUT` socket option on new connections.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TcpUserTimeout(pub(crate) Duration);
+
+impl SetOpt for TcpUserTimeout {
+    #[cfg(target_os = "linux")]
+    #[allow(unsafe_code)]
+    fn set_opt<H>(&self, easy: &mut Easy2<H>) -> Result<(), curl::Error> {
+        use std::convert::TryFrom;
+
+        // The timeout is small enough to pass to the callback as the pointer
+        // itself, so that there is nothing for curl to hold on to.
+        let millis = libc::c_uint::try_from(self.0.as_millis()).unwrap_or(libc::c_uint::MAX);
+
+        unsafe {
+            match curl_sys::curl_easy_setopt(
+                easy.raw(),
+                curl_sys::CURLOPT_SOCKOPTFUNCTION,
+                set_tcp_user_timeout as SockoptCallback,
+            ) {
+                curl_sys::CURLE_OK => {}
+                code => return Err(curl::Error::new(code)),
+            }
+
+            match curl_sys::curl_easy_setopt(
+                easy.raw(),
+                curl_sys::CURLOPT_SOCKOPTDATA,
+                millis as usize as *mut c_void,
+            ) {
+                curl_sys::CURLE_OK => Ok(()),
+                code => Err(curl::Error::new(code)),
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn set_opt<H>(&self, _: &mut Easy2<H>) -> Result<(), curl::Error> {
+        tracing::debug!("TCP_USER_TIMEOUT is only supported on Linux, ignoring");
+        Ok(())
+    }
+}
+
+/// Socket option callback that sets `TCP_USER_TIMEOUT` to the number of
+/// milliseconds given as the callback data.
+#[cfg(target_os = "linux")]
+#[allow(unsafe_code)]
+extern "C" fn set_tcp_user_timeout(
+    data: *mut c_void,
+    socket: curl_sys::curl_socket_t,
+    purpose: curl_sys::curlsocktype,
+) -> c_int {
+    if purpose == curl_sys::CURLSOCKTYPE_IPCXN {
+        let millis = data as usize as libc::c_uint;
+
+        let result = unsafe {
+            libc::setsockopt(
+                socket,
+                libc::IPPROTO_TCP,
+                libc::TCP_USER_TIMEOUT,
+                &millis as *const libc::c_uint as *const libc::c_void,
+                std::mem::size_of::<libc::c_uint>() as libc::socklen_t,
+            )
+        };
+
+        // Not all sockets are TCP sockets, such as Unix sockets, so failing to
+        // set the option is not an error.
+        if result != 0 {
+            tracing::debug!(
+                "failed to set TCP_USER_TIMEOUT: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    CURL_SOCKOPT_OK
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+/// Describes a policy for adaptively slowing down requests to hosts that
+/// signal that they are overloaded.
+///
+/// When a server responds with `429 Too Many Requests` or `503 Service
+/// Unavailable`, subsequent requests to that same host are delayed. The delay
+/// honors any `Retry-After` header sent by the server, and otherwise backs off
+/// exponentially for each consecutive overloaded response. Once the server
+/// stops complaining, the delay gradually recovers back to zero over time.
+///
+/// Unlike
+/// [`Configurable::max_download_speed`](crate::config::Configurable::max_download_speed),
+/// which caps the transfer rate of a single response, this limits how often
+/// requests are started against a host.
+///
+/// Set on a client using
+/// [`HttpClientBuilder::throttle_policy`](crate::HttpClientBuilder::throttle_policy).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ThrottlePolicy {
+    pub(crate) initial_delay: Duration,
+    pub(crate) max_delay: Duration,
+    pub(crate) recovery_period: Duration,
+}
+
+impl Default for ThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            recovery_period: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ThrottlePolicy {
+    /// Create a new throttle policy with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the delay between requests to use after the first overloaded
+    /// response from a host if the server did not send a `Retry-After`
+    /// header. Each further overloaded response doubles the delay.
+    ///
+    /// The default is 1 second.
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Set the maximum delay between requests to a single host. This also
+    /// limits how long a `Retry-After` header is honored for.
+    ///
+    /// The default is 60 seconds.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Set how quickly the delay recovers after a host stops sending
+    /// overloaded responses. The delay is halved for every elapsed period.
+    ///
+    /// The default is 10 seconds.
+    pub fn recovery_period(mut self, period: Duration) -> Self {
+        self.recovery_period = period;
+        self
+    }
+}
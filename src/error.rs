@@ -16,8 +16,20 @@ pub enum ErrorKind {
     BadClientCertificate,
 
     /// The server certificate could not be validated.
+    ///
+    /// This also includes failing to load the CA certificates to validate the
+    /// server certificate with, such as when a file given with
+    /// [`CaCertificate::file`](crate::config::CaCertificate::file) or a
+    /// directory given with
+    /// [`CaCertificate::dir`](crate::config::CaCertificate::dir) does not
+    /// exist.
     BadServerCertificate,
 
+    /// The request was canceled before it completed.
+    ///
+    /// See [`HttpClient::cancel_all`](crate::HttpClient::cancel_all).
+    Canceled,
+
     /// The HTTP client failed to initialize.
     ///
     /// This error can occur when trying to create a client with invalid
@@ -30,6 +42,13 @@ pub enum ErrorKind {
     /// the request on the specified port.
     ConnectionFailed,
 
+    /// An interceptor returned an error of its own.
+    ///
+    /// The original error returned by the interceptor can be retrieved with
+    /// [`Error::source`][std::error::Error::source] and downcast to its
+    /// concrete type.
+    Interceptor,
+
     /// The server either returned a response using an unknown or unsupported
     /// encoding format, or the response encoding was malformed.
     InvalidContentEncoding,
@@ -68,6 +87,23 @@ pub enum ErrorKind {
     /// likely to produce the same error.
     ProtocolViolation,
 
+    /// Provided proxy authentication credentials were rejected by the proxy,
+    /// or the proxy required authentication but none was provided.
+    ///
+    /// This error is returned when a proxy answers the `CONNECT` request used
+    /// to open a tunnel through it with `407 Proxy Authentication Required`.
+    /// Requests sent to a plain HTTP proxy without a tunnel receive the
+    /// proxy's response unaltered instead, like any other response.
+    ///
+    /// See
+    /// [`Configurable::proxy_credentials`](crate::config::Configurable::proxy_credentials).
+    ProxyAuthenticationFailed,
+
+    /// A redirect was rejected by the function set using
+    /// [`Configurable::on_redirect`](crate::config::Configurable::on_redirect),
+    /// or because it pointed to a URI scheme other than `http` or `https`.
+    RedirectRejected,
+
     /// Request processing could not continue because the client needed to
     /// re-send the request body, but was unable to rewind the body stream to
     /// the beginning in order to do so.
@@ -79,6 +115,33 @@ pub enum ErrorKind {
     /// [`AsyncBody::from_bytes_static`][crate::AsyncBody::from_bytes_static].
     RequestBodyNotRewindable,
 
+    /// A request body with a known length ended before producing as many bytes
+    /// as its declared length, or a `Content-Length` header was set on the
+    /// request that does not agree with the known length of the body.
+    ///
+    /// This usually means that the length given to
+    /// [`Body::from_reader_sized`][crate::Body::from_reader_sized] or
+    /// [`AsyncBody::from_reader_sized`][crate::AsyncBody::from_reader_sized]
+    /// did not match the actual size of the reader, or that a `Content-Length`
+    /// header was computed incorrectly.
+    RequestBodyLengthMismatch,
+
+    /// The request body has an unknown length, but sending it using chunked
+    /// transfer encoding was disabled.
+    ///
+    /// See
+    /// [`Configurable::require_content_length`](crate::config::Configurable::require_content_length).
+    RequestBodyLengthRequired,
+
+    /// The response headers sent by the server exceeded a configured limit on
+    /// their total size or number.
+    ///
+    /// See
+    /// [`Configurable::max_response_header_size`](crate::config::Configurable::max_response_header_size)
+    /// and
+    /// [`Configurable::max_response_headers`](crate::config::Configurable::max_response_headers).
+    ResponseHeadersTooLarge,
+
     /// A request or operation took longer than the configured timeout time.
     Timeout,
 
@@ -103,8 +166,10 @@ impl ErrorKind {
         match self {
             Self::BadClientCertificate => Some("a problem occurred with the local certificate"),
             Self::BadServerCertificate => Some("the server certificate could not be validated"),
+            Self::Canceled => Some("request was canceled"),
             Self::ClientInitialization => Some("failed to initialize client"),
             Self::ConnectionFailed => Some("failed to connect to the server"),
+            Self::Interceptor => Some("an interceptor returned an error"),
             Self::InvalidContentEncoding => Some(
                 "the server either returned a response using an unknown or unsupported encoding format, or the response encoding was malformed",
             ),
@@ -116,9 +181,18 @@ impl ErrorKind {
             Self::ProtocolViolation => {
                 Some("the server made an unrecoverable HTTP protocol violation")
             }
+            Self::ProxyAuthenticationFailed => {
+                Some("provided proxy authentication credentials were rejected by the proxy")
+            }
+            Self::RedirectRejected => Some("redirect was rejected"),
             Self::RequestBodyNotRewindable => {
                 Some("request body could not be re-sent because it is not rewindable")
             }
+            Self::RequestBodyLengthMismatch => {
+                Some("request body length does not match its declared length")
+            }
+            Self::RequestBodyLengthRequired => Some("request body length required"),
+            Self::ResponseHeadersTooLarge => Some("response headers exceeded the configured limit"),
             Self::Timeout => {
                 Some("request or operation took longer than the configured timeout time")
             }
@@ -164,6 +238,7 @@ pub struct Error(Arc<Inner>);
 struct Inner {
     kind: ErrorKind,
     context: Option<String>,
+    tag: Option<String>,
     source: Option<Box<dyn SourceError>>,
 }
 
@@ -185,10 +260,29 @@ impl Error {
         Self(Arc::new(Inner {
             kind,
             context,
+            tag: None,
             source: Some(Box::new(source)),
         }))
     }
 
+    /// Attach the tag of the request that caused this error.
+    pub(crate) fn with_tag(self, tag: String) -> Self {
+        match Arc::try_unwrap(self.0) {
+            Ok(mut inner) => {
+                inner.tag = Some(tag);
+                Self(Arc::new(inner))
+            }
+
+            // The error is shared, so wrap it instead of modifying it.
+            Err(inner) => Self(Arc::new(Inner {
+                kind: inner.kind.clone(),
+                context: inner.context.clone(),
+                tag: Some(tag),
+                source: Some(Box::new(Self(inner))),
+            })),
+        }
+    }
+
     /// Statically cast a given error into an Isahc error, converting if
     /// necessary.
     ///
@@ -204,10 +298,11 @@ impl Error {
             <error as std::io::Error> => error.into(),
             <error as curl::Error> => {
                 Self::with_context(
-                    if error.is_ssl_certproblem() || error.is_ssl_cacert_badfile() {
+                    if error.is_ssl_certproblem() {
                         ErrorKind::BadClientCertificate
                     } else if error.is_peer_failed_verification()
                         || error.is_ssl_cacert()
+                        || error.is_ssl_cacert_badfile()
                         || error.is_ssl_cipher()
                         || error.is_ssl_issuer_error()
                     {
@@ -271,6 +366,64 @@ impl Error {
         }
     }
 
+    /// Convert an error returned by curl for a transfer, using what is known
+    /// about the transfer to pick a more specific error kind where possible.
+    pub(crate) fn from_transfer<H>(error: curl::Error, easy: &curl::easy::Easy2<H>) -> Self {
+        // Curl reports a proxy rejecting the CONNECT request for a tunnel as a
+        // generic connection failure, so check the proxy's response ourselves.
+        if error.is_couldnt_connect() && easy.http_connectcode().ok() == Some(407) {
+            return Self::with_context(
+                ErrorKind::ProxyAuthenticationFailed,
+                error.extra_description().map(String::from),
+                error,
+            );
+        }
+
+        Self::from_any(error)
+    }
+
+    /// Add a message describing the circumstances of this error.
+    ///
+    /// The message is shown when the error is displayed, ahead of any details
+    /// the error already had. The kind, tag and source of the error are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::error::{Error, ErrorKind};
+    ///
+    /// let error = Error::from(ErrorKind::ConnectionFailed)
+    ///     .with_message("while fetching the user profile");
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "failed to connect to the server: while fetching the user profile"
+    /// );
+    /// ```
+    pub fn with_message(self, message: impl Into<String>) -> Self {
+        let message = message.into();
+
+        let context = |inner: &Inner| match inner.context.as_ref() {
+            Some(context) => format!("{}: {}", message, context),
+            None => message.clone(),
+        };
+
+        match Arc::try_unwrap(self.0) {
+            Ok(mut inner) => {
+                inner.context = Some(context(&inner));
+                Self(Arc::new(inner))
+            }
+
+            // The error is shared, so wrap it instead of modifying it.
+            Err(inner) => Self(Arc::new(Inner {
+                kind: inner.kind.clone(),
+                context: Some(context(&inner)),
+                tag: inner.tag.clone(),
+                source: Some(Box::new(Self(inner))),
+            })),
+        }
+    }
+
     /// Get the kind of error this represents.
     ///
     /// The kind returned may not be matchable against any documented variants
@@ -282,6 +435,12 @@ impl Error {
         &self.0.kind
     }
 
+    /// Get the tag of the request that caused this error, if one was set
+    /// using [`Configurable::tag`](crate::config::Configurable::tag).
+    pub fn tag(&self) -> Option<&str> {
+        self.0.tag.as_deref()
+    }
+
     /// Returns true if this error was likely caused by the client.
     ///
     /// Usually indicates that the client was misconfigured or used to send
@@ -294,7 +453,11 @@ impl Error {
             | ErrorKind::ClientInitialization
             | ErrorKind::InvalidCredentials
             | ErrorKind::InvalidRequest
+            | ErrorKind::ProxyAuthenticationFailed
+            | ErrorKind::RedirectRejected
             | ErrorKind::RequestBodyNotRewindable
+            | ErrorKind::RequestBodyLengthMismatch
+            | ErrorKind::RequestBodyLengthRequired
             | ErrorKind::TlsEngine => true,
             _ => false,
         }
@@ -316,6 +479,7 @@ impl Error {
         match self.kind() {
             ErrorKind::BadServerCertificate
             | ErrorKind::ProtocolViolation
+            | ErrorKind::ResponseHeadersTooLarge
             | ErrorKind::TooManyRedirects => true,
             _ => false,
         }
@@ -365,6 +529,7 @@ impl fmt::Debug for Error {
         f.debug_struct("Error")
             .field("kind", &self.kind())
             .field("context", &self.0.context)
+            .field("tag", &self.0.tag)
             .field("source", &self.source())
             .field(
                 "source_type",
@@ -376,6 +541,10 @@ impl fmt::Debug for Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(tag) = self.0.tag.as_ref() {
+            write!(f, "[{}] ", tag)?;
+        }
+
         if let Some(s) = self.0.context.as_ref() {
             write!(f, "{}: {}", self.kind(), s)
         } else {
@@ -389,6 +558,7 @@ impl From<ErrorKind> for Error {
         Self(Arc::new(Inner {
             kind,
             context: None,
+            tag: None,
             source: None,
         }))
     }
@@ -0,0 +1,143 @@
+//! Support for `file:` URIs, which are answered by reading local files instead
+//! of being sent using curl.
+
+use crate::{
+    body::AsyncBody,
+    error::{Error, ErrorKind},
+    interceptor::InterceptorFuture,
+    parsing::percent_decode,
+    transport::Transport,
+};
+use futures_lite::io::BlockOn;
+use http::{header, Method, Request, Response, StatusCode};
+use std::{fs::File, io, path::PathBuf, thread};
+
+/// Transport that responds to requests for `file:` URIs with the contents of
+/// the local file named by the URI.
+pub(crate) struct FileTransport;
+
+impl Transport for FileTransport {
+    fn execute(&self, request: Request<AsyncBody>) -> InterceptorFuture<'_, Error> {
+        Box::pin(respond(request))
+    }
+}
+
+/// Metadata of an opened file, sent back by the thread reading it.
+struct Opened {
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+}
+
+async fn respond(request: Request<AsyncBody>) -> Result<Response<AsyncBody>, Error> {
+    let path = local_path(request.uri())?;
+
+    let include_body = match *request.method() {
+        Method::GET => true,
+        Method::HEAD => false,
+        _ => {
+            return Ok(Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(header::ALLOW, "GET, HEAD")
+                .body(AsyncBody::empty())?);
+        }
+    };
+
+    let (opened_tx, opened_rx) = async_channel::bounded(1);
+    let (writer, body) = AsyncBody::channel();
+
+    // File I/O blocks, so do it on a separate thread and stream the contents
+    // back through the body.
+    thread::Builder::new()
+        .name(String::from("isahc-file"))
+        .spawn(move || {
+            let opened = File::open(&path).and_then(|file| {
+                let metadata = file.metadata()?;
+
+                if metadata.is_dir() {
+                    return Err(io::Error::new(io::ErrorKind::NotFound, "not a file"));
+                }
+
+                Ok((file, metadata))
+            });
+
+            match opened {
+                Ok((mut file, metadata)) => {
+                    let _ = opened_tx.try_send(Ok(Opened {
+                        len: metadata.len(),
+                        modified: metadata.modified().ok(),
+                    }));
+
+                    if include_body {
+                        if let Err(e) = io::copy(&mut file, &mut BlockOn::new(writer)) {
+                            tracing::debug!("error reading file {:?}: {}", path, e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    let _ = opened_tx.try_send(Err(e));
+                }
+            }
+        })?;
+
+    let opened = match opened_rx.recv().await {
+        Ok(result) => result,
+        Err(_) => return Err(ErrorKind::Unknown.into()),
+    };
+
+    let mut builder = Response::builder();
+
+    match opened {
+        Ok(opened) => {
+            builder = builder
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, opened.len);
+
+            if let Some(modified) = opened.modified {
+                builder = builder.header(header::LAST_MODIFIED, httpdate::fmt_http_date(modified));
+            }
+
+            if include_body {
+                return Ok(builder.body(body)?);
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            builder = builder.status(StatusCode::NOT_FOUND);
+        }
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            builder = builder.status(StatusCode::FORBIDDEN);
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(builder.body(AsyncBody::empty())?)
+}
+
+/// Get the local file path named by a `file:` URI.
+///
+/// Only files on the local host can be accessed, so the host must either be
+/// absent or be `localhost`.
+fn local_path(uri: &http::Uri) -> Result<PathBuf, Error> {
+    match uri.host() {
+        None | Some("") => {}
+        Some(host) if host.eq_ignore_ascii_case("localhost") => {}
+        Some(host) => {
+            return Err(Error::with_context(
+                ErrorKind::InvalidRequest,
+                Some(format!("file URI refers to a remote host: {}", host)),
+                io::Error::from(io::ErrorKind::InvalidInput),
+            ));
+        }
+    }
+
+    let path = percent_decode(uri.path());
+
+    // On Windows, the path starts with a drive letter after the leading slash,
+    // as in `/C:/Windows`.
+    #[cfg(windows)]
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_owned(),
+        _ => path,
+    };
+
+    Ok(PathBuf::from(path))
+}
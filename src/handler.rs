@@ -2,36 +2,160 @@
 
 use crate::{
     body::AsyncBody,
+    config::RequestTrailers,
     error::{Error, ErrorKind},
     metrics::Metrics,
-    parsing::{parse_header, parse_status_line},
-    response::{LocalAddr, RemoteAddr},
+    parsing::{
+        header_to_curl_string,
+        parse_header,
+        parse_nonstandard_status_line,
+        parse_pem_certificate,
+        parse_status_line,
+    },
+    redirect::EffectiveUri,
+    response::{
+        BodyLengths,
+        ConnectionReused,
+        ContentLength,
+        InformationalResponses,
+        LocalAddr,
+        NegotiatedAlpn,
+        OriginalContentEncoding,
+        PeerCertificates,
+        RawStatusLine,
+        RemoteAddr,
+        TlsInfo,
+    },
     trailer::TrailerWriter,
 };
 use async_channel::Sender;
-use curl::easy::{InfoType, ReadError, SeekResult, WriteError};
+use curl::easy::{Easy2, InfoType, ReadError, SeekResult, WriteError};
 use curl_sys::CURL;
-use futures_lite::io::{AsyncRead, AsyncWrite};
+use futures_lite::io::{AsyncBufRead, AsyncRead, AsyncWrite};
 use http::Response;
 use once_cell::sync::OnceCell;
 use sluice::pipe;
 use std::{
     ascii,
-    ffi::CStr,
+    ffi::{CStr, CString},
     fmt,
     future::Future,
     io,
     mem,
     net::SocketAddr,
-    os::raw::{c_char, c_long},
+    os::raw::{c_char, c_int, c_long, c_void},
+    panic::{self, AssertUnwindSafe},
     pin::Pin,
     ptr,
-    sync::Arc,
+    slice,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        Mutex,
+        MutexGuard,
+    },
     task::{Context, Poll, Waker},
+    time::{Duration, Instant},
 };
 
 pub(crate) struct RequestBody(pub(crate) AsyncBody);
 
+/// Info type for getting the TLS session of a transfer's connection. Not
+/// exported by curl-sys.
+#[cfg(all(unix, not(target_os = "macos")))]
+const CURLINFO_TLS_SSL_PTR: curl_sys::CURLINFO = curl_sys::CURLINFO_SLIST + 45;
+
+/// Base of info types that return a `curl_off_t`. Not exported by curl-sys.
+const CURLINFO_OFF_T: curl_sys::CURLINFO = 0x600000;
+
+/// Info type for getting the ID of the last connection used by a transfer.
+/// Not exported by curl-sys.
+const CURLINFO_CONN_ID: curl_sys::CURLINFO = CURLINFO_OFF_T + 64;
+
+/// The `curl_sslbackend` value for OpenSSL.
+#[cfg(all(unix, not(target_os = "macos")))]
+const CURLSSLBACKEND_OPENSSL: c_int = 1;
+
+/// Mirrors `struct curl_tls_sessioninfo`.
+#[cfg(all(unix, not(target_os = "macos")))]
+#[repr(C)]
+struct TlsSessionInfo {
+    backend: c_int,
+    internals: *mut c_void,
+}
+
+/// Calls a function with the final metrics of a transfer once the transfer is
+/// done, but only if it turns out to be the final transfer of its request
+/// rather than a redirect that was followed or an attempt that was retried.
+pub(crate) struct TransferCompletion(Mutex<CompletionState>);
+
+struct CompletionState {
+    callback: Option<Box<CompletionCallback>>,
+    metrics: Option<Metrics>,
+    is_final: bool,
+}
+
+impl TransferCompletion {
+    pub(crate) fn new(f: impl FnOnce(&Metrics) + Send + 'static) -> Self {
+        Self(Mutex::new(CompletionState {
+            callback: Some(Box::new(f)),
+            metrics: None,
+            is_final: false,
+        }))
+    }
+
+    /// Record that the transfer is done.
+    pub(crate) fn transfer_done(&self, metrics: &Metrics) {
+        let mut state = self.0.lock().unwrap();
+        state.metrics = Some(metrics.clone());
+        Self::call_if_ready(state);
+    }
+
+    /// Record that the transfer is the final one of its request.
+    fn set_final(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.is_final = true;
+        Self::call_if_ready(state);
+    }
+
+    fn call_if_ready(mut state: MutexGuard<'_, CompletionState>) {
+        if !state.is_final || state.metrics.is_none() {
+            return;
+        }
+
+        if let Some(f) = state.callback.take() {
+            let metrics = state.metrics.take().unwrap();
+            drop(state);
+
+            // This might run on the agent thread, which must survive a
+            // panicking callback to keep serving other requests.
+            if panic::catch_unwind(AssertUnwindSafe(|| f(&metrics))).is_err() {
+                tracing::error!("request completion callback panicked");
+            }
+        }
+    }
+}
+
+/// Stored in the response extensions to decide whether the transfer of a
+/// response is the final one of its request. The transfer is considered final
+/// once this is dropped, unless it was superseded by a redirect first.
+pub(crate) struct FinalTransfer(pub(crate) Option<Arc<TransferCompletion>>);
+
+impl FinalTransfer {
+    /// Mark the transfer as not being the final one of its request.
+    pub(crate) fn supersede(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for FinalTransfer {
+    fn drop(&mut self) {
+        if let Some(completion) = self.0.take() {
+            completion.set_final();
+        }
+    }
+}
+
 /// Manages the state of a single request/response life cycle.
 ///
 /// During the lifetime of a handler, it will receive callbacks from curl about
@@ -46,7 +170,8 @@ pub(crate) struct RequestBody(pub(crate) AsyncBody);
 ///
 /// If dropped before the response is finished, the associated future will be
 /// completed with an error.
-pub(crate) struct RequestHandler {
+#[allow(unreachable_pub)]
+pub struct RequestHandler {
     /// A tracing span for grouping log events under. Since a request is
     /// processed asynchronously inside an agent thread, this span helps
     /// maintain a link to the parent context where the request is actually
@@ -65,19 +190,46 @@ pub(crate) struct RequestHandler {
     /// The body to be sent in the request.
     request_body: AsyncBody,
 
+    /// Number of bytes read from the request body since it was last rewound.
+    request_body_position: u64,
+
     /// A waker used with reading the request body asynchronously. Populated by
     /// an agent when the request is initialized.
     request_body_waker: Option<Waker>,
 
+    /// Function producing trailer headers to send after the request body, if
+    /// any.
+    request_trailers: Option<RequestTrailers>,
+
     /// Status code of the response.
     response_status_code: Option<http::StatusCode>,
 
     /// HTTP version of the response.
     response_version: Option<http::Version>,
 
+    /// Status line of the response as received, without the line ending.
+    response_status_line: Option<String>,
+
+    /// Set while the next header line received is expected to be the status
+    /// line of a new response.
+    expecting_status_line: bool,
+
+    /// ALPN protocol identifier selected by the server during the TLS
+    /// handshake. This and the TLS info are read when the status line is
+    /// received, as the connection may already be gone by the time the
+    /// response is built.
+    negotiated_alpn: Option<String>,
+
+    /// TLS version and cipher negotiated with the server.
+    tls_info: Option<TlsInfo>,
+
     /// Response headers received so far.
     response_headers: http::HeaderMap,
 
+    /// Status codes and headers of any informational (1xx) responses received
+    /// before the final response.
+    informational_responses: Vec<(http::StatusCode, http::HeaderMap)>,
+
     /// Writing end of the pipe where the response body is written.
     response_body_writer: pipe::PipeWriter,
 
@@ -92,6 +244,56 @@ pub(crate) struct RequestHandler {
     /// Metrics object for publishing metrics data to. Lazily initialized.
     metrics: Option<Metrics>,
 
+    /// Counters for the number of body bytes sent and received.
+    body_lengths: BodyLengths,
+
+    /// Whether curl has been asked to decode compressed response bodies.
+    automatic_decompression: bool,
+
+    /// Maximum total size of response header lines in bytes, if any.
+    max_response_header_size: Option<usize>,
+
+    /// Maximum number of response headers, if any.
+    max_response_headers: Option<usize>,
+
+    /// Total size of response header lines received so far.
+    response_header_size: usize,
+
+    /// Number of response headers received so far.
+    response_header_count: usize,
+
+    /// Maximum amount of time the whole request may take, if any. Curl
+    /// enforces this once the request begins, and the agent enforces it while
+    /// the request is waiting to begin.
+    timeout: Option<Duration>,
+
+    /// Maximum amount of time to wait for the response headers, if any.
+    headers_timeout: Option<Duration>,
+
+    /// Point in time at which the request is aborted if the response headers
+    /// have not been received yet. Set when the request begins.
+    headers_deadline: Option<Instant>,
+
+    /// Priority of the request when waiting to begin in the agent.
+    priority: u8,
+
+    /// Whether the connection should be closed instead of reused if the
+    /// transfer fails.
+    close_on_error: bool,
+
+    /// Function to call with the final metrics once the transfer is done, if
+    /// any.
+    on_complete: Option<Box<CompletionCallback>>,
+
+    /// Function to call with each piece of debug info from curl, if any.
+    debug_callback: Option<Arc<DebugCallback>>,
+
+    /// Set once the headers of the final response have been received.
+    response_headers_received: bool,
+
+    /// List of HTTP 200 aliases given to curl, if any.
+    http_200_aliases: Option<SList>,
+
     /// Raw pointer to the associated curl easy handle. The pointer is not owned
     /// by this struct, but the parent struct to this one, so we know it will be
     /// valid at least for the lifetime of this struct (assuming all other
@@ -99,6 +301,55 @@ pub(crate) struct RequestHandler {
     handle: *mut CURL,
 }
 
+/// A function called with the final metrics of a transfer.
+type CompletionCallback = dyn FnOnce(&Metrics) + Send;
+
+/// A function called with debug info produced by curl.
+pub(crate) type DebugCallback = dyn Fn(DebugInfoType, &[u8]) + Send + Sync;
+
+/// The kind of debug info produced by curl during a transfer.
+///
+/// See [`HttpClientBuilder::debug_callback`](crate::HttpClientBuilder::debug_callback).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DebugInfoType {
+    /// Informational text describing what curl is doing.
+    Text,
+
+    /// Header data received from the server.
+    HeaderIn,
+
+    /// Header data sent to the server.
+    HeaderOut,
+
+    /// Protocol data, such as the response body, received from the server.
+    DataIn,
+
+    /// Protocol data, such as the request body, sent to the server.
+    DataOut,
+
+    /// Encrypted TLS data received from the server.
+    SslDataIn,
+
+    /// Encrypted TLS data sent to the server.
+    SslDataOut,
+}
+
+impl DebugInfoType {
+    fn from_curl(kind: InfoType) -> Option<Self> {
+        match kind {
+            InfoType::Text => Some(Self::Text),
+            InfoType::HeaderIn => Some(Self::HeaderIn),
+            InfoType::HeaderOut => Some(Self::HeaderOut),
+            InfoType::DataIn => Some(Self::DataIn),
+            InfoType::DataOut => Some(Self::DataOut),
+            InfoType::SslDataIn => Some(Self::SslDataIn),
+            InfoType::SslDataOut => Some(Self::SslDataOut),
+            _ => None,
+        }
+    }
+}
+
 // Would be send implicitly except for the raw CURL pointer.
 unsafe impl Send for RequestHandler {}
 
@@ -111,6 +362,21 @@ struct Shared {
     /// to communicate an error while reading the response body if the handler
     /// suddenly aborts.
     result: OnceCell<Result<(), Error>>,
+
+    /// Set once any bytes of the response headers or body have been received.
+    response_data_received: AtomicBool,
+}
+
+/// Allows checking whether a transfer has received any part of a response,
+/// even after the handler has been handed off to the agent.
+pub(crate) struct ResponseProgress(Arc<Shared>);
+
+impl ResponseProgress {
+    /// Check whether any bytes of the response headers or body have been
+    /// received.
+    pub(crate) fn any_data_received(&self) -> bool {
+        self.0.response_data_received.load(Ordering::SeqCst)
+    }
 }
 
 impl RequestHandler {
@@ -130,14 +396,36 @@ impl RequestHandler {
             sender: Some(sender),
             shared: shared.clone(),
             request_body,
+            request_body_position: 0,
             request_body_waker: None,
+            request_trailers: None,
             response_status_code: None,
             response_version: None,
+            response_status_line: None,
+            negotiated_alpn: None,
+            tls_info: None,
+            expecting_status_line: true,
             response_headers: http::HeaderMap::new(),
+            informational_responses: Vec::new(),
             response_body_writer,
             response_body_waker: None,
             response_trailer_writer: TrailerWriter::new(),
             metrics: None,
+            body_lengths: BodyLengths::default(),
+            automatic_decompression: false,
+            max_response_header_size: None,
+            max_response_headers: None,
+            response_header_size: 0,
+            response_header_count: 0,
+            timeout: None,
+            headers_timeout: None,
+            headers_deadline: None,
+            priority: 0,
+            close_on_error: false,
+            on_complete: None,
+            debug_callback: None,
+            response_headers_received: false,
+            http_200_aliases: None,
             handle: ptr::null_mut(),
         };
 
@@ -162,6 +450,158 @@ impl RequestHandler {
         (handler, future)
     }
 
+    /// Get a handle for checking whether this transfer has received any part
+    /// of a response.
+    pub(crate) fn response_progress(&self) -> ResponseProgress {
+        ResponseProgress(self.shared.clone())
+    }
+
+    /// Set a function to generate trailer headers to send after the request
+    /// body has been fully written.
+    ///
+    /// Curl only sends trailer headers when the request body is uploaded using
+    /// chunked transfer encoding.
+    pub(crate) fn set_request_trailers(
+        easy: &mut Easy2<Self>,
+        trailers: RequestTrailers,
+    ) -> Result<(), curl::Error> {
+        const CURLOPT_TRAILERFUNCTION: curl_sys::CURLoption =
+            curl_sys::CURLOPTTYPE_FUNCTIONPOINT + 283;
+        const CURLOPT_TRAILERDATA: curl_sys::CURLoption = curl_sys::CURLOPTTYPE_OBJECTPOINT + 284;
+
+        easy.get_mut().request_trailers = Some(trailers);
+
+        // The handler is boxed inside the easy handle, so this pointer remains
+        // valid for as long as curl may invoke the callback.
+        let data = easy.get_mut() as *mut Self as *mut c_void;
+        let callback: TrailerCallback = trailer_callback;
+
+        unsafe {
+            match curl_sys::curl_easy_setopt(easy.raw(), CURLOPT_TRAILERFUNCTION, callback) {
+                curl_sys::CURLE_OK => {}
+                code => return Err(curl::Error::new(code)),
+            }
+
+            match curl_sys::curl_easy_setopt(easy.raw(), CURLOPT_TRAILERDATA, data) {
+                curl_sys::CURLE_OK => Ok(()),
+                code => Err(curl::Error::new(code)),
+            }
+        }
+    }
+
+    /// Set a list of non-standard status line prefixes that curl should accept
+    /// as `HTTP/1.0 200` responses.
+    pub(crate) fn set_http_200_aliases(
+        easy: &mut Easy2<Self>,
+        aliases: &[String],
+    ) -> Result<(), curl::Error> {
+        let mut list = SList(ptr::null_mut());
+
+        for alias in aliases {
+            let alias = CString::new(alias.as_str())
+                .map_err(|_| curl::Error::new(curl_sys::CURLE_BAD_FUNCTION_ARGUMENT))?;
+
+            // Curl copies the string into the list.
+            let appended = unsafe { curl_sys::curl_slist_append(list.0, alias.as_ptr()) };
+
+            if appended.is_null() {
+                return Err(curl::Error::new(curl_sys::CURLE_OUT_OF_MEMORY));
+            }
+
+            list.0 = appended;
+        }
+
+        unsafe {
+            match curl_sys::curl_easy_setopt(easy.raw(), curl_sys::CURLOPT_HTTP200ALIASES, list.0) {
+                curl_sys::CURLE_OK => {}
+                code => return Err(curl::Error::new(code)),
+            }
+        }
+
+        // Curl does not copy the list itself, so it must live as long as the
+        // handle does.
+        easy.get_mut().http_200_aliases = Some(list);
+
+        Ok(())
+    }
+
+    /// Set whether curl will decode the response body according to its
+    /// `Content-Encoding`.
+    pub(crate) fn set_automatic_decompression(&mut self, enabled: bool) {
+        self.automatic_decompression = enabled;
+    }
+
+    /// Abort the request if the response headers exceed the given total size or
+    /// number of headers.
+    pub(crate) fn set_response_header_limits(&mut self, size: Option<usize>, count: Option<usize>) {
+        self.max_response_header_size = size;
+        self.max_response_headers = count;
+    }
+
+    /// Set the timeout of the whole request that is also set on the curl
+    /// handle.
+    pub(crate) fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Abort the request if the headers of the final response are not received
+    /// within the given amount of time after the request begins.
+    pub(crate) fn set_headers_timeout(&mut self, timeout: Duration) {
+        self.headers_timeout = Some(timeout);
+    }
+
+    /// Set the priority of the request when waiting to begin in the agent.
+    pub(crate) fn set_priority(&mut self, priority: u8) {
+        self.priority = priority;
+    }
+
+    /// Set whether the connection should be closed instead of returned to the
+    /// connection cache if the transfer fails.
+    pub(crate) fn set_close_on_error(&mut self, enabled: bool) {
+        self.close_on_error = enabled;
+    }
+
+    /// Set a function to call with the final metrics once the transfer is
+    /// done, whether it succeeded or not.
+    ///
+    /// Progress reporting must be enabled on the handle for the progress
+    /// metrics to be collected.
+    pub(crate) fn set_on_complete(&mut self, f: impl FnOnce(&Metrics) + Send + 'static) {
+        self.on_complete = Some(Box::new(f));
+    }
+
+    /// Set a function to call with each piece of debug info from curl.
+    ///
+    /// Verbose mode must be enabled on the handle for curl to produce debug
+    /// info.
+    pub(crate) fn set_debug_callback(&mut self, f: Arc<DebugCallback>) {
+        self.debug_callback = Some(f);
+    }
+
+    /// Get the priority of the request when waiting to begin in the agent.
+    pub(crate) fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    /// Get the timeout of the whole request, if any.
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Get the point in time at which the request should be aborted if the
+    /// response headers have not been received by then. The agent is
+    /// responsible for enforcing this deadline.
+    ///
+    /// Returns `None` if there is no headers timeout, or if the response
+    /// headers have already been received.
+    pub(crate) fn headers_deadline(&self) -> Option<Instant> {
+        if self.response_headers_received {
+            None
+        } else {
+            self.headers_deadline
+        }
+    }
+
     /// Check whether debug info should be generated. This function is used to
     /// determine whether to set the `verbose` curl option to true.
     pub(crate) fn is_debug_enabled(&self) -> bool {
@@ -180,7 +620,7 @@ impl RequestHandler {
         }
     }
 
-    fn is_future_canceled(&self) -> bool {
+    pub(crate) fn is_future_canceled(&self) -> bool {
         self.sender.as_ref().map(Sender::is_closed).unwrap_or(false)
     }
 
@@ -204,12 +644,17 @@ impl RequestHandler {
 
         self.span.record("id", &id);
         self.handle = handle;
+        self.headers_deadline = self.headers_timeout.map(|timeout| Instant::now() + timeout);
         self.request_body_waker = Some(request_waker);
         self.response_body_waker = Some(response_waker);
     }
 
     /// Set the final result for this transfer.
     pub(crate) fn set_result(&mut self, result: Result<(), Error>) {
+        if result.is_err() {
+            self.forbid_connection_reuse();
+        }
+
         if self.shared.result.set(result).is_err() {
             tracing::debug!("attempted to set error multiple times");
         }
@@ -219,6 +664,94 @@ impl RequestHandler {
 
         // Complete the response future, if we haven't already.
         self.complete_response_future();
+
+        if let Some(f) = self.on_complete.take() {
+            // Refresh the metrics, since the last progress update may have
+            // been some time before the transfer finished.
+            f(self.update_metrics());
+        }
+    }
+
+    /// Make sure the connection used by this transfer is closed once the
+    /// transfer is done, if enabled.
+    ///
+    /// Curl only honors this if it is set before the transfer is done, so this
+    /// has no effect on errors that curl detects by itself. Curl closes HTTP/1
+    /// connections after such errors anyway.
+    fn forbid_connection_reuse(&mut self) {
+        if !self.close_on_error || self.handle.is_null() {
+            return;
+        }
+
+        unsafe {
+            curl_sys::curl_easy_setopt(self.handle, curl_sys::CURLOPT_FORBID_REUSE, 1 as c_long);
+        }
+    }
+
+    /// Scrape speed and timing metrics from curl, initializing the metrics if
+    /// required.
+    fn update_metrics(&mut self) -> &Metrics {
+        let metrics = self.metrics.get_or_insert_with(Metrics::new);
+
+        if !self.handle.is_null() {
+            unsafe {
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_SPEED_UPLOAD,
+                    metrics.inner.upload_speed.as_ptr(),
+                );
+
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_SPEED_DOWNLOAD,
+                    metrics.inner.download_speed.as_ptr(),
+                );
+
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_NAMELOOKUP_TIME,
+                    metrics.inner.namelookup_time.as_ptr(),
+                );
+
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_CONNECT_TIME,
+                    metrics.inner.connect_time.as_ptr(),
+                );
+
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_APPCONNECT_TIME,
+                    metrics.inner.appconnect_time.as_ptr(),
+                );
+
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_PRETRANSFER_TIME,
+                    metrics.inner.pretransfer_time.as_ptr(),
+                );
+
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_STARTTRANSFER_TIME,
+                    metrics.inner.starttransfer_time.as_ptr(),
+                );
+
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_TOTAL_TIME,
+                    metrics.inner.total_time.as_ptr(),
+                );
+
+                curl_sys::curl_easy_getinfo(
+                    self.handle,
+                    curl_sys::CURLINFO_REDIRECT_TIME,
+                    metrics.inner.redirect_time.as_ptr(),
+                );
+            }
+        }
+
+        metrics
     }
 
     /// Mark the future as completed successfully with the response headers
@@ -252,10 +785,61 @@ impl RequestHandler {
             builder = builder.version(version);
         }
 
+        if let Some(line) = self.response_status_line.take() {
+            builder = builder.extension(RawStatusLine(line));
+        }
+
+        if let Some(protocol) = self.negotiated_alpn.take() {
+            builder = builder.extension(NegotiatedAlpn(protocol));
+        }
+
+        if let Some(info) = self.tls_info.take() {
+            builder = builder.extension(info);
+        }
+
+        // Curl leaves the Content-Encoding header in place after decoding the
+        // body, so remember what the body was originally encoded with.
+        if self.automatic_decompression {
+            if let Some(encoding) = self
+                .response_headers
+                .get(http::header::CONTENT_ENCODING)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty() && !value.eq_ignore_ascii_case("identity"))
+            {
+                builder = builder.extension(OriginalContentEncoding(encoding.to_owned()));
+            }
+        }
+
+        // The server may have announced the length of the body some other way
+        // than a Content-Length header that curl still knows about.
+        if !self
+            .response_headers
+            .contains_key(http::header::CONTENT_LENGTH)
+        {
+            if let Some(len) = self.get_content_length() {
+                builder = builder.extension(ContentLength(len));
+            }
+        }
+
+        if self.get_num_connects() == Some(0) {
+            builder = builder.extension(ConnectionReused);
+        }
+
         if let Some(headers) = builder.headers_mut() {
             headers.extend(self.response_headers.drain());
         }
 
+        if !self.informational_responses.is_empty() {
+            builder = builder.extension(InformationalResponses(mem::take(
+                &mut self.informational_responses,
+            )));
+        }
+
+        if let Some(uri) = self.get_effective_uri() {
+            builder = builder.extension(EffectiveUri(uri));
+        }
+
         if let Some(addr) = self.get_local_addr() {
             builder = builder.extension(LocalAddr(addr));
         }
@@ -264,6 +848,10 @@ impl RequestHandler {
             builder = builder.extension(RemoteAddr(addr));
         }
 
+        if let Some(certificates) = self.get_peer_certificates() {
+            builder = builder.extension(PeerCertificates(certificates));
+        }
+
         // Keep the request body around in case interceptors need access to
         // it. Otherwise we're just going to drop it later.
         builder = builder.extension(RequestBody(mem::take(&mut self.request_body)));
@@ -278,9 +866,35 @@ impl RequestHandler {
             builder = builder.extension(metrics);
         }
 
+        builder = builder.extension(self.body_lengths.clone());
+
         builder
     }
 
+    /// Get the last URI used by curl for this transfer, after any
+    /// normalization it performed on the request URI.
+    fn get_effective_uri(&mut self) -> Option<http::Uri> {
+        if self.handle.is_null() {
+            return None;
+        }
+
+        let mut ptr = ptr::null::<c_char>();
+
+        unsafe {
+            if curl_sys::curl_easy_getinfo(self.handle, curl_sys::CURLINFO_EFFECTIVE_URL, &mut ptr)
+                != curl_sys::CURLE_OK
+            {
+                return None;
+            }
+        }
+
+        if ptr.is_null() {
+            return None;
+        }
+
+        unsafe { CStr::from_ptr(ptr) }.to_str().ok()?.parse().ok()
+    }
+
     fn get_primary_addr(&mut self) -> Option<SocketAddr> {
         let ip = self.get_primary_ip()?.parse().ok()?;
         let port = self.get_primary_port()?;
@@ -328,6 +942,208 @@ impl RequestHandler {
         Some(port as u16)
     }
 
+    /// Get the peer certificate chain, if certificate info collection was
+    /// enabled for this transfer.
+    fn get_peer_certificates(&mut self) -> Option<Vec<Vec<u8>>> {
+        if self.handle.is_null() {
+            return None;
+        }
+
+        let mut info = ptr::null_mut::<curl_sys::curl_certinfo>();
+
+        unsafe {
+            if curl_sys::curl_easy_getinfo(self.handle, curl_sys::CURLINFO_CERTINFO, &mut info)
+                != curl_sys::CURLE_OK
+            {
+                return None;
+            }
+        }
+
+        if info.is_null() {
+            return None;
+        }
+
+        let info = unsafe { &*info };
+
+        if info.num_of_certs <= 0 || info.certinfo.is_null() {
+            return None;
+        }
+
+        let mut certificates = Vec::with_capacity(info.num_of_certs as usize);
+
+        for i in 0..info.num_of_certs as usize {
+            // Each certificate is described by a list of "name:value" strings.
+            // We only care about the PEM-encoded certificate itself.
+            let mut item = unsafe { *info.certinfo.add(i) };
+
+            while !item.is_null() {
+                let data = unsafe { CStr::from_ptr((*item).data) };
+
+                if let Some(pem) = data.to_str().ok().and_then(|s| s.strip_prefix("Cert:")) {
+                    if let Some(der) = parse_pem_certificate(pem) {
+                        certificates.push(der);
+                    }
+                }
+
+                item = unsafe { (*item).next };
+            }
+        }
+
+        Some(certificates)
+    }
+
+    /// Check whether curl is holding this transfer back until a connection
+    /// becomes available, because of a connection limit.
+    ///
+    /// Transfers that curl has not picked up yet are included. Versions of curl
+    /// older than 8.2 cannot tell, in which case this is always `false`.
+    pub(crate) fn is_waiting_for_connection(&self) -> bool {
+        if self.handle.is_null() {
+            return false;
+        }
+
+        let mut id: curl_sys::curl_off_t = 0;
+
+        unsafe {
+            curl_sys::curl_easy_getinfo(self.handle, CURLINFO_CONN_ID, &mut id)
+                == curl_sys::CURLE_OK
+                && id < 0
+        }
+    }
+
+    /// Get the OpenSSL session of the connection used by this transfer, if the
+    /// connection uses TLS and curl uses OpenSSL as its SSL/TLS engine.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn get_openssl_session(&self) -> Option<*mut openssl_sys::SSL> {
+        if self.handle.is_null() {
+            return None;
+        }
+
+        let mut info = ptr::null_mut::<TlsSessionInfo>();
+
+        unsafe {
+            if curl_sys::curl_easy_getinfo(self.handle, CURLINFO_TLS_SSL_PTR, &mut info)
+                != curl_sys::CURLE_OK
+            {
+                return None;
+            }
+        }
+
+        if info.is_null() {
+            return None;
+        }
+
+        let info = unsafe { &*info };
+
+        if info.backend == CURLSSLBACKEND_OPENSSL && !info.internals.is_null() {
+            Some(info.internals as *mut openssl_sys::SSL)
+        } else {
+            None
+        }
+    }
+
+    /// Get the ALPN protocol identifier selected by the server during the TLS
+    /// handshake, if any.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn get_negotiated_alpn(&self) -> Option<String> {
+        let ssl = self.get_openssl_session()?;
+        let mut data = ptr::null();
+        let mut len = 0;
+
+        unsafe {
+            openssl_sys::SSL_get0_alpn_selected(ssl, &mut data, &mut len);
+        }
+
+        if data.is_null() || len == 0 {
+            return None;
+        }
+
+        let protocol = unsafe { slice::from_raw_parts(data, len as usize) };
+
+        String::from_utf8(protocol.to_vec()).ok()
+    }
+
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    fn get_negotiated_alpn(&self) -> Option<String> {
+        None
+    }
+
+    /// Get the TLS version and cipher negotiated with the server.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn get_tls_info(&self) -> Option<TlsInfo> {
+        let ssl = self.get_openssl_session()?;
+
+        unsafe {
+            let version = openssl_sys::SSL_get_version(ssl);
+            let cipher = openssl_sys::SSL_get_current_cipher(ssl);
+
+            if version.is_null() || cipher.is_null() {
+                return None;
+            }
+
+            let cipher = openssl_sys::SSL_CIPHER_get_name(cipher);
+
+            if cipher.is_null() {
+                return None;
+            }
+
+            Some(TlsInfo {
+                version: CStr::from_ptr(version).to_string_lossy().into_owned(),
+                cipher: CStr::from_ptr(cipher).to_string_lossy().into_owned(),
+            })
+        }
+    }
+
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    fn get_tls_info(&self) -> Option<TlsInfo> {
+        None
+    }
+
+    /// Get the length of the response body as determined by curl, if known.
+    fn get_content_length(&mut self) -> Option<u64> {
+        if self.handle.is_null() {
+            return None;
+        }
+
+        let mut len: f64 = -1.0;
+
+        unsafe {
+            if curl_sys::curl_easy_getinfo(
+                self.handle,
+                curl_sys::CURLINFO_CONTENT_LENGTH_DOWNLOAD,
+                &mut len,
+            ) != curl_sys::CURLE_OK
+            {
+                return None;
+            }
+        }
+
+        if len >= 0.0 {
+            Some(len as u64)
+        } else {
+            None
+        }
+    }
+
+    /// Get the number of new connections curl had to open for the transfer.
+    fn get_num_connects(&mut self) -> Option<c_long> {
+        if self.handle.is_null() {
+            return None;
+        }
+
+        let mut count: c_long = 0;
+
+        unsafe {
+            if curl_sys::curl_easy_getinfo(self.handle, curl_sys::CURLINFO_NUM_CONNECTS, &mut count)
+                != curl_sys::CURLE_OK
+            {
+                return None;
+            }
+        }
+
+        Some(count)
+    }
+
     fn get_local_addr(&mut self) -> Option<SocketAddr> {
         let ip = self.get_local_ip()?.parse().ok()?;
         let port = self.get_local_port()?;
@@ -376,6 +1192,66 @@ impl RequestHandler {
     }
 }
 
+/// A curl string list owned by us rather than by curl.
+struct SList(*mut curl_sys::curl_slist);
+
+impl Drop for SList {
+    fn drop(&mut self) {
+        unsafe {
+            curl_sys::curl_slist_free_all(self.0);
+        }
+    }
+}
+
+type TrailerCallback = extern "C" fn(*mut *mut curl_sys::curl_slist, *mut c_void) -> c_int;
+
+/// Gets called by curl after the request body has been sent in order to
+/// collect trailer headers to send.
+extern "C" fn trailer_callback(list: *mut *mut curl_sys::curl_slist, data: *mut c_void) -> c_int {
+    const CURL_TRAILERFUNC_OK: c_int = 0;
+    const CURL_TRAILERFUNC_ABORT: c_int = 1;
+
+    let handler = unsafe { &*(data as *const RequestHandler) };
+
+    let trailers = match handler.request_trailers.as_ref() {
+        Some(trailers) => trailers,
+        None => return CURL_TRAILERFUNC_OK,
+    };
+
+    let _enter = handler.span.enter();
+
+    // Panics must not unwind across the FFI boundary.
+    let headers = match panic::catch_unwind(AssertUnwindSafe(|| {
+        let headers = (trailers.0)();
+
+        headers
+            .iter()
+            .map(|(name, value)| CString::new(header_to_curl_string(name, value, false)))
+            .collect::<Result<Vec<_>, _>>()
+    })) {
+        Ok(Ok(headers)) => headers,
+        _ => {
+            tracing::warn!("failed to generate request trailer headers");
+            return CURL_TRAILERFUNC_ABORT;
+        }
+    };
+
+    // Curl takes ownership of the list and frees it when it is done.
+    for header in headers {
+        let appended = unsafe { curl_sys::curl_slist_append(*list, header.as_ptr()) };
+
+        if appended.is_null() {
+            return CURL_TRAILERFUNC_ABORT;
+        }
+
+        unsafe {
+            *list = appended;
+        }
+    }
+
+    CURL_TRAILERFUNC_OK
+}
+
 impl curl::easy::Handler for RequestHandler {
     /// Gets called by curl for each line of data in the HTTP response header.
     fn header(&mut self, data: &[u8]) -> bool {
@@ -387,6 +1263,33 @@ impl curl::easy::Handler for RequestHandler {
         let span = tracing::trace_span!(parent: &self.span, "header");
         let _enter = span.enter();
 
+        self.shared
+            .response_data_received
+            .store(true, Ordering::SeqCst);
+
+        // Guard against servers flooding us with headers.
+        self.response_header_size += data.len();
+
+        if let Some(max) = self.max_response_header_size {
+            if self.response_header_size > max {
+                tracing::debug!("response headers exceeded maximum size of {} bytes", max);
+                self.set_result(Err(ErrorKind::ResponseHeadersTooLarge.into()));
+                return false;
+            }
+        }
+
+        if let Some(max) = self.max_response_headers {
+            if data != b"\r\n" && parse_status_line(data).is_none() {
+                self.response_header_count += 1;
+
+                if self.response_header_count > max {
+                    tracing::debug!("response exceeded maximum of {} headers", max);
+                    self.set_result(Err(ErrorKind::ResponseHeadersTooLarge.into()));
+                    return false;
+                }
+            }
+        }
+
         // If we already returned the response headers, then this header is from
         // the trailer.
         if self.sender.is_none() {
@@ -404,10 +1307,27 @@ impl curl::easy::Handler for RequestHandler {
         // parse it, just as if we were reading from the socket of a HTTP/1.0 or
         // HTTP/1.1 connection ourselves.
 
-        // Is this the status line?
-        if let Some((version, status)) = parse_status_line(data) {
+        let expecting_status_line = mem::replace(&mut self.expecting_status_line, false);
+
+        // Is this the status line? Curl also accepts status lines using a
+        // non-standard protocol token if configured with an HTTP 200 alias,
+        // in which case it treats the response as HTTP/1.0.
+        if let Some((version, status)) = parse_status_line(data).or_else(|| {
+            if expecting_status_line {
+                parse_nonstandard_status_line(data).map(|status| (http::Version::HTTP_10, status))
+            } else {
+                None
+            }
+        }) {
             self.response_version = Some(version);
             self.response_status_code = Some(status);
+            self.response_status_line = Some(
+                String::from_utf8_lossy(data)
+                    .trim_end_matches(&['\r', '\n'][..])
+                    .to_owned(),
+            );
+            self.negotiated_alpn = self.get_negotiated_alpn();
+            self.tls_info = self.get_tls_info();
 
             // Also clear any pre-existing headers that might be left over from
             // a previous intermediate response.
@@ -424,6 +1344,19 @@ impl curl::easy::Handler for RequestHandler {
 
         // Is this the end of the response header?
         if data == b"\r\n" {
+            self.expecting_status_line = true;
+
+            // Hold on to the headers of informational responses, as the
+            // response headers will be cleared once the next response begins.
+            if let Some(status) = self.response_status_code {
+                if status.is_informational() {
+                    self.informational_responses
+                        .push((status, mem::take(&mut self.response_headers)));
+                } else {
+                    self.response_headers_received = true;
+                }
+            }
+
             // We will acknowledge the end of the header, but we can't complete
             // our response future yet. If curl decides to follow a redirect,
             // then this current response is not the final response and not the
@@ -455,7 +1388,40 @@ impl curl::easy::Handler for RequestHandler {
 
             match Pin::new(&mut self.request_body).poll_read(&mut context, data) {
                 Poll::Pending => Err(ReadError::Pause),
-                Poll::Ready(Ok(len)) => Ok(len),
+                Poll::Ready(Ok(0)) if !data.is_empty() => {
+                    // If the body ends before reaching its declared length,
+                    // curl would fail with a rather confusing error, so fail
+                    // with a more helpful one first.
+                    if let Some(declared) = self
+                        .request_body
+                        .len()
+                        .filter(|&len| len > self.request_body_position)
+                    {
+                        tracing::error!(
+                            "request body ended after {} of {} bytes",
+                            self.request_body_position,
+                            declared
+                        );
+
+                        self.set_result(Err(Error::with_context(
+                            ErrorKind::RequestBodyLengthMismatch,
+                            Some(format!(
+                                "declared length {}, produced {}",
+                                declared, self.request_body_position
+                            )),
+                            io::Error::from(io::ErrorKind::UnexpectedEof),
+                        )));
+
+                        return Err(ReadError::Abort);
+                    }
+
+                    Ok(0)
+                }
+                Poll::Ready(Ok(len)) => {
+                    self.request_body_position += len as u64;
+                    self.body_lengths.0.sent.fetch_add(len as u64);
+                    Ok(len)
+                }
                 Poll::Ready(Err(e)) => {
                     tracing::error!("error reading request body: {}", e);
 
@@ -490,6 +1456,7 @@ impl curl::easy::Handler for RequestHandler {
         // If curl wants to seek to the beginning, there's a chance that we
         // can do that.
         if whence == io::SeekFrom::Start(0) && self.request_body.reset() {
+            self.request_body_position = 0;
             SeekResult::Ok
         } else {
             tracing::warn!("seek requested for request body, but it is not supported");
@@ -504,6 +1471,10 @@ impl curl::easy::Handler for RequestHandler {
         let _enter = span.enter();
         tracing::trace!("received {} bytes of data", data.len());
 
+        self.shared
+            .response_data_received
+            .store(true, Ordering::SeqCst);
+
         // Now that we've started receiving the response body, we know no more
         // redirects can happen and we can complete the future safely.
         self.complete_response_future();
@@ -515,7 +1486,10 @@ impl curl::easy::Handler for RequestHandler {
 
             match Pin::new(&mut self.response_body_writer).poll_write(&mut context, data) {
                 Poll::Pending => Err(WriteError::Pause),
-                Poll::Ready(Ok(len)) => Ok(len),
+                Poll::Ready(Ok(len)) => {
+                    self.body_lengths.0.received.fetch_add(len as u64);
+                    Ok(len)
+                }
                 Poll::Ready(Err(e)) => {
                     if e.kind() == io::ErrorKind::BrokenPipe {
                         tracing::info!(
@@ -523,6 +1497,7 @@ impl curl::easy::Handler for RequestHandler {
                         );
                     } else {
                         tracing::error!("error writing response body to buffer: {}", e);
+                        self.forbid_connection_reuse();
                     }
                     Ok(0)
                 }
@@ -546,63 +1521,7 @@ impl curl::easy::Handler for RequestHandler {
         metrics.inner.download_total.store(dltotal);
 
         // Also scrape additional metrics.
-        if !self.handle.is_null() {
-            unsafe {
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_SPEED_UPLOAD,
-                    metrics.inner.upload_speed.as_ptr(),
-                );
-
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_SPEED_DOWNLOAD,
-                    metrics.inner.download_speed.as_ptr(),
-                );
-
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_NAMELOOKUP_TIME,
-                    metrics.inner.namelookup_time.as_ptr(),
-                );
-
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_CONNECT_TIME,
-                    metrics.inner.connect_time.as_ptr(),
-                );
-
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_APPCONNECT_TIME,
-                    metrics.inner.appconnect_time.as_ptr(),
-                );
-
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_PRETRANSFER_TIME,
-                    metrics.inner.pretransfer_time.as_ptr(),
-                );
-
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_STARTTRANSFER_TIME,
-                    metrics.inner.starttransfer_time.as_ptr(),
-                );
-
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_TOTAL_TIME,
-                    metrics.inner.total_time.as_ptr(),
-                );
-
-                curl_sys::curl_easy_getinfo(
-                    self.handle,
-                    curl_sys::CURLINFO_REDIRECT_TIME,
-                    metrics.inner.redirect_time.as_ptr(),
-                );
-            }
-        }
+        self.update_metrics();
 
         true
     }
@@ -625,6 +1544,16 @@ impl curl::easy::Handler for RequestHandler {
             }
         }
 
+        if let Some(f) = self.debug_callback.as_ref() {
+            if let Some(kind) = DebugInfoType::from_curl(kind) {
+                // This runs on the agent thread, which must survive a
+                // panicking callback to keep serving other requests.
+                if panic::catch_unwind(AssertUnwindSafe(|| f(kind, data))).is_err() {
+                    tracing::error!("debug callback panicked");
+                }
+            }
+        }
+
         match kind {
             InfoType::Text => {
                 tracing::debug!("{}", String::from_utf8_lossy(data).trim_end())
@@ -678,3 +1607,24 @@ impl AsyncRead for ResponseBodyReader {
         }
     }
 }
+
+impl AsyncBufRead for ResponseBodyReader {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+
+        match Pin::new(&mut this.inner).poll_fill_buf(cx) {
+            // On EOF, check to see if the transfer was cancelled, and if so,
+            // return an error.
+            Poll::Ready(Ok(buf)) if buf.is_empty() => match this.shared.result.get() {
+                Some(Ok(())) => Poll::Ready(Ok(buf)),
+                Some(Err(e)) => Poll::Ready(Err(io::Error::from(e.clone()))),
+                None => Poll::Ready(Err(io::ErrorKind::ConnectionAborted.into())),
+            },
+            poll => poll,
+        }
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        Pin::new(&mut self.inner).consume(amt)
+    }
+}
@@ -8,13 +8,6 @@ use http::header::HeaderMap;
 pub(crate) trait HasHeaders {
     fn headers(&self) -> &HeaderMap;
 
-    fn content_length(&self) -> Option<u64> {
-        self.headers()
-            .get(http::header::CONTENT_LENGTH)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|v| v.parse().ok())
-    }
-
     fn content_type(&self) -> Option<&str> {
         self.headers()
             .get(http::header::CONTENT_TYPE)
@@ -11,11 +11,6 @@
 //!   generic over a lifetime, there's no way to give the return type the
 //!   correct name using current Rust syntax.
 //! - [`InterceptorObj`] wraps the returned future in an extra box.
-//! - If an interceptor returns a custom error, it is stringified and wrapped in
-//!   `Error::Curl`. We should introduce a new error variant that boxes the
-//!   error and also records the type of the interceptor that created the error
-//!   for visibility. But we can't add a new variant right now without a BC
-//!   break. See [#182](https://github.com/sagebind/isahc/issues/182).
 //! - Automatic redirect following currently bypasses interceptors for
 //!   subsequent requests. This will be fixed when redirect handling is
 //!   rewritten as an interceptor itself. See
@@ -63,6 +58,11 @@ macro_rules! interceptor {
 /// made in parallel.
 pub trait Interceptor: Send + Sync {
     /// The type of error returned by this interceptor.
+    ///
+    /// If this is not [`Error`](crate::Error), then any error returned is
+    /// surfaced to the caller as an error of kind
+    /// [`ErrorKind::Interceptor`](crate::error::ErrorKind::Interceptor), with
+    /// the original error as its source.
     type Err: Error + Send + Sync + 'static;
 
     /// Intercept a request, returning a response.
@@ -1,5 +1,8 @@
 use super::{Context, Interceptor, InterceptorFuture};
-use crate::{body::AsyncBody, error::Error};
+use crate::{
+    body::AsyncBody,
+    error::{Error, ErrorKind},
+};
 use http::Request;
 
 /// Type-erased interceptor object.
@@ -39,6 +42,13 @@ impl<I: Interceptor> DynInterceptor for I {
         request: Request<AsyncBody>,
         cx: Context<'a>,
     ) -> InterceptorFuture<'a, Error> {
-        Box::pin(async move { self.intercept(request, cx).await.map_err(Error::from_any) })
+        Box::pin(async move {
+            self.intercept(request, cx).await.map_err(|error| {
+                match_type! {
+                    <error as Error> => error,
+                    error => Error::new(ErrorKind::Interceptor, error),
+                }
+            })
+        })
     }
 }
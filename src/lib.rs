@@ -100,6 +100,36 @@
 //! - [`AsyncReadResponseExt`]: Consume an asynchronous response body in a
 //!   variety of ways.
 //!
+//! All of these traits, along with the most commonly used configuration types,
+//! are also available from the crate root. If you prefer to import exactly
+//! what you use instead of the whole prelude, you can do that too:
+//!
+//! ```no_run
+//! use isahc::{
+//!     Configurable,
+//!     HttpClient,
+//!     ReadResponseExt,
+//!     RedirectPolicy,
+//!     Request,
+//!     ResponseExt,
+//! };
+//! use std::time::Duration;
+//!
+//! let client = HttpClient::builder()
+//!     .redirect_policy(RedirectPolicy::Follow)
+//!     .build()?;
+//!
+//! let request = Request::get("https://example.org")
+//!     .timeout(Duration::from_secs(5))
+//!     .body(())?;
+//!
+//! let mut response = client.send(request)?;
+//!
+//! println!("Effective URI: {:?}", response.effective_uri());
+//! println!("{}", response.text()?);
+//! # Ok::<(), isahc::Error>(())
+//! ```
+//!
 //! ## Custom clients
 //!
 //! The free-standing functions for sending requests use a shared [`HttpClient`]
@@ -135,6 +165,26 @@
 //! [`text`](AsyncReadResponseExt::text)) will also automatically be selected by
 //! the compiler.
 //!
+//! ## Local files
+//!
+//! Clients can optionally answer requests for `file:` URIs by reading the
+//! named local file instead of going over the network, so that applications
+//! accepting URIs from trusted sources do not need to handle them separately.
+//! This is disabled by default and has to be turned on using
+//! [`Configurable::allow_file_uris`](config::Configurable::allow_file_uris).
+//! Note that the [`http`] URI type does not accept URIs with an empty host such
+//! as `file:///etc/hosts`, so the host has to be given explicitly, as in
+//! `file://localhost/etc/hosts`.
+//!
+//! ```no_run
+//! use isahc::{prelude::*, HttpClient};
+//!
+//! let client = HttpClient::builder().allow_file_uris(true).build()?;
+//! let mut response = client.get("file://localhost/etc/hosts")?;
+//! println!("{}", response.text()?);
+//! # Ok::<(), isahc::Error>(())
+//! ```
+//!
 //! # Feature flags
 //!
 //! Isahc is designed to be as "pay-as-you-need" as possible using Cargo feature
@@ -150,6 +200,12 @@
 //!
 //! Below is a list of all available feature flags and their meanings.
 //!
+//! ## `bytes`
+//!
+//! Enable reading response bodies into a reference-counted
+//! [`Bytes`](https://docs.rs/bytes) buffer that can be cheaply shared, via
+//! [`ReadResponseExt::bytes_shared`]. Disabled by default.
+//!
 //! ## `cookies`
 //!
 //! Enable persistent HTTP cookie support. Disabled by default.
@@ -168,11 +224,24 @@
 //! Additional serialization and deserialization of JSON bodies via
 //! [serde](https://serde.rs). Disabled by default.
 //!
+//! ## `mock`
+//!
+//! Enable [`MockTransport`](mock::MockTransport), which lets clients return
+//! canned responses instead of making network requests, for testing code that
+//! uses Isahc. Disabled by default.
+//!
 //! ## `psl`
 //!
 //! Enable use of the Public Suffix List to filter out potentially malicious
 //! cross-domain cookies. Implies `cookies`, disabled by default.
 //!
+//! ## `request-compression`
+//!
+//! Enable support for compressing request bodies with gzip or deflate before
+//! sending them, via
+//! [`Configurable::request_body_compression`](config::Configurable::request_body_compression).
+//! Disabled by default.
+//!
 //! ## `spnego`
 //!
 //! Enable support for [SPNEGO-based HTTP
@@ -182,6 +251,13 @@
 //! Kerberos](https://web.mit.edu/kerberos/) headers must be pre-installed at
 //! compile time.
 //!
+//! ## `sse`
+//!
+//! Enable parsing of [Server-Sent
+//! Events](https://html.spec.whatwg.org/multipage/server-sent-events.html)
+//! response bodies into a stream of events, via
+//! [`ResponseExt::events`]. Disabled by default.
+//!
 //! ## `static-curl`
 //!
 //! Use a bundled libcurl version and statically link to it. Enabled by default.
@@ -202,6 +278,15 @@
 //! Unstable until the API is finalized. This an unstable feature whose
 //! interface may change between patch releases.
 //!
+//! ### `unstable-raw-handle`
+//!
+//! Enable
+//! [`Configurable::with_raw_handle`](config::Configurable::with_raw_handle),
+//! an escape hatch for configuring the underlying curl handle of a request
+//! directly. This exposes types from the [curl](https://docs.rs/curl) crate
+//! and implementation details of Isahc, so its interface may change between
+//! patch releases.
+//!
 //! # Logging and tracing
 //!
 //! Isahc logs quite a bit of useful information at various levels compatible
@@ -245,6 +330,7 @@ mod agent;
 mod body;
 mod client;
 mod default_headers;
+mod file;
 mod handler;
 mod headers;
 mod metrics;
@@ -254,11 +340,21 @@ mod request;
 mod response;
 mod task;
 mod text;
+mod throttle;
 mod trailer;
+mod transport;
+mod tunnel;
 
 pub mod auth;
 pub mod config;
 pub mod error;
+pub mod multipart;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+#[cfg(feature = "sse")]
+pub mod sse;
 
 #[cfg(feature = "unstable-interceptors")]
 pub mod interceptor;
@@ -267,19 +363,37 @@ pub mod interceptor;
 pub(crate) mod interceptor;
 
 pub use crate::{
-    body::{AsyncBody, Body},
-    client::{HttpClient, HttpClientBuilder, ResponseFuture},
+    body::{AsyncBody, AsyncBodyWriter, Body, BodyWriter, IdleTimeout, ReusableBody},
+    client::{
+        ClientStats,
+        HttpClient,
+        HttpClientBuilder,
+        OrderedResponseStream,
+        ResponseFuture,
+        ResponseStream,
+        WarmUpFuture,
+    },
     error::Error,
+    handler::DebugInfoType,
     http::{request::Request, response::Response},
-    metrics::Metrics,
-    request::RequestExt,
-    response::{AsyncReadResponseExt, ReadResponseExt, ResponseExt},
+    metrics::{Metrics, MetricsSnapshot},
+    request::{RequestBuilderExt, RequestExt},
+    response::{AsyncReadResponseExt, ReadResponseExt, ResponseExt, RetryAfter, TlsInfo},
     trailer::Trailer,
+    tunnel::{Tunnel, TunnelFuture},
 };
 
+#[doc(no_inline)]
+pub use crate::config::{Configurable, IpVersion, RedirectPolicy, VersionNegotiation};
+
 /// Re-export of HTTP types.
 pub use http;
 
+/// Re-export of the channel types accepted by
+/// [`ResponseExt::stream_to_channel`].
+#[cfg(feature = "bytes")]
+pub use async_channel;
+
 /// A "prelude" for importing commonly used Isahc types and traits.
 ///
 /// The prelude re-exports most commonly used traits and macros from this crate.
@@ -297,6 +411,7 @@ pub mod prelude {
         config::Configurable,
         AsyncReadResponseExt,
         ReadResponseExt,
+        RequestBuilderExt,
         RequestExt,
         ResponseExt,
     };
@@ -326,6 +441,33 @@ where
     HttpClient::shared().get_async(uri)
 }
 
+/// Send a GET request to the given URI with a given request body.
+///
+/// The request is executed using a shared [`HttpClient`] instance. See
+/// [`HttpClient::get_with_body`] for details.
+pub fn get_with_body<U, B>(uri: U, body: B) -> Result<Response<Body>, Error>
+where
+    http::Uri: TryFrom<U>,
+    <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+    B: Into<Body>,
+{
+    HttpClient::shared().get_with_body(uri, body)
+}
+
+/// Send a GET request to the given URI asynchronously with a given request
+/// body.
+///
+/// The request is executed using a shared [`HttpClient`] instance. See
+/// [`HttpClient::get_with_body_async`] for details.
+pub fn get_with_body_async<U, B>(uri: U, body: B) -> ResponseFuture<'static>
+where
+    http::Uri: TryFrom<U>,
+    <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+    B: Into<AsyncBody>,
+{
+    HttpClient::shared().get_with_body_async(uri, body)
+}
+
 /// Send a HEAD request to the given URI.
 ///
 /// The request is executed using a shared [`HttpClient`] instance. See
@@ -447,6 +589,81 @@ where
     HttpClient::shared().delete_async(uri)
 }
 
+/// Send a DELETE request to the given URI with a given request body.
+///
+/// The request is executed using a shared [`HttpClient`] instance. See
+/// [`HttpClient::delete_with_body`] for details.
+pub fn delete_with_body<U, B>(uri: U, body: B) -> Result<Response<Body>, Error>
+where
+    http::Uri: TryFrom<U>,
+    <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+    B: Into<Body>,
+{
+    HttpClient::shared().delete_with_body(uri, body)
+}
+
+/// Send a DELETE request to the given URI asynchronously with a given request
+/// body.
+///
+/// The request is executed using a shared [`HttpClient`] instance. See
+/// [`HttpClient::delete_with_body_async`] for details.
+pub fn delete_with_body_async<U, B>(uri: U, body: B) -> ResponseFuture<'static>
+where
+    http::Uri: TryFrom<U>,
+    <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+    B: Into<AsyncBody>,
+{
+    HttpClient::shared().delete_with_body_async(uri, body)
+}
+
+/// Send an OPTIONS request to the given URI.
+///
+/// The request is executed using a shared [`HttpClient`] instance. See
+/// [`HttpClient::options`] for details.
+pub fn options<U>(uri: U) -> Result<Response<Body>, Error>
+where
+    http::Uri: TryFrom<U>,
+    <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+{
+    HttpClient::shared().options(uri)
+}
+
+/// Send an OPTIONS request to the given URI asynchronously.
+///
+/// The request is executed using a shared [`HttpClient`] instance. See
+/// [`HttpClient::options_async`] for details.
+pub fn options_async<U>(uri: U) -> ResponseFuture<'static>
+where
+    http::Uri: TryFrom<U>,
+    <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+{
+    HttpClient::shared().options_async(uri)
+}
+
+/// Send a TRACE request to the given URI.
+///
+/// The request is executed using a shared [`HttpClient`] instance. See
+/// [`HttpClient::trace`] for details.
+pub fn trace<U>(uri: U) -> Result<Response<Body>, Error>
+where
+    http::Uri: TryFrom<U>,
+    <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+{
+    HttpClient::shared().trace(uri)
+}
+
+/// Send a TRACE request to the given URI asynchronously.
+///
+/// The request is executed using a shared [`HttpClient`] instance. See
+/// [`HttpClient::trace_async`] for details.
+pub fn trace_async<U>(uri: U) -> ResponseFuture<'static>
+where
+    http::Uri: TryFrom<U>,
+    <http::Uri as TryFrom<U>>::Error: Into<http::Error>,
+{
+    HttpClient::shared().trace_async(uri)
+}
+
 /// Send an HTTP request and return the HTTP response.
 ///
 /// The request is executed using a shared [`HttpClient`] instance. See
@@ -463,6 +680,29 @@ pub fn send_async<B: Into<AsyncBody>>(request: Request<B>) -> ResponseFuture<'st
     HttpClient::shared().send_async(request)
 }
 
+/// Perform one-time global initialization of libcurl.
+///
+/// Global initialization of libcurl is not thread-safe, and must happen before
+/// any other thread uses libcurl. This function performs it at most once no
+/// matter how many times or from how many threads it is called, and is called
+/// automatically when building an [`HttpClient`], so most programs never need
+/// to call it themselves.
+///
+/// If your program also uses libcurl in other ways, such as through the `curl`
+/// crate directly or through a C library linked into the same process, call
+/// this early on the main thread before starting any other threads.
+///
+/// # Examples
+///
+/// ```
+/// // Call this first thing in `main`, before spawning any threads.
+/// isahc::init();
+/// ```
+pub fn init() {
+    // The curl crate guards its own initialization with a `Once`.
+    curl::init();
+}
+
 /// Gets a human-readable string with the version number of Isahc and its
 /// dependencies.
 ///
@@ -26,7 +26,7 @@ macro_rules! decl_future {
     (
         $(
             $(#[$meta:meta])*
-            $vis:vis type $ident:ident$(<$($T:ident),*>)? = impl Future<Output = $output:ty> $(+ SendIf<$($S:ident),+>)?;
+            $vis:vis type $ident:ident$(<$($T:ident),*>)? = impl Future<Output = $output:ty> $(+ SendIf<$($S:ident),*>)?;
         )*
     ) => {
         $(
@@ -149,6 +149,29 @@ impl Metrics {
     pub fn redirect_time(&self) -> Duration {
         Duration::from_secs_f64(self.inner.redirect_time.load())
     }
+
+    /// Take a snapshot of the current values of all metrics.
+    ///
+    /// Unlike [`Metrics`] itself, which continues to be updated while the
+    /// request is in progress, the returned snapshot is a plain value that
+    /// will never change. Metrics are most complete after the response body
+    /// has been fully consumed, so that is usually the best time to take a
+    /// snapshot.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            upload_progress: self.upload_progress(),
+            upload_speed: self.upload_speed(),
+            download_progress: self.download_progress(),
+            download_speed: self.download_speed(),
+            name_lookup_time: self.name_lookup_time(),
+            connect_time: self.connect_time(),
+            secure_connect_time: self.secure_connect_time(),
+            transfer_start_time: self.transfer_start_time(),
+            transfer_time: self.transfer_time(),
+            total_time: self.total_time(),
+            redirect_time: self.redirect_time(),
+        }
+    }
 }
 
 impl fmt::Debug for Metrics {
@@ -168,3 +191,129 @@ impl fmt::Debug for Metrics {
             .finish()
     }
 }
+
+/// A point-in-time copy of the values of a [`Metrics`] object.
+///
+/// Snapshots are created using [`Metrics::snapshot`]. See the corresponding
+/// methods on [`Metrics`] for a description of each value.
+///
+/// If the `json` feature is enabled, snapshots can also be serialized using
+/// [serde](https://serde.rs), which is handy for structured logging.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MetricsSnapshot {
+    upload_progress: (u64, u64),
+    upload_speed: f64,
+    download_progress: (u64, u64),
+    download_speed: f64,
+    name_lookup_time: Duration,
+    connect_time: Duration,
+    secure_connect_time: Duration,
+    transfer_start_time: Duration,
+    transfer_time: Duration,
+    total_time: Duration,
+    redirect_time: Duration,
+}
+
+impl MetricsSnapshot {
+    /// Number of bytes uploaded / estimated total.
+    pub fn upload_progress(&self) -> (u64, u64) {
+        self.upload_progress
+    }
+
+    /// Average upload speed in bytes/second.
+    pub fn upload_speed(&self) -> f64 {
+        self.upload_speed
+    }
+
+    /// Number of bytes downloaded / estimated total.
+    pub fn download_progress(&self) -> (u64, u64) {
+        self.download_progress
+    }
+
+    /// Average download speed in bytes/second.
+    pub fn download_speed(&self) -> f64 {
+        self.download_speed
+    }
+
+    /// Get the total time from the start of the request until DNS name
+    /// resolving was completed.
+    pub fn name_lookup_time(&self) -> Duration {
+        self.name_lookup_time
+    }
+
+    /// Get the amount of time taken to establish a connection to the server
+    /// (not including TLS connection time).
+    pub fn connect_time(&self) -> Duration {
+        self.connect_time
+    }
+
+    /// Get the amount of time spent on TLS handshakes.
+    pub fn secure_connect_time(&self) -> Duration {
+        self.secure_connect_time
+    }
+
+    /// Get the time it took from the start of the request until the first
+    /// byte is either sent or received.
+    pub fn transfer_start_time(&self) -> Duration {
+        self.transfer_start_time
+    }
+
+    /// Get the amount of time spent performing the actual request transfer.
+    pub fn transfer_time(&self) -> Duration {
+        self.transfer_time
+    }
+
+    /// Get the total time for the entire request.
+    pub fn total_time(&self) -> Duration {
+        self.total_time
+    }
+
+    /// Get the total time taken for all redirection steps.
+    pub fn redirect_time(&self) -> Duration {
+        self.redirect_time
+    }
+}
+
+/// Serializes the snapshot as a flat structure with the following fields,
+/// which are intended to be stable for use in structured logs:
+///
+/// - `upload_bytes`, `upload_total_bytes`: bytes uploaded and the estimated
+///   total.
+/// - `upload_speed`: average upload speed in bytes/second.
+/// - `download_bytes`, `download_total_bytes`: bytes downloaded and the
+///   estimated total.
+/// - `download_speed`: average download speed in bytes/second.
+/// - `name_lookup_time`, `connect_time`, `secure_connect_time`,
+///   `transfer_start_time`, `transfer_time`, `total_time`, `redirect_time`:
+///   the corresponding durations in seconds, as floating-point numbers.
+#[cfg(feature = "json")]
+impl serde::Serialize for MetricsSnapshot {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("MetricsSnapshot", 13)?;
+        s.serialize_field("upload_bytes", &self.upload_progress.0)?;
+        s.serialize_field("upload_total_bytes", &self.upload_progress.1)?;
+        s.serialize_field("upload_speed", &self.upload_speed)?;
+        s.serialize_field("download_bytes", &self.download_progress.0)?;
+        s.serialize_field("download_total_bytes", &self.download_progress.1)?;
+        s.serialize_field("download_speed", &self.download_speed)?;
+        s.serialize_field("name_lookup_time", &self.name_lookup_time.as_secs_f64())?;
+        s.serialize_field("connect_time", &self.connect_time.as_secs_f64())?;
+        s.serialize_field(
+            "secure_connect_time",
+            &self.secure_connect_time.as_secs_f64(),
+        )?;
+        s.serialize_field(
+            "transfer_start_time",
+            &self.transfer_start_time.as_secs_f64(),
+        )?;
+        s.serialize_field("transfer_time", &self.transfer_time.as_secs_f64())?;
+        s.serialize_field("total_time", &self.total_time.as_secs_f64())?;
+        s.serialize_field("redirect_time", &self.redirect_time.as_secs_f64())?;
+        s.end()
+    }
+}
@@ -0,0 +1,150 @@
+//! Mock transport for testing code that uses an [`HttpClient`] without making
+//! any real network requests.
+//!
+//! A [`MockTransport`] holds a set of canned responses, each registered for a
+//! request method and URI. When a client built with a mock transport sends a
+//! request, the matching response is returned instead of contacting a server.
+//! Requests still pass through the client's interceptors, so things like
+//! default headers and redirect following behave as usual.
+//!
+//! # Examples
+//!
+//! ```
+//! use isahc::{mock::MockTransport, prelude::*, HttpClient, Response};
+//!
+//! let mock = MockTransport::new().respond(
+//!     "GET",
+//!     "http://example.org/hello",
+//!     Response::builder().status(200).body("hello world")?,
+//! );
+//!
+//! let client = HttpClient::builder().transport(mock).build()?;
+//! let mut response = client.get("http://example.org/hello")?;
+//!
+//! assert_eq!(response.text()?, "hello world");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! # Availability
+//!
+//! This module is only available when the [`mock`](crate#mock)
+//! feature is enabled.
+//!
+//! [`HttpClient`]: crate::HttpClient
+
+use crate::{
+    body::AsyncBody,
+    error::{Error, ErrorKind},
+    interceptor::InterceptorFuture,
+    transport::Transport,
+};
+use http::{HeaderMap, Method, Request, Response, StatusCode, Uri, Version};
+use std::{convert::TryFrom, fmt, io};
+
+/// A transport that returns preconfigured responses instead of sending
+/// requests over the network.
+///
+/// Set on a client using
+/// [`HttpClientBuilder::transport`](crate::HttpClientBuilder::transport).
+/// Requests are matched against registered responses by method and URI. If no
+/// response matches a request, the request fails with a
+/// [`ConnectionFailed`](ErrorKind::ConnectionFailed) error, as if no server
+/// were listening.
+#[derive(Default)]
+pub struct MockTransport {
+    routes: Vec<Route>,
+}
+
+struct Route {
+    method: Method,
+    uri: Uri,
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl MockTransport {
+    /// Create a new mock transport with no registered responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a response to return for requests with the given method and
+    /// URI.
+    ///
+    /// The response is returned for every matching request, not just the
+    /// first. If multiple responses are registered for the same method and
+    /// URI, the one registered first wins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method or URI are invalid.
+    pub fn respond<M, U, B>(mut self, method: M, uri: U, response: Response<B>) -> Self
+    where
+        Method: TryFrom<M>,
+        <Method as TryFrom<M>>::Error: fmt::Debug,
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: fmt::Debug,
+        B: Into<Vec<u8>>,
+    {
+        let (parts, body) = response.into_parts();
+
+        self.routes.push(Route {
+            method: Method::try_from(method).expect("invalid method"),
+            uri: Uri::try_from(uri).expect("invalid URI"),
+            status: parts.status,
+            version: parts.version,
+            headers: parts.headers,
+            body: body.into(),
+        });
+
+        self
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(&self, request: Request<AsyncBody>) -> InterceptorFuture<'_, Error> {
+        let route = self
+            .routes
+            .iter()
+            .find(|route| route.method == request.method() && route.uri == *request.uri());
+
+        let result = match route {
+            Some(route) => {
+                let mut builder = Response::builder()
+                    .status(route.status)
+                    .version(route.version);
+
+                if let Some(headers) = builder.headers_mut() {
+                    headers.extend(route.headers.clone());
+                }
+
+                builder
+                    .body(AsyncBody::from(route.body.clone()))
+                    .map_err(|e| Error::new(ErrorKind::ProtocolViolation, e))
+            }
+            None => Err(Error::new(
+                ErrorKind::ConnectionFailed,
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "no mock response registered for {} {}",
+                        request.method(),
+                        request.uri()
+                    ),
+                ),
+            )),
+        };
+
+        Box::pin(async move { result })
+    }
+}
+
+impl fmt::Debug for MockTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockTransport")
+            .field("routes", &self.routes.len())
+            .finish()
+    }
+}
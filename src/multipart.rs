@@ -0,0 +1,414 @@
+//! Parsing of [multipart](https://tools.ietf.org/html/rfc2046#section-5.1)
+//! response bodies.
+//!
+//! The most common multipart response is `multipart/byteranges`, which servers
+//! send in reply to a request for more than one byte range of a resource, as
+//! described in [RFC 7233, Section
+//! 4.1](https://tools.ietf.org/html/rfc7233#section-4.1). Such responses can be
+//! turned into a stream of [`Part`]s using
+//! [`ResponseExt::multipart`](crate::ResponseExt::multipart).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use futures_lite::StreamExt;
+//! use isahc::{prelude::*, Request};
+//!
+//! # async fn run() -> Result<(), isahc::Error> {
+//! let mut parts = Request::get("https://example.org/video.mp4")
+//!     .header("Range", "bytes=0-99,1000-1099")
+//!     .body(())?
+//!     .send_async()
+//!     .await?
+//!     .multipart();
+//!
+//! while let Some(part) = parts.next().await {
+//!     let part = part?;
+//!     println!("{:?}: {} bytes", part.headers().get("content-range"), part.body().len());
+//! }
+//! # Ok(()) }
+//! ```
+
+use crate::{
+    error::{Error, ErrorKind},
+    parsing::parse_header,
+};
+use futures_lite::{io::AsyncBufRead, Stream};
+use http::HeaderMap;
+use std::{
+    fmt,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A single part of a multipart body.
+#[derive(Clone, Debug)]
+pub struct Part {
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl Part {
+    /// Get the headers of this part, such as `Content-Type` and
+    /// `Content-Range`.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Get the body of this part.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Take the body of this part.
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+}
+
+/// A stream of [`Part`]s parsed from a multipart response body.
+///
+/// Each part is buffered in memory in full before it is produced. Any preamble
+/// before the first part and any epilogue after the last part are discarded.
+///
+/// Created by [`ResponseExt::multipart`](crate::ResponseExt::multipart).
+pub struct Parts<R> {
+    reader: R,
+
+    /// The delimiter that separates parts, consisting of a line ending, two
+    /// dashes and the boundary.
+    delimiter: Vec<u8>,
+
+    /// Data read but not yet parsed.
+    buffer: Vec<u8>,
+
+    /// Number of bytes at the start of the buffer already searched for the
+    /// delimiter of the current part, so that they are not searched again.
+    scanned: usize,
+
+    state: State,
+
+    /// Error to produce before anything else, if the response was not valid.
+    error: Option<Error>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Looking for the first delimiter.
+    Preamble,
+
+    /// Looking for the end of the line containing a delimiter.
+    Delimiter,
+
+    /// Looking for the delimiter that ends the current part.
+    Part,
+
+    /// The closing delimiter has been found, or an error occurred.
+    Finished,
+}
+
+impl<R: AsyncBufRead + Unpin> Parts<R> {
+    pub(crate) fn new(reader: R, headers: &HeaderMap) -> Self {
+        let boundary = headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_boundary);
+
+        let (delimiter, error) = match boundary {
+            Some(boundary) => (format!("\r\n--{}", boundary).into_bytes(), None),
+            None => (
+                Vec::new(),
+                Some(malformed("response is not multipart or has no boundary")),
+            ),
+        };
+
+        Self {
+            reader,
+            delimiter,
+            // The first delimiter may appear at the very beginning of the body
+            // without a preceding line ending, so pretend that there is one.
+            buffer: b"\r\n".to_vec(),
+            scanned: 0,
+            state: State::Preamble,
+            error,
+        }
+    }
+}
+
+impl<R> Parts<R> {
+    /// Parse as much of the buffered data as possible, returning a part if a
+    /// complete one was found.
+    fn parse(&mut self) -> Result<Option<Part>, Error> {
+        loop {
+            match self.state {
+                State::Preamble => match find(&self.buffer, &self.delimiter) {
+                    Some(i) => {
+                        self.buffer.drain(..i + self.delimiter.len());
+                        self.state = State::Delimiter;
+                    }
+                    None => {
+                        // Keep just enough to match a delimiter split across
+                        // reads.
+                        let keep = self.delimiter.len() - 1;
+                        if self.buffer.len() > keep {
+                            self.buffer.drain(..self.buffer.len() - keep);
+                        }
+                        return Ok(None);
+                    }
+                },
+                State::Delimiter => {
+                    if self.buffer.starts_with(b"--") {
+                        // The closing delimiter; the rest is the epilogue.
+                        self.buffer.clear();
+                        self.state = State::Finished;
+                        return Ok(None);
+                    }
+
+                    if self.buffer.len() < 2 {
+                        return Ok(None);
+                    }
+
+                    // Skip any transport padding up to the end of the line.
+                    match self.buffer.iter().position(|&b| b == b'\n') {
+                        Some(i) => {
+                            self.buffer.drain(..=i);
+                            self.state = State::Part;
+                        }
+                        None => return Ok(None),
+                    }
+                }
+                State::Part => match find(&self.buffer[self.scanned..], &self.delimiter) {
+                    Some(i) => {
+                        let i = self.scanned + i;
+                        let part = parse_part(&self.buffer[..i])?;
+                        self.buffer.drain(..i + self.delimiter.len());
+                        self.scanned = 0;
+                        self.state = State::Delimiter;
+                        return Ok(Some(part));
+                    }
+                    None => {
+                        // A delimiter split across reads can only begin within
+                        // the last few bytes.
+                        self.scanned = self.buffer.len().saturating_sub(self.delimiter.len() - 1);
+                        return Ok(None);
+                    }
+                },
+                State::Finished => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for Parts<R> {
+    type Item = Result<Part, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some(e) = this.error.take() {
+            this.state = State::Finished;
+            return Poll::Ready(Some(Err(e)));
+        }
+
+        loop {
+            match this.parse() {
+                Ok(Some(part)) => return Poll::Ready(Some(Ok(part))),
+                Ok(None) => {}
+                Err(e) => {
+                    this.state = State::Finished;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+
+            if this.state == State::Finished {
+                return Poll::Ready(None);
+            }
+
+            let len = {
+                let buf = match Pin::new(&mut this.reader).poll_fill_buf(cx) {
+                    Poll::Ready(Ok(buf)) => buf,
+                    Poll::Ready(Err(e)) => {
+                        this.state = State::Finished;
+                        return Poll::Ready(Some(Err(e.into())));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                };
+
+                this.buffer.extend_from_slice(buf);
+                buf.len()
+            };
+
+            if len == 0 {
+                this.state = State::Finished;
+                return Poll::Ready(Some(Err(malformed(
+                    "multipart body ended before the closing delimiter",
+                ))));
+            }
+
+            Pin::new(&mut this.reader).consume(len);
+        }
+    }
+}
+
+impl<R> fmt::Debug for Parts<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parts").field("state", &self.state).finish()
+    }
+}
+
+/// Get the boundary parameter of a multipart content type.
+fn parse_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+
+    if !params
+        .next()?
+        .trim()
+        .to_ascii_lowercase()
+        .starts_with("multipart/")
+    {
+        return None;
+    }
+
+    params.find_map(|param| {
+        let mut split = param.splitn(2, '=');
+        let name = split.next()?.trim();
+        let value = split.next()?.trim();
+
+        if !name.eq_ignore_ascii_case("boundary") {
+            return None;
+        }
+
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+
+        Some(value.to_owned()).filter(|value| !value.is_empty())
+    })
+}
+
+/// Parse the headers and body of a single part.
+fn parse_part(data: &[u8]) -> Result<Part, Error> {
+    // A part without any headers begins with a blank line.
+    let (head, body) = if data.starts_with(b"\r\n") {
+        (&[][..], &data[2..])
+    } else {
+        match find(data, b"\r\n\r\n") {
+            Some(i) => (&data[..i], &data[i + 4..]),
+            None => return Err(malformed("multipart part headers are incomplete")),
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+
+    for line in head.split(|&b| b == b'\n').filter(|line| !line.is_empty()) {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        match parse_header(line) {
+            Some((name, value)) => {
+                headers.append(name, value);
+            }
+            None => return Err(malformed("multipart part has an invalid header")),
+        }
+    }
+
+    Ok(Part {
+        headers,
+        body: body.to_vec(),
+    })
+}
+
+/// Find the first occurrence of a byte sequence.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn malformed(message: &'static str) -> Error {
+    Error::with_context(
+        ErrorKind::ProtocolViolation,
+        Some(String::from(message)),
+        io::Error::from(io::ErrorKind::InvalidData),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::{future::block_on, io::BufReader, StreamExt};
+
+    fn parts(content_type: &str, input: &'static [u8]) -> Vec<Result<Part, Error>> {
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::CONTENT_TYPE, content_type.parse().unwrap());
+
+        block_on(Parts::new(BufReader::with_capacity(3, input), &headers).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parse_boundary_parameter() {
+        assert_eq!(
+            parse_boundary("multipart/byteranges; boundary=abc").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            parse_boundary("Multipart/Mixed;charset=x; BOUNDARY=\"a b\"").as_deref(),
+            Some("a b")
+        );
+        assert_eq!(parse_boundary("text/plain; boundary=abc"), None);
+        assert_eq!(parse_boundary("multipart/mixed"), None);
+    }
+
+    #[test]
+    fn preamble_and_epilogue_are_ignored() {
+        let parts = parts(
+            "multipart/mixed; boundary=xyz",
+            b"preamble\r\n--xyz\r\nX-A: 1\r\n\r\nfirst\r\n--xyz  \r\n\r\nsecond\r\n\r\n--xyz--\r\nepilogue",
+        );
+
+        assert_eq!(parts.len(), 2);
+
+        let first = parts[0].as_ref().unwrap();
+        assert_eq!(first.headers()["x-a"], "1");
+        assert_eq!(first.body(), b"first");
+
+        let second = parts[1].as_ref().unwrap();
+        assert!(second.headers().is_empty());
+        assert_eq!(second.body(), b"second\r\n");
+    }
+
+    #[test]
+    fn partial_delimiters_in_part_body_are_kept() {
+        let parts = parts(
+            "multipart/mixed; boundary=xyz",
+            b"--xyz\r\n\r\na\r\n--xy\r\n--x\r\n-\r\n--xyz--",
+        );
+
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].as_ref().unwrap().body(), b"a\r\n--xy\r\n--x\r\n-");
+    }
+
+    #[test]
+    fn missing_boundary_is_an_error() {
+        let parts = parts("text/plain", b"--xyz--");
+
+        assert_eq!(parts.len(), 1);
+        assert_eq!(
+            parts[0].as_ref().unwrap_err().kind(),
+            &ErrorKind::ProtocolViolation
+        );
+    }
+
+    #[test]
+    fn truncated_body_is_an_error() {
+        let parts = parts(
+            "multipart/mixed; boundary=xyz",
+            b"--xyz\r\n\r\nfirst\r\n--xyz\r\n\r\nsec",
+        );
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].as_ref().unwrap().body(), b"first");
+        assert!(parts[1].is_err());
+    }
+}
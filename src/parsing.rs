@@ -1,8 +1,10 @@
+use crate::response::RetryAfter;
 use http::{
     header::{HeaderName, HeaderValue},
     StatusCode,
     Version,
 };
+use std::time::Duration;
 
 pub(crate) fn parse_status_line(line: &[u8]) -> Option<(Version, StatusCode)> {
     let mut parts = line.split(u8::is_ascii_whitespace);
@@ -24,6 +26,22 @@ pub(crate) fn parse_status_line(line: &[u8]) -> Option<(Version, StatusCode)> {
     Some((version, status_code))
 }
 
+/// Parse the status code out of a status line with a non-standard protocol
+/// token, such as `ICY 200 OK`.
+pub(crate) fn parse_nonstandard_status_line(line: &[u8]) -> Option<StatusCode> {
+    let mut parts = line
+        .split(u8::is_ascii_whitespace)
+        .filter(|s| !s.is_empty());
+
+    let protocol = parts.next()?;
+
+    if protocol.contains(&b':') {
+        return None;
+    }
+
+    StatusCode::from_bytes(parts.next()?).ok()
+}
+
 pub(crate) fn parse_header(line: &[u8]) -> Option<(HeaderName, HeaderValue)> {
     let split_index = line.iter().position(|&f| f == b':')?;
 
@@ -92,6 +110,130 @@ pub(crate) fn header_to_curl_string(
     string
 }
 
+/// Parse the value of a `Retry-After` header, in either its delay-seconds or
+/// HTTP-date form.
+pub(crate) fn parse_retry_after(value: &[u8]) -> Option<RetryAfter> {
+    let value = std::str::from_utf8(value).ok()?.trim();
+
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        value
+            .parse()
+            .ok()
+            .map(Duration::from_secs)
+            .map(RetryAfter::Delay)
+    } else {
+        httpdate::parse_http_date(value)
+            .ok()
+            .map(RetryAfter::DateTime)
+    }
+}
+
+/// Decode a PEM-encoded certificate into its DER-encoded bytes.
+pub(crate) fn parse_pem_certificate(pem: &str) -> Option<Vec<u8>> {
+    let begin = pem.find("-----BEGIN CERTIFICATE-----")? + "-----BEGIN CERTIFICATE-----".len();
+    let end = begin + pem[begin..].find("-----END CERTIFICATE-----")?;
+
+    decode_base64(&pem[begin..end])
+}
+
+/// Encode bytes as standard base64 with padding.
+pub(crate) fn encode_base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut output = String::with_capacity(input.len() * 4 / 3 + 4);
+
+    for chunk in input.chunks(3) {
+        let buffer = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(buffer >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
+
+/// Decode percent-encoded octets in a URI component. Malformed escape
+/// sequences are kept as-is, and any invalid UTF-8 produced by decoding is
+/// replaced.
+pub(crate) fn percent_decode(input: &str) -> String {
+    fn hex(byte: u8) -> Option<u8> {
+        match byte {
+            b'0'..=b'9' => Some(byte - b'0'),
+            b'a'..=b'f' => Some(byte - b'a' + 10),
+            b'A'..=b'F' => Some(byte - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let (Some(high), Some(low)) = (
+                bytes.get(i + 1).copied().and_then(hex),
+                bytes.get(i + 2).copied().and_then(hex),
+            ) {
+                output.push(high << 4 | low);
+                i += 3;
+                continue;
+            }
+        }
+
+        output.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// Decode standard base64, ignoring any whitespace.
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    fn value(byte: u8) -> Option<u32> {
+        Some(match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as u32)
+    }
+
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for byte in input.bytes() {
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+
+        if byte == b'=' {
+            break;
+        }
+
+        buffer = (buffer << 6) | value(byte)?;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(output)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,4 +341,81 @@ mod tests {
             "User-Agent: foo"
         );
     }
+
+    #[test]
+    fn parse_pem_certificate_as_der() {
+        let pem = "-----BEGIN CERTIFICATE-----\naGVsbG8g\nd29ybGQ=\n-----END CERTIFICATE-----\n";
+
+        assert_eq!(parse_pem_certificate(pem).unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn parse_invalid_pem_certificate() {
+        assert_eq!(parse_pem_certificate(""), None);
+        assert_eq!(parse_pem_certificate("-----BEGIN CERTIFICATE-----\naGVsbG8"), None);
+        assert_eq!(
+            parse_pem_certificate("-----BEGIN CERTIFICATE-----\n!!\n-----END CERTIFICATE-----"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_retry_after_delay() {
+        assert_eq!(
+            parse_retry_after(b"120"),
+            Some(RetryAfter::Delay(Duration::from_secs(120)))
+        );
+    }
+
+    #[test]
+    fn parse_retry_after_date() {
+        assert_eq!(
+            parse_retry_after(b"Fri, 31 Dec 1999 23:59:59 GMT"),
+            Some(RetryAfter::DateTime(
+                std::time::UNIX_EPOCH + Duration::from_secs(946_684_799)
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_retry_after() {
+        assert_eq!(parse_retry_after(b""), None);
+        assert_eq!(parse_retry_after(b"-1"), None);
+        assert_eq!(parse_retry_after(b"1.5"), None);
+        assert_eq!(parse_retry_after(b"tomorrow"), None);
+        assert_eq!(parse_retry_after(b"99999999999999999999999"), None);
+    }
+
+    #[test]
+    fn parse_nonstandard_status_lines() {
+        assert_eq!(
+            parse_nonstandard_status_line(b"ICY 200 OK\r\n"),
+            Some(StatusCode::OK)
+        );
+        assert_eq!(parse_nonstandard_status_line(b"ICY\r\n"), None);
+        assert_eq!(parse_nonstandard_status_line(b"icy-name: 200\r\n"), None);
+        assert_eq!(parse_nonstandard_status_line(b"\r\n"), None);
+    }
+
+    #[test]
+    fn percent_decode_escapes() {
+        assert_eq!(percent_decode("p%40ss%3Aword"), "p@ss:word");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("a+b"), "a+b");
+    }
+
+    #[test]
+    fn encode_base64_with_padding() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"user:pass"), "dXNlcjpwYXNz");
+        assert_eq!(
+            decode_base64(&encode_base64(&[0xff, 0x00, 0xfe, 0x7f])),
+            Some(vec![0xff, 0x00, 0xfe, 0x7f])
+        );
+    }
 }
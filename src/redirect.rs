@@ -1,8 +1,8 @@
 use crate::{
     body::AsyncBody,
-    config::{request::RequestConfig, RedirectPolicy},
+    config::{request::RequestConfig, PostRedirectPolicy, RedirectPolicy},
     error::{Error, ErrorKind},
-    handler::RequestBody,
+    handler::{FinalTransfer, RequestBody},
     interceptor::{Context, Interceptor, InterceptorFuture},
     request::RequestExt,
 };
@@ -17,8 +17,25 @@ const DEFAULT_REDIRECT_LIMIT: u32 = 1024;
 
 /// Extension containing the final "effective" URI that was visited, after
 /// following any redirects.
+///
+/// This is initially populated by the handler using the URI reported by curl,
+/// and is only filled in here if the response does not already have one.
 pub(crate) struct EffectiveUri(pub(crate) Uri);
 
+/// Extension marking that the request credentials were taken from userinfo in
+/// the request URI.
+///
+/// Such credentials are only meant for the origin named in the URI, so they
+/// are dropped if a redirect leads somewhere else.
+pub(crate) struct UserinfoCredentials {
+    /// The URI the userinfo was found in.
+    pub(crate) origin: Uri,
+
+    /// Whether basic authentication was enabled only so that the userinfo
+    /// would be used.
+    pub(crate) forced_authentication: bool,
+}
+
 /// Interceptor that implements automatic following of HTTP redirects.
 pub(crate) struct RedirectInterceptor;
 
@@ -45,9 +62,7 @@ impl Interceptor for RedirectInterceptor {
             // No redirect handling, just proceed normally.
             if policy == RedirectPolicy::None {
                 let mut response = ctx.send(request).await?;
-                response
-                    .extensions_mut()
-                    .insert(EffectiveUri(effective_uri));
+                set_effective_uri(&mut response, effective_uri);
 
                 return Ok(response);
             }
@@ -58,6 +73,19 @@ impl Interceptor for RedirectInterceptor {
                 .and_then(|config| config.auto_referer)
                 .unwrap_or(false);
 
+            let on_redirect = request
+                .extensions()
+                .get::<RequestConfig>()
+                .and_then(|config| config.on_redirect.clone());
+
+            let userinfo_credentials = request.extensions_mut().remove::<UserinfoCredentials>();
+
+            let post_redirect_policy = request
+                .extensions()
+                .get::<RequestConfig>()
+                .and_then(|config| config.post_redirect_policy)
+                .unwrap_or_default();
+
             let limit = match policy {
                 RedirectPolicy::Limit(limit) => limit,
                 _ => DEFAULT_REDIRECT_LIMIT,
@@ -74,12 +102,25 @@ impl Interceptor for RedirectInterceptor {
                 let mut response = ctx.send(request).await?;
 
                 // Check for a redirect.
-                if let Some(location) = get_redirect_location(&effective_uri, &response) {
+                if let Some(location) = get_redirect_location(&effective_uri, &response)? {
                     // If we've reached the limit, return an error as requested.
                     if redirect_count >= limit {
                         return Err(ErrorKind::TooManyRedirects.into());
                     }
 
+                    // Give the user a chance to veto the redirect.
+                    if let Some(on_redirect) = on_redirect.as_ref() {
+                        if !(on_redirect.0)(&effective_uri, &location) {
+                            tracing::debug!("redirect to {} was rejected", location);
+                            return Err(ErrorKind::RedirectRejected.into());
+                        }
+                    }
+
+                    // The response to this transfer is not the final one.
+                    if let Some(transfer) = response.extensions_mut().remove::<FinalTransfer>() {
+                        transfer.supersede();
+                    }
+
                     // Set referer header.
                     if auto_referer {
                         let referer = request_builder.uri_ref().unwrap().to_string();
@@ -89,10 +130,13 @@ impl Interceptor for RedirectInterceptor {
                     // Check if we should change the request method into a GET. HTTP
                     // specs don't really say one way or another when this should
                     // happen for most status codes, so we just mimic curl's
-                    // behavior here since it is so common.
-                    if response.status() == 301
-                        || response.status() == 302
-                        || response.status() == 303
+                    // behavior here since it is so common, unless asked to keep
+                    // POST requests as they are.
+                    let preserve_post = post_redirect_policy == PostRedirectPolicy::PreservePost
+                        && request_builder.method_ref() == Some(&http::Method::POST);
+
+                    if response.status() == 303
+                        || (response.status() == 301 || response.status() == 302) && !preserve_post
                     {
                         request_builder = request_builder.method(http::Method::GET);
                     }
@@ -123,12 +167,31 @@ impl Interceptor for RedirectInterceptor {
                         .body(request_body)
                         .map_err(|e| Error::new(ErrorKind::InvalidRequest, e))?;
                     redirect_count += 1;
+
+                    // Never send credentials taken from the original URI to
+                    // a different origin.
+                    if let Some(userinfo) = userinfo_credentials.as_ref() {
+                        if !is_same_origin(&userinfo.origin, &effective_uri) {
+                            if let Some(config) =
+                                request.extensions_mut().get_mut::<RequestConfig>()
+                            {
+                                if config.credentials.take().is_some() {
+                                    tracing::debug!(
+                                        "not sending URI credentials to {}",
+                                        effective_uri
+                                    );
+                                }
+
+                                if userinfo.forced_authentication {
+                                    config.authentication = None;
+                                }
+                            }
+                        }
+                    }
                 }
                 // No more redirects; set the effective URI we finally settled on and return.
                 else {
-                    response
-                        .extensions_mut()
-                        .insert(EffectiveUri(effective_uri));
+                    set_effective_uri(&mut response, effective_uri);
 
                     return Ok(response);
                 }
@@ -137,13 +200,40 @@ impl Interceptor for RedirectInterceptor {
     }
 }
 
-fn get_redirect_location<T>(request_uri: &Uri, response: &Response<T>) -> Option<Uri> {
+/// Set the effective URI of a response, unless the handler has already
+/// populated it with the URI reported by curl.
+fn set_effective_uri<T>(response: &mut Response<T>, uri: Uri) {
+    if response.extensions().get::<EffectiveUri>().is_none() {
+        response.extensions_mut().insert(EffectiveUri(uri));
+    }
+}
+
+fn get_redirect_location<T>(
+    request_uri: &Uri,
+    response: &Response<T>,
+) -> Result<Option<Uri>, Error> {
     if response.status().is_redirection() {
-        let location = response.headers().get(http::header::LOCATION)?;
+        let location = match response.headers().get(http::header::LOCATION) {
+            Some(location) => location,
+            None => return Ok(None),
+        };
 
         match parse_location(location) {
             Ok(location) => match resolve(request_uri, location.as_ref()) {
-                Ok(uri) => return Some(uri),
+                // Only ever follow redirects to other HTTP URIs, so that a
+                // server cannot point us at local files or other protocols.
+                Ok(url) if url.scheme() != "http" && url.scheme() != "https" => {
+                    return Err(Error::from(ErrorKind::RedirectRejected).with_message(format!(
+                        "refusing to follow redirect to unsupported scheme `{}`",
+                        url.scheme()
+                    )));
+                }
+                Ok(url) => match Uri::try_from(url.as_str()) {
+                    Ok(uri) => return Ok(Some(uri)),
+                    Err(e) => {
+                        tracing::debug!("invalid redirect location: {}", e);
+                    }
+                },
                 Err(e) => {
                     tracing::debug!("invalid redirect location: {}", e);
                 }
@@ -154,7 +244,7 @@ fn get_redirect_location<T>(request_uri: &Uri, response: &Response<T>) -> Option
         }
     }
 
-    None
+    Ok(None)
 }
 
 /// Parse the given `Location` header value into a string.
@@ -204,19 +294,32 @@ fn parse_location(location: &HeaderValue) -> Result<Cow<'_, str>, ToStrError> {
     }
 }
 
+/// Check whether two URIs share the same scheme, host, and port.
+fn is_same_origin(a: &Uri, b: &Uri) -> bool {
+    fn port(uri: &Uri) -> Option<u16> {
+        uri.port_u16().or_else(|| match uri.scheme_str() {
+            Some("http") => Some(80),
+            Some("https") => Some(443),
+            _ => None,
+        })
+    }
+
+    a.scheme_str().map(str::to_ascii_lowercase) == b.scheme_str().map(str::to_ascii_lowercase)
+        && a.host().map(str::to_ascii_lowercase) == b.host().map(str::to_ascii_lowercase)
+        && port(a) == port(b)
+}
+
 /// Resolve one URI in terms of another.
-fn resolve(base: &Uri, target: &str) -> Result<Uri, Box<dyn std::error::Error>> {
+fn resolve(base: &Uri, target: &str) -> Result<Url, url::ParseError> {
     // Optimistically check if this is an absolute URI.
     match Url::parse(target) {
-        Ok(url) => Ok(Uri::try_from(url.as_str())?),
+        Ok(url) => Ok(url),
 
         // Relative URI, resolve against the base.
         Err(url::ParseError::RelativeUrlWithoutBase) => {
-            let base = Url::parse(base.to_string().as_str())?;
-
-            Ok(Uri::try_from(base.join(target)?.as_str())?)
+            Url::parse(base.to_string().as_str())?.join(target)
         }
 
-        Err(e) => Err(Box::new(e)),
+        Err(e) => Err(e),
     }
 }
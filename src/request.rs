@@ -4,10 +4,16 @@ use crate::{
     config::{
         request::{RequestConfig, WithRequestConfig},
         Configurable,
+        VersionNegotiation,
     },
     error::Error,
+    parsing::encode_base64,
+};
+use http::{HeaderValue, Request, Response, Uri};
+use std::{
+    borrow::Borrow,
+    time::{Duration, SystemTime},
 };
-use http::{Request, Response};
 
 /// Extension methods on an HTTP request.
 pub trait RequestExt<T> {
@@ -87,6 +93,291 @@ impl<T> RequestExt<T> for Request<T> {
     }
 }
 
+/// Extension methods on an HTTP request builder.
+pub trait RequestBuilderExt {
+    /// Make the request conditional on the resource not matching the given
+    /// entity tag, by setting the `If-None-Match` header.
+    ///
+    /// The entity tag is usually taken from the `ETag` header of a previous
+    /// response for the same resource. If the given tag is not already quoted
+    /// then quotes are added, as required by [RFC 7232, Section
+    /// 2.3](https://tools.ietf.org/html/rfc7232#section-2.3). Weak tags
+    /// (prefixed with `W/`) and the wildcard `*` are used as-is.
+    ///
+    /// If the resource has not changed, the server will respond with `304 Not
+    /// Modified`, which can be checked using
+    /// [`ResponseExt::is_not_modified`](crate::ResponseExt::is_not_modified).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let response = Request::get("https://httpbin.org/etag/abc")
+    ///     .if_none_match("abc")
+    ///     .body(())?
+    ///     .send()?;
+    ///
+    /// assert!(response.is_not_modified());
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn if_none_match(self, etag: &str) -> Self;
+
+    /// Make the request conditional on the resource having been modified
+    /// after the given time, by setting the `If-Modified-Since` header.
+    ///
+    /// The time is formatted as an HTTP-date as defined in [RFC 7231, Section
+    /// 7.1.1.1](https://tools.ietf.org/html/rfc7231#section-7.1.1.1), which
+    /// has a resolution of one second.
+    fn if_modified_since(self, time: SystemTime) -> Self;
+
+    /// Authorize the request using HTTP Basic authentication, by setting the
+    /// `Authorization` header.
+    ///
+    /// The username and password are encoded as described in [RFC
+    /// 7617](https://tools.ietf.org/html/rfc7617). This replaces any
+    /// `Authorization` header already set on the request, and takes
+    /// precedence over credentials configured on the client.
+    ///
+    /// To let curl handle authentication instead, including other schemes
+    /// and challenge-response negotiation, use
+    /// [`Configurable::credentials`](crate::config::Configurable::credentials).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let request = Request::get("https://example.org")
+    ///     .basic_auth("user", "pass")
+    ///     .body(())?;
+    ///
+    /// assert_eq!(request.headers()["authorization"], "Basic dXNlcjpwYXNz");
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn basic_auth(self, username: &str, password: &str) -> Self;
+
+    /// Authorize the request using a bearer token, by setting the
+    /// `Authorization` header.
+    ///
+    /// This replaces any `Authorization` header already set on the request,
+    /// and takes precedence over a token provider configured on the client
+    /// with
+    /// [`HttpClientBuilder::bearer_token_provider`](crate::HttpClientBuilder::bearer_token_provider).
+    fn bearer_auth(self, token: &str) -> Self;
+
+    /// Disable the timeout for this request, overriding any timeout set on the
+    /// client using [`Configurable::timeout`].
+    ///
+    /// This is useful for requests that are expected to take a long time, such
+    /// as long polling, when sent using a client that otherwise has a short
+    /// timeout. Other limits, such as a connect timeout, still apply.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, HttpClient, Request};
+    /// use std::time::Duration;
+    ///
+    /// let client = HttpClient::builder()
+    ///     .timeout(Duration::from_secs(5))
+    ///     .build()?;
+    ///
+    /// let response = client.send(
+    ///     Request::get("https://example.org/events/poll")
+    ///         .no_timeout()
+    ///         .body(())?,
+    /// )?;
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn no_timeout(self) -> Self;
+
+    /// Remove the connect timeout for this request, overriding any connect
+    /// timeout set on the client using [`Configurable::connect_timeout`].
+    ///
+    /// Curl's built-in connect timeout of 300 seconds still applies.
+    fn no_connect_timeout(self) -> Self;
+
+    /// Use only the given HTTP version for this request.
+    ///
+    /// This is a shorthand for [`Configurable::version_negotiation`] with the
+    /// [`VersionNegotiation`] strategy corresponding to the given version, such
+    /// as [`VersionNegotiation::http2`] for [`http::Version::HTTP_2`]. The
+    /// version of the request itself is also set to match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::{http::Version, prelude::*, Request};
+    ///
+    /// let request = Request::get("http://localhost:8080")
+    ///     .http_version(Version::HTTP_2)
+    ///     .body(())?;
+    ///
+    /// assert_eq!(request.version(), Version::HTTP_2);
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn http_version(self, version: http::Version) -> Self;
+
+    /// Append the given parameters to the query string of the request URI.
+    ///
+    /// Keys and values are percent-encoded as needed, using the
+    /// `application/x-www-form-urlencoded` format. Any query string already
+    /// present in the URI is kept, and the new parameters are added after it.
+    /// Keys are not deduplicated, so adding a key that is already present
+    /// results in the key being repeated.
+    ///
+    /// The request URI must be set before calling this method, or else it has
+    /// no effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let request = Request::get("https://example.org/search?page=2")
+    ///     .query(&[("q", "rust & curl"), ("lang", "en")])
+    ///     .body(())?;
+    ///
+    /// assert_eq!(
+    ///     request.uri(),
+    ///     "https://example.org/search?page=2&q=rust+%26+curl&lang=en"
+    /// );
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn query<I, K, V>(self, pairs: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<(K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>;
+}
+
+impl RequestBuilderExt for http::request::Builder {
+    fn if_none_match(self, etag: &str) -> Self {
+        if etag == "*" || etag.starts_with('"') || etag.starts_with("W/\"") {
+            self.header(http::header::IF_NONE_MATCH, etag)
+        } else {
+            self.header(http::header::IF_NONE_MATCH, format!("\"{}\"", etag))
+        }
+    }
+
+    fn if_modified_since(self, time: SystemTime) -> Self {
+        self.header(
+            http::header::IF_MODIFIED_SINCE,
+            httpdate::fmt_http_date(time),
+        )
+    }
+
+    fn basic_auth(self, username: &str, password: &str) -> Self {
+        let credentials = format!("{}:{}", username, password);
+
+        set_authorization(
+            self,
+            format!("Basic {}", encode_base64(credentials.as_bytes())),
+        )
+    }
+
+    fn bearer_auth(self, token: &str) -> Self {
+        set_authorization(self, format!("Bearer {}", token))
+    }
+
+    // A zero timeout disables the timeout in curl, and unlike an absent value
+    // is not replaced by the client default.
+    fn no_timeout(self) -> Self {
+        self.with_config(|config| {
+            config.timeout = Some(Duration::from_secs(0));
+        })
+    }
+
+    fn no_connect_timeout(self) -> Self {
+        self.with_config(|config| {
+            config.connect_timeout = Some(Duration::from_secs(0));
+        })
+    }
+
+    fn http_version(self, version: http::Version) -> Self {
+        self.version(version)
+            .version_negotiation(VersionNegotiation::from(version))
+    }
+
+    fn query<I, K, V>(self, pairs: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<(K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        match self.uri_ref().map(|uri| append_query(uri, pairs)) {
+            Some(uri) => self.uri(uri),
+            None => self,
+        }
+    }
+}
+
+/// Replace the `Authorization` header of a request with a sensitive value.
+fn set_authorization(mut builder: http::request::Builder, value: String) -> http::request::Builder {
+    match HeaderValue::from_str(&value) {
+        Ok(mut value) => {
+            value.set_sensitive(true);
+
+            if let Some(headers) = builder.headers_mut() {
+                headers.insert(http::header::AUTHORIZATION, value);
+            }
+
+            builder
+        }
+        // Let the builder record the invalid value as an error.
+        Err(_) => builder.header(http::header::AUTHORIZATION, value),
+    }
+}
+
+/// Append percent-encoded query parameters to a URI, keeping any existing
+/// query string.
+fn append_query<I, K, V>(uri: &Uri, pairs: I) -> Uri
+where
+    I: IntoIterator,
+    I::Item: Borrow<(K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+
+    for pair in pairs {
+        let (key, value) = pair.borrow();
+        serializer.append_pair(key.as_ref(), value.as_ref());
+    }
+
+    let query = serializer.finish();
+
+    if query.is_empty() {
+        return uri.clone();
+    }
+
+    let mut path_and_query = String::from(uri.path());
+
+    match uri.query() {
+        Some(existing) if !existing.is_empty() => {
+            path_and_query.push('?');
+            path_and_query.push_str(existing);
+
+            if !existing.ends_with('&') {
+                path_and_query.push('&');
+            }
+        }
+        _ => path_and_query.push('?'),
+    }
+
+    path_and_query.push_str(&query);
+
+    let mut parts = uri.clone().into_parts();
+
+    // The encoded query only contains valid URI characters.
+    parts.path_and_query = Some(path_and_query.parse().unwrap());
+
+    Uri::from_parts(parts).unwrap()
+}
+
 impl Configurable for http::request::Builder {
     #[cfg(feature = "cookies")]
     fn cookie_jar(self, cookie_jar: crate::cookies::CookieJar) -> Self {
@@ -109,3 +400,89 @@ impl WithRequestConfig for http::request::Builder {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn query(uri: &str, pairs: &[(&str, &str)]) -> String {
+        append_query(&uri.parse().unwrap(), pairs).to_string()
+    }
+
+    #[test]
+    fn append_query_without_existing_query() {
+        assert_eq!(
+            query("http://example.org/path", &[("a", "1"), ("b", "2")]),
+            "http://example.org/path?a=1&b=2"
+        );
+        assert_eq!(
+            query("http://example.org", &[("a", "1")]),
+            "http://example.org/?a=1"
+        );
+        assert_eq!(query("/relative?", &[("a", "1")]), "/relative?a=1");
+    }
+
+    #[test]
+    fn append_query_with_existing_query() {
+        assert_eq!(
+            query("http://example.org/?a=1", &[("b", "2")]),
+            "http://example.org/?a=1&b=2"
+        );
+        assert_eq!(
+            query("http://example.org/?a=1&", &[("b", "2")]),
+            "http://example.org/?a=1&b=2"
+        );
+    }
+
+    #[test]
+    fn append_query_repeats_keys() {
+        assert_eq!(
+            query("http://example.org/?a=1", &[("a", "2"), ("a", "3")]),
+            "http://example.org/?a=1&a=2&a=3"
+        );
+    }
+
+    #[test]
+    fn append_query_escapes_special_characters() {
+        assert_eq!(
+            query(
+                "http://example.org/",
+                &[
+                    ("name", "Jane Doe"),
+                    ("q", "a&b=c?d#e"),
+                    ("emoji", "\u{1f600}")
+                ]
+            ),
+            "http://example.org/?name=Jane+Doe&q=a%26b%3Dc%3Fd%23e&emoji=%F0%9F%98%80"
+        );
+    }
+
+    #[test]
+    fn append_nothing_leaves_uri_unchanged() {
+        assert_eq!(query("http://example.org/?a", &[]), "http://example.org/?a");
+    }
+
+    #[test_case(http::Version::HTTP_09, VersionNegotiation::http10())]
+    #[test_case(http::Version::HTTP_10, VersionNegotiation::http10())]
+    #[test_case(http::Version::HTTP_11, VersionNegotiation::http11())]
+    #[test_case(http::Version::HTTP_2, VersionNegotiation::http2())]
+    #[test_case(http::Version::HTTP_3, VersionNegotiation::http3())]
+    fn http_version_sets_version_negotiation(version: http::Version, expected: VersionNegotiation) {
+        let request = Request::get("http://example.org")
+            .http_version(version)
+            .body(())
+            .unwrap();
+
+        let negotiation = request
+            .extensions()
+            .get::<RequestConfig>()
+            .unwrap()
+            .version_negotiation
+            .as_ref()
+            .unwrap();
+
+        assert_eq!(request.version(), version);
+        assert_eq!(format!("{:?}", negotiation), format!("{:?}", expected));
+    }
+}
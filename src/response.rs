@@ -1,11 +1,29 @@
-use crate::{metrics::Metrics, redirect::EffectiveUri, trailer::Trailer};
-use futures_lite::io::{copy as copy_async, AsyncRead, AsyncWrite};
-use http::{Response, Uri};
+use crate::{
+    metrics::Metrics,
+    parsing::parse_retry_after,
+    redirect::EffectiveUri,
+    trailer::Trailer,
+};
+use crossbeam_utils::atomic::AtomicCell;
+use futures_lite::{
+    future::block_on,
+    io::{copy as copy_async, AsyncRead, AsyncReadExt, AsyncWrite},
+};
+use http::{
+    header::{AsHeaderName, HeaderValue},
+    HeaderMap,
+    Response,
+    StatusCode,
+    Uri,
+};
 use std::{
     fs::File,
     io::{self, Read, Write},
     net::SocketAddr,
     path::Path,
+    sync::Arc,
+    thread,
+    time::{Duration, SystemTime},
 };
 
 /// Provides extension methods for working with HTTP responses.
@@ -39,6 +57,12 @@ pub trait ResponseExt<T> {
     /// original URI provided when making the request if at least one redirect
     /// was followed.
     ///
+    /// The effective URI is populated for every response sent by an
+    /// [`HttpClient`](crate::HttpClient), whether or not any redirects were
+    /// followed. It reflects the URI that was actually requested, including
+    /// any normalization performed on the original request URI, such as
+    /// resolving dot segments in the path.
+    ///
     /// This information is only available if populated by the HTTP client that
     /// produced the response.
     fn effective_uri(&self) -> Option<&Uri>;
@@ -73,6 +97,228 @@ pub trait ResponseExt<T> {
     /// nearest proxy rather than the server.
     fn remote_addr(&self) -> Option<SocketAddr>;
 
+    /// Get the certificate chain presented by the server during the TLS
+    /// handshake, if known. Each certificate is returned in DER-encoded form,
+    /// starting with the server's own certificate.
+    ///
+    /// Certificate information is only collected if enabled with
+    /// [`Configurable::collect_certificate_info`](crate::config::Configurable::collect_certificate_info),
+    /// and only for SSL/TLS engines that support it. Otherwise this method
+    /// returns `None`.
+    fn peer_certificates(&self) -> Option<Vec<Vec<u8>>>;
+
+    /// Check whether the server responded with `304 Not Modified`.
+    ///
+    /// This is the expected response to a conditional request, such as one
+    /// made using
+    /// [`RequestBuilderExt::if_none_match`](crate::RequestBuilderExt::if_none_match),
+    /// if the cached copy of the resource is still up to date.
+    fn is_not_modified(&self) -> bool;
+
+    /// Get the status codes and headers of any informational (1xx) responses
+    /// that were received before the final response, in the order they were
+    /// received.
+    ///
+    /// Informational responses such as `100 Continue` or `103 Early Hints` may
+    /// carry useful headers, such as `Link` headers with preload hints. Returns
+    /// an empty slice if no informational responses were received.
+    fn informational_headers(&self) -> &[(StatusCode, HeaderMap)];
+
+    /// Check whether the response body was automatically decompressed.
+    ///
+    /// Returns `true` if
+    /// [`Configurable::automatic_decompression`](crate::config::Configurable::automatic_decompression)
+    /// is enabled and the server sent the body using a content encoding other
+    /// than `identity`, in which case the body has been decoded before being
+    /// handed to you.
+    fn was_decompressed(&self) -> bool;
+
+    /// Get the content encoding that the response body was originally sent
+    /// with, if it was automatically decompressed.
+    ///
+    /// The `Content-Encoding` header describes the data as sent by the server
+    /// and not the decompressed body you read, so this is useful for example
+    /// when deciding whether to re-compress a body before caching it.
+    fn original_content_encoding(&self) -> Option<&str>;
+
+    /// Parse the response body as a stream of [Server-Sent
+    /// Events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+    ///
+    /// This is intended for responses with a content type of
+    /// `text/event-stream`. Events are parsed incrementally as the body is
+    /// received, so the stream can be consumed for as long as the server keeps
+    /// the connection open.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use futures_lite::StreamExt;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// # async fn run() -> Result<(), isahc::Error> {
+    /// let mut events = Request::get("https://example.org/events")
+    ///     .header("Accept", "text/event-stream")
+    ///     .body(())?
+    ///     .send_async()
+    ///     .await?
+    ///     .events();
+    ///
+    /// while let Some(event) = events.next().await {
+    ///     println!("{}", event?.data());
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the [`sse`](crate#sse) feature
+    /// is enabled.
+    #[cfg(feature = "sse")]
+    fn events(self) -> crate::sse::Events<T>
+    where
+        T: futures_lite::io::AsyncBufRead + Unpin;
+
+    /// Turn a multipart response, such as a `multipart/byteranges` response to
+    /// a request for several byte ranges, into a stream of its parts.
+    ///
+    /// The boundary separating the parts is taken from the `Content-Type`
+    /// header. If the response is not multipart or has no boundary, the stream
+    /// produces a single error. See the [`multipart`](crate::multipart) module
+    /// for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use futures_lite::StreamExt;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// # async fn run() -> Result<(), isahc::Error> {
+    /// let mut parts = Request::get("https://example.org/file.bin")
+    ///     .header("Range", "bytes=0-9,20-29")
+    ///     .body(())?
+    ///     .send_async()
+    ///     .await?
+    ///     .multipart();
+    ///
+    /// while let Some(part) = parts.next().await {
+    ///     let part = part?;
+    ///     println!("{:?}", part.headers().get("content-range"));
+    /// }
+    /// # Ok(()) }
+    /// ```
+    fn multipart(self) -> crate::multipart::Parts<T>
+    where
+        T: futures_lite::io::AsyncBufRead + Unpin;
+
+    /// Fail reading the response body if no data arrives for the given amount
+    /// of time.
+    ///
+    /// Each read of the returned body that waits longer than `timeout` for the
+    /// next chunk of data fails with an error of kind
+    /// [`TimedOut`](io::ErrorKind::TimedOut). Unlike
+    /// [`Configurable::timeout`](crate::config::Configurable::timeout), this
+    /// does not limit how long the entire body may take to download, which
+    /// makes it suitable for long-lived streaming responses. The timeout only
+    /// applies while the body is being read; time spent between reads is not
+    /// counted.
+    ///
+    /// Stalled reads are woken up by a single background thread shared by all
+    /// bodies, which is started the first time a read has to wait for data.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::prelude::*;
+    /// use std::{io::Read, time::Duration};
+    ///
+    /// let mut body = isahc::get("https://example.org/stream")?
+    ///     .with_idle_timeout(Duration::from_secs(30))
+    ///     .into_body();
+    ///
+    /// let mut buf = [0; 8192];
+    /// while body.read(&mut buf)? > 0 {
+    ///     // Process the chunk...
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn with_idle_timeout(self, timeout: Duration) -> Response<crate::IdleTimeout<T>>;
+
+    /// Stream the response body into a channel in chunks.
+    ///
+    /// The returned future takes ownership of the response and reads the body
+    /// until the end, sending each chunk read into the given channel. This
+    /// makes it possible to hand the body off to a consumer in another task
+    /// without keeping the response around. If the channel is bounded then
+    /// reading is paused while the channel is full, so a slow consumer applies
+    /// backpressure to the transfer.
+    ///
+    /// If reading the body fails, the error is sent into the channel and no
+    /// further chunks are sent. The future completes once the end of the body
+    /// has been reached, an error has occurred, or the receiving side of the
+    /// channel has been closed. The sender is dropped on completion, so the
+    /// channel is closed if it was the last sender.
+    ///
+    /// The channel comes from the `async-channel` crate, which is re-exported
+    /// as [`isahc::async_channel`](crate::async_channel) so that a compatible
+    /// version is always available.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::prelude::*;
+    ///
+    /// # async fn run() -> Result<(), isahc::Error> {
+    /// let (sender, receiver) = isahc::async_channel::bounded(8);
+    ///
+    /// let response = isahc::get_async("https://example.org").await?;
+    /// let producer = response.stream_to_channel(sender);
+    ///
+    /// let consumer = async move {
+    ///     while let Ok(chunk) = receiver.recv().await {
+    ///         println!("received {} bytes", chunk?.len());
+    ///     }
+    ///     Ok::<(), std::io::Error>(())
+    /// };
+    ///
+    /// let ((), result) = futures_lite::future::zip(producer, consumer).await;
+    /// result?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the [`bytes`](crate#bytes)
+    /// feature is enabled.
+    #[cfg(feature = "bytes")]
+    fn stream_to_channel<'a>(
+        self,
+        sender: async_channel::Sender<io::Result<bytes::Bytes>>,
+    ) -> StreamToChannelFuture<'a, T>
+    where
+        T: AsyncRead + Unpin + 'a;
+
+    /// Get the value of the `Retry-After` header, if present and valid.
+    ///
+    /// Servers send this header along with responses such as `429 Too Many
+    /// Requests` or `503 Service Unavailable` to indicate how long the client
+    /// ought to wait before making another request. Both the delay-seconds and
+    /// HTTP-date forms are supported. Returns `None` if the header is missing
+    /// or malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::{prelude::*, RetryAfter};
+    ///
+    /// let response = isahc::get("https://example.org")?;
+    ///
+    /// if let Some(RetryAfter::Delay(delay)) = response.retry_after() {
+    ///     std::thread::sleep(delay);
+    /// }
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn retry_after(&self) -> Option<RetryAfter>;
+
     /// Get the configured cookie jar used for persisting cookies from this
     /// response, if any.
     ///
@@ -90,6 +336,117 @@ pub trait ResponseExt<T> {
     /// metrics you can use
     /// [`Configurable::metrics`](crate::config::Configurable::metrics).
     fn metrics(&self) -> Option<&Metrics>;
+
+    /// Get the status line of the response exactly as it was received from the
+    /// server, without the trailing line ending.
+    ///
+    /// The status and version of a [`Response`] are normalized, which loses
+    /// information about responses using non-standard protocol tokens, such as
+    /// `ICY 200 OK` (see
+    /// [`Configurable::http_200_aliases`](crate::config::Configurable::http_200_aliases)).
+    /// The raw status line can be useful for debugging such servers. For HTTP/2
+    /// and newer, this is a status line synthesized by curl.
+    ///
+    /// Returns `None` if the response was not received from a server, such as
+    /// one constructed by hand.
+    fn raw_status_line(&self) -> Option<&str>;
+
+    /// Get the application protocol that was negotiated with the server using
+    /// ALPN during the TLS handshake, such as `h2` or `http/1.1`.
+    ///
+    /// This is read from the TLS session, so it is only available if curl uses
+    /// OpenSSL as its SSL/TLS engine, which is the default on Unix platforms
+    /// other than macOS.
+    ///
+    /// Returns `None` if the request was not made over TLS, if the server did
+    /// not select a protocol, or if the information is not available.
+    fn negotiated_alpn(&self) -> Option<&str>;
+
+    /// Get the TLS protocol version and cipher suite that were negotiated with
+    /// the server during the TLS handshake.
+    ///
+    /// This is read from the TLS session, so it is only available if curl uses
+    /// OpenSSL as its SSL/TLS engine, which is the default on Unix platforms
+    /// other than macOS.
+    ///
+    /// Returns `None` if the request was not made over TLS or if the
+    /// information is not available.
+    fn tls_info(&self) -> Option<TlsInfo>;
+
+    /// Get the tag of the request that produced this response, if one was set
+    /// using [`Configurable::tag`](crate::config::Configurable::tag).
+    fn tag(&self) -> Option<&str>;
+
+    /// Get the number of bytes of the request body that have been sent so far.
+    ///
+    /// Unlike [`ResponseExt::metrics`], this counter is always tracked and does
+    /// not need to be enabled. Returns `None` if the response was not produced
+    /// by a transfer, such as one constructed by hand.
+    fn request_body_len(&self) -> Option<u64>;
+
+    /// Get the number of bytes of the response body that have been received so
+    /// far.
+    ///
+    /// The count is taken after any automatic decompression has been applied,
+    /// so it matches the number of bytes you can read from the body. Since the
+    /// body is streamed, the count is only final once the body has been fully
+    /// consumed. Returns `None` if the response was not produced by a
+    /// transfer, such as one constructed by hand.
+    fn response_body_len(&self) -> Option<u64>;
+
+    /// Get the length of the response body announced by the server, if known.
+    ///
+    /// This is usually the value of the `Content-Length` header. If the header
+    /// is absent but the length of the body is still known to the transfer,
+    /// such as for some HTTP/2 responses, then that length is returned
+    /// instead. Returns `None` if the length is not known ahead of time, such
+    /// as for responses using chunked transfer encoding.
+    ///
+    /// Note that if the body is automatically decompressed, the length
+    /// reported is of the compressed body as sent by the server.
+    fn content_length(&self) -> Option<u64>;
+
+    /// Check whether the request that produced this response was sent over a
+    /// connection that was already open, rather than a new one.
+    ///
+    /// Returns `false` if a new connection had to be established, or if the
+    /// response was not produced by a transfer, such as one constructed by
+    /// hand.
+    fn connection_reused(&self) -> bool;
+
+    /// Get all values of the given header, in the order they were received.
+    ///
+    /// Header names are matched case-insensitively. This is a shorthand for
+    /// collecting [`HeaderMap::get_all`] and is useful for headers that may be
+    /// repeated, such as `Set-Cookie` or `Via`. Returns an empty vector if the
+    /// header is not present.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::prelude::*;
+    ///
+    /// let response = isahc::get("https://example.org")?;
+    ///
+    /// for hop in response.header_all("via") {
+    ///     println!("via {:?}", hop);
+    /// }
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    fn header_all<K>(&self, name: K) -> Vec<&HeaderValue>
+    where
+        K: AsHeaderName;
+
+    /// Get the first value of the given header as a string.
+    ///
+    /// Header names are matched case-insensitively. Returns `None` if the
+    /// header is not present, or if its first value contains bytes that are
+    /// not visible ASCII characters and so cannot be represented as a string
+    /// safely. Use [`ResponseExt::header_all`] or [`Response::headers`] to get
+    /// at the raw bytes of such values.
+    fn header_str<K>(&self, name: K) -> Option<&str>
+    where
+        K: AsHeaderName;
 }
 
 impl<T> ResponseExt<T> for Response<T> {
@@ -112,6 +469,88 @@ impl<T> ResponseExt<T> for Response<T> {
         self.extensions().get::<RemoteAddr>().map(|v| v.0)
     }
 
+    fn peer_certificates(&self) -> Option<Vec<Vec<u8>>> {
+        self.extensions()
+            .get::<PeerCertificates>()
+            .map(|v| v.0.clone())
+    }
+
+    fn is_not_modified(&self) -> bool {
+        self.status() == StatusCode::NOT_MODIFIED
+    }
+
+    fn informational_headers(&self) -> &[(StatusCode, HeaderMap)] {
+        self.extensions()
+            .get::<InformationalResponses>()
+            .map(|v| v.0.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn was_decompressed(&self) -> bool {
+        self.original_content_encoding().is_some()
+    }
+
+    fn original_content_encoding(&self) -> Option<&str> {
+        self.extensions()
+            .get::<OriginalContentEncoding>()
+            .map(|v| v.0.as_str())
+    }
+
+    #[cfg(feature = "sse")]
+    fn events(self) -> crate::sse::Events<T>
+    where
+        T: futures_lite::io::AsyncBufRead + Unpin,
+    {
+        crate::sse::Events::new(self.into_body())
+    }
+
+    fn multipart(self) -> crate::multipart::Parts<T>
+    where
+        T: futures_lite::io::AsyncBufRead + Unpin,
+    {
+        let (parts, body) = self.into_parts();
+        crate::multipart::Parts::new(body, &parts.headers)
+    }
+
+    fn with_idle_timeout(self, timeout: Duration) -> Response<crate::IdleTimeout<T>> {
+        self.map(|body| crate::IdleTimeout::new(body, timeout))
+    }
+
+    #[cfg(feature = "bytes")]
+    fn stream_to_channel<'a>(
+        self,
+        sender: async_channel::Sender<io::Result<bytes::Bytes>>,
+    ) -> StreamToChannelFuture<'a, T>
+    where
+        T: AsyncRead + Unpin + 'a,
+    {
+        let mut body = self.into_body();
+
+        StreamToChannelFuture::new(async move {
+            let mut buf = vec![0; 16 * 1024];
+
+            loop {
+                let chunk = match body.read(&mut buf).await {
+                    Ok(0) => break,
+                    Ok(len) => Ok(bytes::Bytes::copy_from_slice(&buf[..len])),
+                    Err(e) => Err(e),
+                };
+                let is_err = chunk.is_err();
+
+                // Stop early if nobody is listening anymore.
+                if sender.send(chunk).await.is_err() || is_err {
+                    break;
+                }
+            }
+        })
+    }
+
+    fn retry_after(&self) -> Option<RetryAfter> {
+        self.headers()
+            .get(http::header::RETRY_AFTER)
+            .and_then(|value| parse_retry_after(value.as_bytes()))
+    }
+
     #[cfg(feature = "cookies")]
     fn cookie_jar(&self) -> Option<&crate::cookies::CookieJar> {
         self.extensions().get()
@@ -120,6 +559,98 @@ impl<T> ResponseExt<T> for Response<T> {
     fn metrics(&self) -> Option<&Metrics> {
         self.extensions().get()
     }
+
+    fn raw_status_line(&self) -> Option<&str> {
+        self.extensions()
+            .get::<RawStatusLine>()
+            .map(|v| v.0.as_str())
+    }
+
+    fn negotiated_alpn(&self) -> Option<&str> {
+        self.extensions()
+            .get::<NegotiatedAlpn>()
+            .map(|v| v.0.as_str())
+    }
+
+    fn tls_info(&self) -> Option<TlsInfo> {
+        self.extensions().get::<TlsInfo>().cloned()
+    }
+
+    fn tag(&self) -> Option<&str> {
+        self.extensions().get::<RequestTag>().map(|v| v.0.as_str())
+    }
+
+    fn request_body_len(&self) -> Option<u64> {
+        self.extensions()
+            .get::<BodyLengths>()
+            .map(|v| v.0.sent.load())
+    }
+
+    fn response_body_len(&self) -> Option<u64> {
+        self.extensions()
+            .get::<BodyLengths>()
+            .map(|v| v.0.received.load())
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.headers()
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .or_else(|| self.extensions().get::<ContentLength>().map(|v| v.0))
+    }
+
+    fn connection_reused(&self) -> bool {
+        self.extensions().get::<ConnectionReused>().is_some()
+    }
+
+    fn header_all<K>(&self, name: K) -> Vec<&HeaderValue>
+    where
+        K: AsHeaderName,
+    {
+        self.headers().get_all(name).iter().collect()
+    }
+
+    fn header_str<K>(&self, name: K) -> Option<&str>
+    where
+        K: AsHeaderName,
+    {
+        self.headers().get(name).and_then(|v| v.to_str().ok())
+    }
+}
+
+/// The parsed value of a `Retry-After` response header.
+///
+/// Returned by [`ResponseExt::retry_after`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RetryAfter {
+    /// The client should wait for the given amount of time after receiving the
+    /// response before retrying.
+    Delay(Duration),
+
+    /// The client should wait until the given point in time before retrying.
+    DateTime(SystemTime),
+}
+
+/// Parameters of the TLS session used to make a request.
+///
+/// Returned by [`ResponseExt::tls_info`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TlsInfo {
+    pub(crate) version: String,
+    pub(crate) cipher: String,
+}
+
+impl TlsInfo {
+    /// Get the TLS protocol version, such as `TLSv1.3`.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Get the name of the cipher suite, as reported by OpenSSL.
+    pub fn cipher(&self) -> &str {
+        &self.cipher
+    }
 }
 
 /// Provides extension methods for consuming HTTP response streams.
@@ -210,6 +741,41 @@ pub trait ReadResponseExt<R: Read> {
         File::create(path).and_then(|f| self.copy_to(f))
     }
 
+    /// Read the entire response body into a reference-counted
+    /// [`Bytes`](bytes::Bytes) buffer.
+    ///
+    /// Unlike reading the body into a `Vec<u8>`, the returned buffer can be
+    /// cloned cheaply without copying the body, which is useful when passing
+    /// the body to multiple consumers.
+    ///
+    /// This method consumes the entire response body stream and can only be
+    /// called once.
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the [`bytes`](crate#bytes)
+    /// feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::prelude::*;
+    ///
+    /// let body = isahc::get("https://example.org")?.bytes_shared()?;
+    /// let copy = body.clone();
+    /// println!("Read {} bytes", copy.len());
+    /// # Ok::<(), isahc::Error>(())
+    /// ```
+    #[cfg(feature = "bytes")]
+    fn bytes_shared(&mut self) -> io::Result<bytes::Bytes> {
+        use bytes::BufMut;
+
+        let mut writer = bytes::BytesMut::new().writer();
+        self.copy_to(&mut writer)?;
+
+        Ok(writer.into_inner().freeze())
+    }
+
     /// Read the response body as a string.
     ///
     /// The encoding used to decode the response body into a string depends on
@@ -265,6 +831,45 @@ pub trait ReadResponseExt<R: Read> {
     fn json<T>(&mut self) -> Result<T, serde_json::Error>
     where
         T: serde::de::DeserializeOwned;
+
+    /// Deserialize the response body as JSON using a custom function.
+    ///
+    /// The given function is handed a JSON deserializer reading from the
+    /// response body, which it can configure or wrap before deserializing a
+    /// value with it. This is useful for reporting more detailed errors, such
+    /// as the path to the field that failed to deserialize, or for using
+    /// deserialization options that [`ReadResponseExt::json`] does not.
+    ///
+    /// Unlike [`ReadResponseExt::json`], trailing data after the JSON value is
+    /// not rejected unless the function calls
+    /// [`Deserializer::end`](serde_json::Deserializer::end).
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the [`json`](index.html#json) feature
+    /// is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::prelude::*;
+    /// use serde::Deserialize;
+    /// use serde_json::Value;
+    ///
+    /// let json = isahc::get("https://httpbin.org/json")?.json_with(|de| {
+    ///     let value = Value::deserialize(&mut *de)?;
+    ///     de.end()?;
+    ///     Ok::<_, serde_json::Error>(value)
+    /// })?;
+    /// println!("author: {}", json["slideshow"]["author"]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "json")]
+    fn json_with<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(
+            &mut serde_json::Deserializer<serde_json::de::IoRead<&mut dyn Read>>,
+        ) -> Result<T, E>;
 }
 
 impl<R: Read> ReadResponseExt<R> for Response<R> {
@@ -284,6 +889,16 @@ impl<R: Read> ReadResponseExt<R> for Response<R> {
     {
         serde_json::from_reader(self.body_mut())
     }
+
+    #[cfg(feature = "json")]
+    fn json_with<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(
+            &mut serde_json::Deserializer<serde_json::de::IoRead<&mut dyn Read>>,
+        ) -> Result<T, E>,
+    {
+        f(&mut serde_json::Deserializer::from_reader(self.body_mut()))
+    }
 }
 
 /// Provides extension methods for consuming asynchronous HTTP response streams.
@@ -355,6 +970,27 @@ pub trait AsyncReadResponseExt<R: AsyncRead + Unpin> {
     where
         W: AsyncWrite + Unpin + 'a;
 
+    /// Write the response body to a file asynchronously.
+    ///
+    /// Since there is no asynchronous file API in the standard library, the
+    /// file is created and written to on a separate background thread, so
+    /// that neither blocks the executor polling this future. Chunks of the
+    /// body are handed off to the thread as they are received.
+    ///
+    /// Returns the number of bytes that were written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use isahc::prelude::*;
+    ///
+    /// # async fn run() -> Result<(), isahc::Error> {
+    /// isahc::get_async("https://httpbin.org/image/jpeg").await?
+    ///     .copy_to_file("myimage.jpg").await?;
+    /// # Ok(()) }
+    /// ```
+    fn copy_to_file<P: AsRef<Path>>(&mut self, path: P) -> CopyToFileFuture<'_, R>;
+
     /// Read the response body as a string asynchronously.
     ///
     /// This method consumes the entire response body stream and can only be
@@ -410,6 +1046,25 @@ pub trait AsyncReadResponseExt<R: AsyncRead + Unpin> {
     fn json<T>(&mut self) -> JsonFuture<'_, R, T>
     where
         T: serde::de::DeserializeOwned;
+
+    /// Deserialize the response body as JSON using a custom function.
+    ///
+    /// This is the asynchronous equivalent of [`ReadResponseExt::json_with`].
+    /// The entire response body is read into memory before the function is
+    /// called, for the same reasons as [`AsyncReadResponseExt::json`]. If
+    /// reading the body fails, the error is reported by the deserializer.
+    ///
+    /// # Availability
+    ///
+    /// This method is only available when the [`json`](index.html#json) feature
+    /// is enabled.
+    #[cfg(feature = "json")]
+    fn json_with<'a, T, E, F>(&'a mut self, f: F) -> JsonWithFuture<'a, R, T, E, F>
+    where
+        F: FnOnce(
+                &mut serde_json::Deserializer<serde_json::de::IoRead<&mut dyn Read>>,
+            ) -> Result<T, E>
+            + 'a;
 }
 
 impl<R: AsyncRead + Unpin> AsyncReadResponseExt<R> for Response<R> {
@@ -428,6 +1083,56 @@ impl<R: AsyncRead + Unpin> AsyncReadResponseExt<R> for Response<R> {
         CopyFuture::new(async move { copy_async(self.body_mut(), writer).await })
     }
 
+    fn copy_to_file<P: AsRef<Path>>(&mut self, path: P) -> CopyToFileFuture<'_, R> {
+        let path = path.as_ref().to_owned();
+
+        CopyToFileFuture::new(async move {
+            // Keep only a few chunks in flight, so that a slow disk applies
+            // backpressure to reading the response.
+            let (chunk_tx, chunk_rx) = async_channel::bounded::<Vec<u8>>(4);
+            let (result_tx, result_rx) = async_channel::bounded(1);
+
+            thread::Builder::new()
+                .name(String::from("isahc-copy-to-file"))
+                .spawn(move || {
+                    let result = File::create(path).and_then(|mut file| {
+                        let mut written = 0;
+
+                        while let Ok(chunk) = block_on(chunk_rx.recv()) {
+                            file.write_all(&chunk)?;
+                            written += chunk.len() as u64;
+                        }
+
+                        file.flush()?;
+
+                        Ok(written)
+                    });
+
+                    let _ = result_tx.try_send(result);
+                })?;
+
+            let mut buf = vec![0; 16 * 1024];
+
+            loop {
+                let len = self.body_mut().read(&mut buf).await?;
+
+                // If sending fails then the file could not be written to, and
+                // the error will be returned below.
+                if len == 0 || chunk_tx.send(buf[..len].to_vec()).await.is_err() {
+                    break;
+                }
+            }
+
+            drop(chunk_tx);
+
+            // The writer thread only fails to send a result if it panicked.
+            result_rx
+                .recv()
+                .await
+                .unwrap_or_else(|_| Err(io::ErrorKind::BrokenPipe.into()))
+        })
+    }
+
     #[cfg(feature = "text-decoding")]
     fn text(&mut self) -> crate::text::TextFuture<'_, &mut R> {
         crate::text::Decoder::for_response(&self).decode_reader_async(self.body_mut())
@@ -445,14 +1150,6 @@ impl<R: AsyncRead + Unpin> AsyncReadResponseExt<R> for Response<R> {
             // to reading the entire response into memory first and then
             // deserializing.
             if let Err(e) = copy_async(self.body_mut(), &mut buf).await {
-                struct ErrorReader(Option<io::Error>);
-
-                impl Read for ErrorReader {
-                    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
-                        Err(self.0.take().unwrap())
-                    }
-                }
-
                 // Serde offers no public way to directly create an error from
                 // an I/O error, but we can do so in a roundabout way by parsing
                 // a reader that always returns the desired error.
@@ -462,6 +1159,43 @@ impl<R: AsyncRead + Unpin> AsyncReadResponseExt<R> for Response<R> {
             }
         })
     }
+
+    #[cfg(feature = "json")]
+    fn json_with<'a, T, E, F>(&'a mut self, f: F) -> JsonWithFuture<'a, R, T, E, F>
+    where
+        F: FnOnce(
+                &mut serde_json::Deserializer<serde_json::de::IoRead<&mut dyn Read>>,
+            ) -> Result<T, E>
+            + 'a,
+    {
+        JsonWithFuture::new(async move {
+            let mut buf = Vec::new();
+
+            match copy_async(self.body_mut(), &mut buf).await {
+                Ok(_) => f(&mut serde_json::Deserializer::from_reader(
+                    &mut buf.as_slice(),
+                )),
+                Err(e) => f(&mut serde_json::Deserializer::from_reader(
+                    &mut ErrorReader(Some(e)),
+                )),
+            }
+        })
+    }
+}
+
+/// A reader that always fails with the given error, used to report an I/O
+/// error through a JSON deserializer.
+#[cfg(feature = "json")]
+struct ErrorReader(Option<io::Error>);
+
+#[cfg(feature = "json")]
+impl Read for ErrorReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(self
+            .0
+            .take()
+            .unwrap_or_else(|| io::ErrorKind::UnexpectedEof.into()))
+    }
 }
 
 decl_future! {
@@ -472,15 +1206,57 @@ decl_future! {
     /// A future which copies all the response body bytes into a sink.
     pub type CopyFuture<R, W> = impl Future<Output = io::Result<u64>> + SendIf<R, W>;
 
+    /// A future which writes all the response body bytes into a file.
+    pub type CopyToFileFuture<R> = impl Future<Output = io::Result<u64>> + SendIf<R>;
+
+    /// A future which streams the response body into a channel.
+    #[cfg(feature = "bytes")]
+    pub type StreamToChannelFuture<R> = impl Future<Output = ()> + SendIf<R>;
+
     /// A future which deserializes the response body as JSON.
     #[cfg(feature = "json")]
     pub type JsonFuture<R, T> = impl Future<Output = Result<T, serde_json::Error>> + SendIf<R, T>;
+
+    /// A future which deserializes the response body as JSON using a custom
+    /// function.
+    #[cfg(feature = "json")]
+    pub type JsonWithFuture<R, T, E, D> = impl Future<Output = Result<T, E>> + SendIf<R, T, E, D>;
 }
 
+pub(crate) struct InformationalResponses(pub(crate) Vec<(StatusCode, HeaderMap)>);
+
+pub(crate) struct OriginalContentEncoding(pub(crate) String);
+
 pub(crate) struct LocalAddr(pub(crate) SocketAddr);
 
 pub(crate) struct RemoteAddr(pub(crate) SocketAddr);
 
+pub(crate) struct PeerCertificates(pub(crate) Vec<Vec<u8>>);
+
+pub(crate) struct RawStatusLine(pub(crate) String);
+
+pub(crate) struct NegotiatedAlpn(pub(crate) String);
+
+pub(crate) struct RequestTag(pub(crate) String);
+
+/// Length of the response body known to curl when no `Content-Length` header
+/// was received.
+pub(crate) struct ContentLength(pub(crate) u64);
+
+/// Marker set when the transfer did not need to open any new connections.
+pub(crate) struct ConnectionReused;
+
+/// Running totals of request and response body bytes transferred, updated by
+/// the request handler.
+#[derive(Clone, Default)]
+pub(crate) struct BodyLengths(pub(crate) Arc<BodyLengthsInner>);
+
+#[derive(Default)]
+pub(crate) struct BodyLengthsInner {
+    pub(crate) sent: AtomicCell<u64>,
+    pub(crate) received: AtomicCell<u64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
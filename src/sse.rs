@@ -0,0 +1,361 @@
+//! Parsing of [Server-Sent
+//! Events](https://html.spec.whatwg.org/multipage/server-sent-events.html)
+//! response bodies.
+//!
+//! Responses with a content type of `text/event-stream` can be turned into a
+//! stream of [`Event`]s using
+//! [`ResponseExt::events`](crate::ResponseExt::events).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use futures_lite::StreamExt;
+//! use isahc::prelude::*;
+//!
+//! # async fn run() -> Result<(), isahc::Error> {
+//! let mut events = isahc::get_async("https://example.org/events").await?.events();
+//!
+//! while let Some(event) = events.next().await {
+//!     let event = event?;
+//!     println!("{}: {}", event.event(), event.data());
+//! }
+//! # Ok(()) }
+//! ```
+
+use crate::error::Error;
+use futures_lite::{io::AsyncBufRead, Stream};
+use std::{
+    fmt,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// A single event received from an event stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    id: Option<String>,
+    event: String,
+    data: String,
+    retry: Option<Duration>,
+}
+
+impl Event {
+    /// Get the last event ID set by the server at the time this event was
+    /// received, if any.
+    ///
+    /// As per the specification, the last event ID persists across events
+    /// until the server sets a new one. This is the value that should be sent
+    /// in a `Last-Event-ID` header when reconnecting.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Get the type of this event. Defaults to `message` if the server did not
+    /// specify one.
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// Get the data of this event. Multiple `data` lines of a single event are
+    /// joined together with a newline.
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// Get the reconnection time requested by the server alongside this event,
+    /// if any.
+    ///
+    /// Servers may also send a reconnection time on its own without any event
+    /// data, in which case no event is produced. Use
+    /// [`Events::reconnection_time`] to get the most recent reconnection time
+    /// received regardless of how it was sent.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+}
+
+/// A stream of [`Event`]s parsed from a response body.
+///
+/// Created by [`ResponseExt::events`](crate::ResponseExt::events).
+pub struct Events<R> {
+    reader: R,
+
+    /// The current line being read.
+    line: Vec<u8>,
+
+    /// Set if the last line ended with a carriage return, in which case a
+    /// following line feed is part of the same line ending.
+    pending_cr: bool,
+
+    /// Set until the first line has been read, which may begin with a byte
+    /// order mark.
+    first_line: bool,
+
+    finished: bool,
+
+    // Buffers for the event currently being parsed.
+    event: String,
+    data: String,
+    retry: Option<Duration>,
+
+    last_event_id: String,
+    reconnection_time: Option<Duration>,
+}
+
+impl<R: AsyncBufRead + Unpin> Events<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            pending_cr: false,
+            first_line: true,
+            finished: false,
+            event: String::new(),
+            data: String::new(),
+            retry: None,
+            last_event_id: String::new(),
+            reconnection_time: None,
+        }
+    }
+}
+
+impl<R> Events<R> {
+    /// Get the most recent reconnection time requested by the server, if any.
+    pub fn reconnection_time(&self) -> Option<Duration> {
+        self.reconnection_time
+    }
+
+    /// Process a single complete line, returning an event if the line
+    /// completes one.
+    fn process_line(&mut self, mut line: &[u8]) -> Option<Event> {
+        if mem::replace(&mut self.first_line, false) && line.starts_with(b"\xEF\xBB\xBF") {
+            line = &line[3..];
+        }
+
+        // A blank line dispatches the event.
+        if line.is_empty() {
+            return self.dispatch();
+        }
+
+        // Lines beginning with a colon are comments.
+        if line.starts_with(b":") {
+            return None;
+        }
+
+        let line = String::from_utf8_lossy(line);
+        let (field, value) = match line.find(':') {
+            Some(i) => {
+                let value = &line[i + 1..];
+                (&line[..i], value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (&line[..], ""),
+        };
+
+        match field {
+            "event" => {
+                self.event.clear();
+                self.event.push_str(value);
+            }
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            // IDs containing a null character are ignored.
+            "id" if !value.contains('\0') => {
+                self.last_event_id.clear();
+                self.last_event_id.push_str(value);
+            }
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                    self.reconnection_time = self.retry;
+                }
+            }
+            // Unknown fields are ignored.
+            _ => {}
+        }
+
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let retry = self.retry.take();
+        let event = mem::take(&mut self.event);
+
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let mut data = mem::take(&mut self.data);
+        data.pop();
+
+        Some(Event {
+            id: Some(self.last_event_id.clone()).filter(|id| !id.is_empty()),
+            event: if event.is_empty() {
+                String::from("message")
+            } else {
+                event
+            },
+            data,
+            retry,
+        })
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for Events<R> {
+    type Item = Result<Event, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            let (consumed, line_complete) = {
+                let buf = match Pin::new(&mut this.reader).poll_fill_buf(cx) {
+                    Poll::Ready(Ok(buf)) => buf,
+                    Poll::Ready(Err(e)) => {
+                        this.finished = true;
+                        return Poll::Ready(Some(Err(e.into())));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                };
+
+                // Any incomplete event at the end of the stream is discarded.
+                if buf.is_empty() {
+                    this.finished = true;
+                    return Poll::Ready(None);
+                }
+
+                if mem::replace(&mut this.pending_cr, false) && buf[0] == b'\n' {
+                    (1, false)
+                } else if let Some(i) = buf.iter().position(|&b| b == b'\r' || b == b'\n') {
+                    this.line.extend_from_slice(&buf[..i]);
+                    this.pending_cr = buf[i] == b'\r';
+                    (i + 1, true)
+                } else {
+                    this.line.extend_from_slice(buf);
+                    (buf.len(), false)
+                }
+            };
+
+            Pin::new(&mut this.reader).consume(consumed);
+
+            if line_complete {
+                let line = mem::take(&mut this.line);
+                let event = this.process_line(&line);
+                this.line = line;
+                this.line.clear();
+
+                if let Some(event) = event {
+                    return Poll::Ready(Some(Ok(event)));
+                }
+            }
+        }
+    }
+}
+
+impl<R> fmt::Debug for Events<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Events")
+            .field("last_event_id", &self.last_event_id)
+            .field("reconnection_time", &self.reconnection_time)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::{future::block_on, io::BufReader, StreamExt};
+
+    fn parse(input: &'static [u8]) -> Vec<Event> {
+        block_on(Events::new(input).map(Result::unwrap).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parse_simple_events() {
+        let events = parse(b"data: hello\n\nevent: greeting\ndata: world\n\n");
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event(), "message");
+        assert_eq!(events[0].data(), "hello");
+        assert_eq!(events[0].id(), None);
+        assert_eq!(events[1].event(), "greeting");
+        assert_eq!(events[1].data(), "world");
+    }
+
+    #[test]
+    fn parse_multi_line_data() {
+        let events = parse(b"data: first\ndata:second\ndata\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data(), "first\nsecond\n");
+    }
+
+    #[test]
+    fn comments_and_unknown_fields_are_ignored() {
+        let events = parse(b": keep-alive\nfoo: bar\ndata: hello\n\n:\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data(), "hello");
+    }
+
+    #[test]
+    fn last_event_id_persists() {
+        let events = parse(b"id: 1\ndata: a\n\ndata: b\n\nid\ndata: c\n\n");
+
+        assert_eq!(events[0].id(), Some("1"));
+        assert_eq!(events[1].id(), Some("1"));
+        assert_eq!(events[2].id(), None);
+    }
+
+    #[test]
+    fn retry_is_parsed() {
+        block_on(async {
+            let mut events =
+                Events::new(&b"retry: 3000\n\nretry: soon\ndata: a\n\nretry: 500\ndata: b\n\n"[..]);
+
+            let event = events.next().await.unwrap().unwrap();
+            assert_eq!(event.data(), "a");
+            assert_eq!(event.retry(), None);
+            assert_eq!(events.reconnection_time(), Some(Duration::from_secs(3)));
+
+            let event = events.next().await.unwrap().unwrap();
+            assert_eq!(event.retry(), Some(Duration::from_millis(500)));
+            assert_eq!(events.reconnection_time(), Some(Duration::from_millis(500)));
+        });
+    }
+
+    #[test]
+    fn all_line_endings_are_supported() {
+        let input: &[u8] = b"\xEF\xBB\xBFdata: a\r\n\r\ndata: b\r\rdata: c\n\n";
+        let expected = vec!["a", "b", "c"];
+
+        assert_eq!(
+            parse(input).iter().map(Event::data).collect::<Vec<_>>(),
+            expected
+        );
+
+        // Also check line endings split across reads.
+        let events = block_on(
+            Events::new(BufReader::with_capacity(1, input))
+                .map(Result::unwrap)
+                .collect::<Vec<_>>(),
+        );
+
+        assert_eq!(events.iter().map(Event::data).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn incomplete_event_is_discarded() {
+        let events = parse(b"data: a\n\ndata: b\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data(), "a");
+    }
+}
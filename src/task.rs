@@ -1,6 +1,16 @@
 //! Helpers for working with tasks and futures.
 
-use std::task::Waker;
+use futures_lite::future::poll_fn;
+use once_cell::sync::Lazy;
+use std::{
+    collections::BTreeMap,
+    io,
+    mem,
+    sync::{Condvar, Mutex},
+    task::{Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
 
 /// Helper methods for working with wakers.
 pub(crate) trait WakerExt {
@@ -15,3 +25,163 @@ impl WakerExt for Waker {
         waker_fn::waker_fn(move || (f)(&inner))
     }
 }
+
+/// Wait asynchronously for the given amount of time to elapse, using the
+/// shared timer thread.
+///
+/// Returns an error if the timer thread could not be started.
+pub(crate) async fn sleep(duration: Duration) -> io::Result<()> {
+    let deadline = Instant::now() + duration;
+    let mut entry = None;
+
+    poll_fn(|cx| {
+        if Instant::now() >= deadline {
+            return Poll::Ready(Ok(()));
+        }
+
+        // Register again on every poll in case the task's waker has changed.
+        // This also removes the previous registration.
+        match wake_at(deadline, cx.waker().clone()) {
+            Ok(new_entry) => {
+                entry = Some(new_entry);
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    })
+    .await
+}
+
+/// A single background thread shared by everything that needs to wake up a
+/// task at a certain time, started the first time it is needed.
+static TIMER: Lazy<Timer> = Lazy::new(|| Timer {
+    state: Mutex::new(TimerState {
+        wakers: BTreeMap::new(),
+        next_id: 0,
+        started: false,
+    }),
+    condvar: Condvar::new(),
+});
+
+struct Timer {
+    state: Mutex<TimerState>,
+    condvar: Condvar,
+}
+
+struct TimerState {
+    /// Wakers to wake, ordered by deadline and then by registration order.
+    wakers: BTreeMap<(Instant, u64), Waker>,
+    next_id: u64,
+    started: bool,
+}
+
+/// A waker registered with the shared timer thread. The waker is removed from
+/// the timer when this is dropped.
+#[derive(Debug)]
+pub(crate) struct TimerEntry {
+    key: (Instant, u64),
+}
+
+impl TimerEntry {
+    /// Get the time at which the waker will be woken.
+    pub(crate) fn deadline(&self) -> Instant {
+        self.key.0
+    }
+}
+
+impl Drop for TimerEntry {
+    fn drop(&mut self) {
+        TIMER.state.lock().unwrap().wakers.remove(&self.key);
+    }
+}
+
+/// Wake the given waker once the deadline has passed, using the shared timer
+/// thread.
+///
+/// Returns an error if the timer thread could not be started.
+pub(crate) fn wake_at(deadline: Instant, waker: Waker) -> io::Result<TimerEntry> {
+    let mut state = TIMER.state.lock().unwrap();
+
+    if !state.started {
+        thread::Builder::new()
+            .name(String::from("isahc-timer"))
+            .spawn(|| TIMER.run())?;
+        state.started = true;
+    }
+
+    let key = (deadline, state.next_id);
+    state.next_id += 1;
+    state.wakers.insert(key, waker);
+
+    // The new deadline might be earlier than the one the thread is waiting for.
+    TIMER.condvar.notify_one();
+
+    Ok(TimerEntry { key })
+}
+
+impl Timer {
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            let now = Instant::now();
+
+            // Take all expired wakers and wake them without holding the lock,
+            // since waking a task might register a new waker.
+            let pending = state.wakers.split_off(&(now, u64::MAX));
+            let expired = mem::replace(&mut state.wakers, pending);
+
+            if !expired.is_empty() {
+                drop(state);
+                expired.into_iter().for_each(|(_, waker)| waker.wake());
+                state = self.state.lock().unwrap();
+                continue;
+            }
+
+            state = match state.wakers.keys().next() {
+                Some((deadline, _)) => {
+                    let wait = deadline.saturating_duration_since(now);
+                    self.condvar.wait_timeout(state, wait).unwrap().0
+                }
+                None => self.condvar.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn timer_wakes_registered_wakers_but_not_dropped_ones() {
+        let woken = Arc::new(AtomicUsize::new(0));
+        let waker = {
+            let woken = woken.clone();
+            waker_fn::waker_fn(move || {
+                woken.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let _kept = wake_at(deadline, waker.clone()).unwrap();
+        drop(wake_at(deadline, waker).unwrap());
+
+        thread::sleep(Duration::from_millis(200));
+
+        assert_eq!(woken.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn sleep_waits_for_duration() {
+        let start = Instant::now();
+
+        futures_lite::future::block_on(sleep(Duration::from_millis(50))).unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
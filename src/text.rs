@@ -37,6 +37,10 @@ decl_future! {
     pub type TextFuture<R> = impl Future<Output = io::Result<String>> + SendIf<R>;
 }
 
+/// The charset label to decode a response with if it does not specify one.
+#[derive(Clone, Debug)]
+pub(crate) struct DefaultCharset(pub(crate) String);
+
 /// A streaming text decoder that supports multiple encodings.
 pub(crate) struct Decoder {
     /// Inner decoder implementation.
@@ -67,11 +71,19 @@ impl Decoder {
                 {
                     return Self::new(encoding);
                 } else {
-                    tracing::warn!("unknown encoding '{}', falling back to UTF-8", charset);
+                    tracing::warn!("unknown encoding '{}', falling back to default", charset);
                 }
             }
         }
 
+        if let Some(DefaultCharset(charset)) = response.extensions().get() {
+            if let Some(encoding) = encoding_rs::Encoding::for_label(charset.as_bytes()) {
+                return Self::new(encoding);
+            } else {
+                tracing::warn!("unknown default encoding '{}', falling back to UTF-8", charset);
+            }
+        }
+
         Self::new(encoding_rs::UTF_8)
     }
 
@@ -0,0 +1,234 @@
+//! Adaptive per-host request throttling.
+
+use crate::{
+    body::AsyncBody,
+    config::ThrottlePolicy,
+    error::Error,
+    interceptor::{Context, Interceptor, InterceptorFuture},
+    response::{ResponseExt, RetryAfter},
+    task::sleep,
+};
+use http::{Request, StatusCode};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Interceptor that delays requests to hosts that have recently responded
+/// that they are overloaded, according to a [`ThrottlePolicy`].
+pub(crate) struct ThrottleInterceptor {
+    policy: ThrottlePolicy,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+/// Throttling state for a single host.
+#[derive(Debug)]
+struct HostState {
+    /// The delay between requests as of `updated`.
+    delay: Duration,
+
+    /// When the delay was last increased.
+    updated: Instant,
+
+    /// Do not start any requests before this time.
+    not_before: Instant,
+
+    /// When the most recent request was started.
+    last_request: Instant,
+}
+
+impl HostState {
+    /// Get the delay between requests in effect at the given time, after
+    /// applying any recovery.
+    fn current_delay(&self, policy: &ThrottlePolicy, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated);
+        let periods = if policy.recovery_period > Duration::from_secs(0) {
+            elapsed.as_nanos() / policy.recovery_period.as_nanos()
+        } else {
+            u128::MAX
+        };
+
+        if periods >= 32 {
+            Duration::from_secs(0)
+        } else {
+            self.delay / (1 << periods)
+        }
+    }
+
+    /// Check whether the host has fully recovered and no request is waiting
+    /// for a reserved time slot, in which case the state can be forgotten.
+    fn is_idle(&self, policy: &ThrottlePolicy, now: Instant) -> bool {
+        self.current_delay(policy, now) == Duration::from_secs(0)
+            && self.not_before <= now
+            && self.last_request <= now
+    }
+}
+
+impl ThrottleInterceptor {
+    pub(crate) fn new(policy: ThrottlePolicy) -> Self {
+        Self {
+            policy,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserve a time slot for starting a request to the given host,
+    /// returning how long to wait before starting it.
+    fn reserve(&self, host: &str, now: Instant) -> Option<Duration> {
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts.get_mut(host)?;
+
+        if state.is_idle(&self.policy, now) {
+            hosts.remove(host);
+            return None;
+        }
+
+        let start = (state.last_request + state.current_delay(&self.policy, now))
+            .max(state.not_before)
+            .max(now);
+
+        state.last_request = start;
+
+        Some(start - now).filter(|wait| *wait > Duration::from_secs(0))
+    }
+
+    /// Record that the given host responded that it is overloaded.
+    fn back_off(&self, host: &str, retry_after: Option<Duration>, now: Instant) {
+        let mut hosts = self.hosts.lock().unwrap();
+
+        // Forget other hosts that have recovered, so that the map does not
+        // grow with every host that was ever throttled.
+        let policy = &self.policy;
+        hosts.retain(|_, state| !state.is_idle(policy, now));
+
+        let state = hosts.entry(host.to_owned()).or_insert(HostState {
+            delay: Duration::from_secs(0),
+            updated: now,
+            not_before: now,
+            last_request: now,
+        });
+
+        let delay = (state.current_delay(&self.policy, now) * 2)
+            .max(self.policy.initial_delay)
+            .min(self.policy.max_delay);
+
+        tracing::debug!(host, ?delay, "server is overloaded, throttling requests");
+
+        state.delay = delay;
+        state.updated = now;
+        state.not_before = now + retry_after.unwrap_or(delay).min(self.policy.max_delay);
+    }
+}
+
+impl Interceptor for ThrottleInterceptor {
+    type Err = Error;
+
+    fn intercept<'a>(
+        &'a self,
+        request: Request<AsyncBody>,
+        ctx: Context<'a>,
+    ) -> InterceptorFuture<'a, Self::Err> {
+        Box::pin(async move {
+            let host = match request.uri().authority() {
+                Some(authority) => authority.as_str().to_ascii_lowercase(),
+                None => return ctx.send(request).await,
+            };
+
+            if let Some(wait) = self.reserve(&host, Instant::now()) {
+                tracing::debug!(%host, ?wait, "delaying throttled request");
+                sleep(wait).await?;
+            }
+
+            let response = ctx.send(request).await?;
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS
+                || response.status() == StatusCode::SERVICE_UNAVAILABLE
+            {
+                let retry_after = response.retry_after().map(|retry_after| match retry_after {
+                    RetryAfter::Delay(delay) => delay,
+                    RetryAfter::DateTime(time) => {
+                        time.duration_since(SystemTime::now()).unwrap_or_default()
+                    }
+                });
+
+                self.back_off(&host, retry_after, Instant::now());
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn back_off_doubles_and_recovers() {
+        let interceptor = ThrottleInterceptor::new(
+            ThrottlePolicy::new()
+                .initial_delay(Duration::from_secs(1))
+                .max_delay(Duration::from_secs(3))
+                .recovery_period(Duration::from_secs(10)),
+        );
+        let now = Instant::now();
+
+        assert_eq!(interceptor.reserve("example.org", now), None);
+
+        interceptor.back_off("example.org", None, now);
+        assert_eq!(
+            interceptor.reserve("example.org", now),
+            Some(Duration::from_secs(1))
+        );
+
+        interceptor.back_off("example.org", None, now);
+        interceptor.back_off("example.org", None, now);
+        let hosts = interceptor.hosts.lock().unwrap();
+        let state = &hosts["example.org"];
+        assert_eq!(state.delay, Duration::from_secs(3));
+        assert_eq!(
+            state.current_delay(&interceptor.policy, now + Duration::from_secs(10)),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            state.current_delay(&interceptor.policy, now + Duration::from_secs(1000)),
+            Duration::from_secs(0)
+        );
+    }
+
+    #[test]
+    fn retry_after_is_honored() {
+        let interceptor = ThrottleInterceptor::new(ThrottlePolicy::new());
+        let now = Instant::now();
+
+        interceptor.back_off("example.org", Some(Duration::from_secs(5)), now);
+
+        assert_eq!(
+            interceptor.reserve("example.org", now),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(interceptor.reserve("example.net", now), None);
+    }
+
+    #[test]
+    fn recovered_hosts_are_forgotten() {
+        let interceptor = ThrottleInterceptor::new(
+            ThrottlePolicy::new()
+                .initial_delay(Duration::from_secs(1))
+                .recovery_period(Duration::from_secs(1)),
+        );
+        let now = Instant::now();
+        let later = now + Duration::from_secs(100);
+
+        interceptor.back_off("example.org", None, now);
+        interceptor.back_off("example.net", None, now);
+        assert_eq!(interceptor.hosts.lock().unwrap().len(), 2);
+
+        assert_eq!(interceptor.reserve("example.org", later), None);
+        assert_eq!(interceptor.hosts.lock().unwrap().len(), 1);
+
+        interceptor.back_off("example.com", None, later);
+        assert!(!interceptor.hosts.lock().unwrap().contains_key("example.net"));
+    }
+}
@@ -0,0 +1,16 @@
+//! Abstraction over how requests are actually executed once they have passed
+//! through all interceptors.
+
+use crate::{body::AsyncBody, error::Error, interceptor::InterceptorFuture};
+use http::Request;
+
+/// A transport is responsible for turning a fully prepared request into a
+/// response.
+///
+/// Clients execute requests using curl by default; a transport can be set on
+/// the client to replace that with something else, such as canned responses
+/// for testing.
+pub(crate) trait Transport: Send + Sync {
+    /// Execute a request, returning the response.
+    fn execute(&self, request: Request<AsyncBody>) -> InterceptorFuture<'_, Error>;
+}
@@ -0,0 +1,574 @@
+//! Raw connections opened using the HTTP `CONNECT` method.
+
+use crate::{
+    agent::selector::{is_bad_socket_error, poller_add, poller_modify},
+    error::Error,
+    task::{wake_at, TimerEntry},
+};
+use curl::{
+    easy::{Easy2, Handler},
+    multi::{Easy2Handle, Events, Multi, Socket, SocketEvents},
+};
+use futures_lite::{
+    future::poll_fn,
+    io::{AsyncRead, AsyncWrite},
+};
+use once_cell::sync::OnceCell;
+use polling::Poller;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    io,
+    os::raw::c_void,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+        Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::Instant,
+};
+
+/// Not exposed by the curl crate or curl-sys.
+const CURLINFO_ACTIVESOCKET: curl_sys::CURLINFO = 0x500000 + 44;
+
+/// Handler for connect-only transfers, which never transfer any data using
+/// callbacks.
+pub(crate) struct TunnelHandler;
+
+impl Handler for TunnelHandler {}
+
+decl_future! {
+    /// A future for a tunnel being opened, returned by
+    /// [`HttpClient::connect_async`](crate::HttpClient::connect_async).
+    pub type TunnelFuture = impl Future<Output = Result<Tunnel, Error>> + SendIf<>;
+}
+
+impl fmt::Debug for TunnelFuture<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TunnelFuture").finish()
+    }
+}
+
+/// A raw, bidirectional connection to a remote host, usually tunneled through
+/// an HTTP proxy using the `CONNECT` method.
+///
+/// Created by [`HttpClient::connect_async`](crate::HttpClient::connect_async).
+/// Bytes written to the tunnel are sent to the remote host as-is, and bytes
+/// sent back by the remote host can be read from it. The connection is closed
+/// when the tunnel is dropped.
+pub struct Tunnel {
+    // Must be dropped before the connection, which closes the socket.
+    watcher: Watcher,
+    connection: Connection,
+    socket: Socket,
+}
+
+impl Tunnel {
+    /// Open a tunnel using a curl handle configured for a connect-only
+    /// transfer.
+    ///
+    /// The transfer is driven by a multi handle of its own while the returned
+    /// future is polled, so no thread is blocked while connecting.
+    pub(crate) async fn open(easy: Easy2<TunnelHandler>) -> Result<Self, Error> {
+        let updates = Arc::new(Mutex::new(SocketUpdates::default()));
+        let mut multi = Multi::new();
+
+        multi
+            .socket_function({
+                let updates = updates.clone();
+
+                move |socket, events, _| updates.lock().unwrap().update(socket, events)
+            })
+            .map_err(Error::from_any)?;
+
+        multi
+            .timer_function({
+                let updates = updates.clone();
+
+                move |timeout| {
+                    updates.lock().unwrap().deadline = timeout.map(|t| Instant::now() + t);
+                    true
+                }
+            })
+            .map_err(Error::from_any)?;
+
+        let handle = multi.add2(easy).map_err(Error::from_any)?;
+        let connection = Connection { handle, multi };
+        let mut watcher = Watcher::new();
+        let mut timer = None;
+
+        let result =
+            poll_fn(|cx| connection.poll_connect(cx, &updates, &mut watcher, &mut timer)).await?;
+
+        if let Err(e) = result {
+            let Connection { handle, multi } = connection;
+            let easy = multi.remove2(handle).map_err(Error::from_any)?;
+
+            return Err(Error::from_transfer(e, &easy));
+        }
+
+        let mut socket = curl_sys::CURL_SOCKET_BAD;
+
+        #[allow(unsafe_code)]
+        unsafe {
+            match curl_sys::curl_easy_getinfo(
+                connection.handle.raw(),
+                CURLINFO_ACTIVESOCKET,
+                &mut socket,
+            ) {
+                curl_sys::CURLE_OK => {}
+                code => return Err(Error::from_any(curl::Error::new(code))),
+            }
+        }
+
+        if socket == curl_sys::CURL_SOCKET_BAD {
+            return Err(Error::from_any(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "tunnel connection was closed",
+            )));
+        }
+
+        Ok(Self {
+            watcher,
+            connection,
+            socket,
+        })
+    }
+}
+
+impl AsyncRead for Tunnel {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.connection.recv(buf) {
+            Ok(len) => Poll::Ready(Ok(len)),
+            Err(e) if e.is_again() => {
+                let socket = self.socket;
+                self.watcher.watch(socket, true, false, cx.waker())?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(Error::from_any(e).into())),
+        }
+    }
+}
+
+impl AsyncWrite for Tunnel {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.connection.send(buf) {
+            Ok(len) => Poll::Ready(Ok(len)),
+            Err(e) if e.is_again() => {
+                let socket = self.socket;
+                self.watcher.watch(socket, false, true, cx.waker())?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(Error::from_any(e).into())),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Writes are sent to the socket immediately.
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Curl does not support shutting down one half of the connection, so
+        // the connection is only closed when the tunnel is dropped.
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl fmt::Debug for Tunnel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tunnel")
+            .field("socket", &self.socket)
+            .finish()
+    }
+}
+
+/// A connect-only transfer, along with the multi handle used to drive it.
+///
+/// The transfer has to stay in the multi handle for as long as the connection
+/// is used, since curl closes connect-only connections when their transfer is
+/// removed from a multi handle.
+struct Connection {
+    // Must be dropped before the multi handle, which owns the callbacks that
+    // curl might call while removing the transfer.
+    handle: Easy2Handle<TunnelHandler>,
+    multi: Multi,
+}
+
+// The multi handle is not shared with any other transfer, and both handles are
+// only ever used by whoever owns the connection.
+#[allow(unsafe_code)]
+unsafe impl Send for Connection {}
+
+impl Connection {
+    /// Drive the transfer until the connection is established, returning the
+    /// result of the transfer once it is done.
+    fn poll_connect(
+        &self,
+        cx: &mut Context<'_>,
+        updates: &Mutex<SocketUpdates>,
+        watcher: &mut Watcher,
+        timer: &mut Option<TimerEntry>,
+    ) -> Poll<Result<Result<(), curl::Error>, Error>> {
+        // Let curl check on every socket it is waiting on, since the reactor
+        // only tells us that one of them is ready.
+        let sockets = updates
+            .lock()
+            .unwrap()
+            .sockets
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+
+        for socket in sockets {
+            self.multi
+                .action(socket, &Events::new())
+                .map_err(Error::from_any)?;
+        }
+
+        let deadline = updates.lock().unwrap().deadline;
+
+        if matches!(deadline, Some(deadline) if deadline <= Instant::now()) {
+            self.multi.timeout().map_err(Error::from_any)?;
+        }
+
+        let mut result = None;
+
+        self.multi.messages(|message| {
+            if let Some(r) = message.result_for2(&self.handle) {
+                result = Some(r);
+            }
+        });
+
+        let mut updates = updates.lock().unwrap();
+
+        for socket in updates.removed.drain(..) {
+            watcher.forget(socket);
+        }
+
+        if let Some(result) = result {
+            // The tunnel waits on its socket only when it needs to.
+            watcher.clear();
+            *timer = None;
+
+            return Poll::Ready(Ok(result));
+        }
+
+        for (&socket, &(readable, writable)) in &updates.sockets {
+            watcher.watch(socket, readable, writable, cx.waker())?;
+        }
+
+        // Curl also needs to be called after a timeout, to try another address
+        // or to give up on connecting.
+        *timer = match updates.deadline {
+            Some(deadline) if deadline <= Instant::now() => {
+                cx.waker().wake_by_ref();
+                None
+            }
+            Some(deadline) => Some(wake_at(deadline, cx.waker().clone())?),
+            None => None,
+        };
+
+        Poll::Pending
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> Result<usize, curl::Error> {
+        let mut len = 0;
+
+        #[allow(unsafe_code)]
+        let code = unsafe {
+            curl_sys::curl_easy_recv(
+                self.handle.raw(),
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len(),
+                &mut len,
+            )
+        };
+
+        match code {
+            curl_sys::CURLE_OK => Ok(len),
+            code => Err(curl::Error::new(code)),
+        }
+    }
+
+    fn send(&self, buf: &[u8]) -> Result<usize, curl::Error> {
+        let mut len = 0;
+
+        #[allow(unsafe_code)]
+        let code = unsafe {
+            curl_sys::curl_easy_send(
+                self.handle.raw(),
+                buf.as_ptr() as *const c_void,
+                buf.len(),
+                &mut len,
+            )
+        };
+
+        match code {
+            curl_sys::CURLE_OK => Ok(len),
+            code => Err(curl::Error::new(code)),
+        }
+    }
+}
+
+/// Sockets that curl wants to be watched while connecting, as reported by its
+/// socket callback.
+#[derive(Default)]
+struct SocketUpdates {
+    /// Sockets to watch, and whether to watch them for reading and writing.
+    sockets: HashMap<Socket, (bool, bool)>,
+
+    /// Sockets that curl is no longer interested in.
+    removed: Vec<Socket>,
+
+    /// When curl wants to be called again if no socket becomes ready.
+    deadline: Option<Instant>,
+}
+
+impl SocketUpdates {
+    fn update(&mut self, socket: Socket, events: SocketEvents) {
+        if events.remove() {
+            self.sockets.remove(&socket);
+            self.removed.push(socket);
+        } else {
+            let readable = events.input() || events.input_and_output();
+            let writable = events.output() || events.input_and_output();
+
+            self.sockets.insert(socket, (readable, writable));
+        }
+    }
+}
+
+/// Incrementing counter used to tell the owners of sockets apart.
+static NEXT_WATCHER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A set of sockets watched by the shared reactor thread on behalf of a single
+/// tunnel. The sockets stop being watched when this is dropped.
+struct Watcher {
+    id: usize,
+    sockets: HashSet<Socket>,
+}
+
+impl Watcher {
+    fn new() -> Self {
+        Self {
+            id: NEXT_WATCHER_ID.fetch_add(1, Ordering::Relaxed),
+            sockets: HashSet::new(),
+        }
+    }
+
+    /// Wake the given task once the socket is ready for reading or writing,
+    /// as requested.
+    fn watch(
+        &mut self,
+        socket: Socket,
+        readable: bool,
+        writable: bool,
+        waker: &Waker,
+    ) -> io::Result<()> {
+        self.sockets.insert(socket);
+        Reactor::get()?.watch(self.id, socket, readable, writable, waker)
+    }
+
+    /// Stop watching a socket.
+    fn forget(&mut self, socket: Socket) {
+        if self.sockets.remove(&socket) {
+            if let Some(reactor) = REACTOR.get() {
+                reactor.forget(self.id, socket);
+            }
+        }
+    }
+
+    /// Stop watching all sockets.
+    fn clear(&mut self) {
+        if let Some(reactor) = REACTOR.get() {
+            for socket in self.sockets.drain() {
+                reactor.forget(self.id, socket);
+            }
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A single background thread shared by all tunnels that wakes up tasks when
+/// the sockets they are waiting on are ready, started the first time it is
+/// needed.
+static REACTOR: OnceCell<Arc<Reactor>> = OnceCell::new();
+
+struct Reactor {
+    poller: Poller,
+
+    /// Tasks waiting on each watched socket.
+    sockets: Mutex<HashMap<Socket, Interest>>,
+}
+
+/// Tasks waiting for a socket to become ready.
+struct Interest {
+    /// ID of the watcher that the socket belongs to.
+    watcher: usize,
+    read: Option<Waker>,
+    write: Option<Waker>,
+}
+
+impl Reactor {
+    /// Get the shared reactor, starting it if necessary.
+    fn get() -> io::Result<&'static Self> {
+        REACTOR
+            .get_or_try_init(|| {
+                let reactor = Arc::new(Self {
+                    poller: Poller::new()?,
+                    sockets: Mutex::default(),
+                });
+
+                thread::Builder::new()
+                    .name(String::from("isahc-tunnel"))
+                    .spawn({
+                        let reactor = reactor.clone();
+                        move || reactor.run()
+                    })?;
+
+                Ok(reactor)
+            })
+            .map(|reactor| &**reactor)
+    }
+
+    fn watch(
+        &self,
+        watcher: usize,
+        socket: Socket,
+        readable: bool,
+        writable: bool,
+        waker: &Waker,
+    ) -> io::Result<()> {
+        let mut sockets = self.sockets.lock().unwrap();
+        let is_new = !sockets.contains_key(&socket);
+        let interest = sockets.entry(socket).or_insert_with(|| Interest {
+            watcher,
+            read: None,
+            write: None,
+        });
+
+        // The socket might re-use the number of a closed socket that its
+        // previous owner has not forgotten yet.
+        if interest.watcher != watcher {
+            *interest = Interest {
+                watcher,
+                read: None,
+                write: None,
+            };
+        }
+
+        if readable {
+            interest.read = Some(waker.clone());
+        }
+
+        if writable {
+            interest.write = Some(waker.clone());
+        }
+
+        let readable = interest.read.is_some();
+        let writable = interest.write.is_some();
+
+        let result = if is_new {
+            poller_add(&self.poller, socket, readable, writable)
+        } else {
+            poller_modify(&self.poller, socket, readable, writable)
+        };
+
+        match result {
+            // Curl sometimes hands out sockets that are not valid yet while
+            // connecting, and calls us again after a timeout anyway.
+            Err(e) if is_bad_socket_error(&e) => {
+                tracing::debug!(socket, error = ?e, "bad tunnel socket, not watching it");
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    fn forget(&self, watcher: usize, socket: Socket) {
+        let mut sockets = self.sockets.lock().unwrap();
+
+        if sockets.get(&socket).map(|interest| interest.watcher) != Some(watcher) {
+            return;
+        }
+
+        sockets.remove(&socket);
+
+        // The socket might already have been closed.
+        if let Err(e) = self.poller.delete(socket) {
+            if !is_bad_socket_error(&e) && e.kind() != io::ErrorKind::PermissionDenied {
+                tracing::debug!("failed to stop watching tunnel socket: {}", e);
+            }
+        }
+    }
+
+    fn run(&self) {
+        let mut events = Vec::new();
+
+        loop {
+            events.clear();
+
+            if let Err(e) = self.poller.wait(&mut events, None) {
+                if e.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+
+                tracing::error!("tunnel poller failed: {}", e);
+                break;
+            }
+
+            let mut ready = Vec::new();
+            let mut sockets = self.sockets.lock().unwrap();
+
+            for event in &events {
+                let socket = event.key as Socket;
+
+                if let Some(interest) = sockets.get_mut(&socket) {
+                    if event.readable {
+                        ready.extend(interest.read.take());
+                    }
+
+                    if event.writable {
+                        ready.extend(interest.write.take());
+                    }
+
+                    // Events are oneshot, so keep waiting for the other
+                    // direction if a task is still interested in it.
+                    if interest.read.is_some() || interest.write.is_some() {
+                        let readable = interest.read.is_some();
+                        let writable = interest.write.is_some();
+
+                        if let Err(e) = poller_modify(&self.poller, socket, readable, writable) {
+                            tracing::debug!("failed to update tunnel poller: {}", e);
+                        }
+                    }
+                }
+            }
+
+            drop(sockets);
+
+            // Wake up the tasks, which watch their sockets again if they still
+            // need to wait.
+            for waker in ready {
+                waker.wake();
+            }
+        }
+    }
+}
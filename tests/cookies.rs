@@ -1,6 +1,6 @@
 #![cfg(feature = "cookies")]
 
-use isahc::{cookies::CookieJar, prelude::*, HttpClient};
+use isahc::{cookies::CookieJar, prelude::*, HttpClient, Request};
 use testserver::mock;
 
 #[test]
@@ -29,3 +29,48 @@ fn cookie_lifecycle() {
 
     dbg!(m2.request()).expect_header("cookie", "baz=123; foo=bar");
 }
+
+#[test]
+fn request_cookie_jar_overrides_client_jar() {
+    let client_jar = CookieJar::default();
+    let client = HttpClient::builder()
+        .cookie_jar(client_jar.clone())
+        .build()
+        .unwrap();
+
+    let jar1 = CookieJar::default();
+    let jar2 = CookieJar::default();
+
+    let m1 = mock! {
+        headers {
+            "set-cookie": "session=one",
+        }
+    };
+    let m2 = mock! {
+        headers {
+            "set-cookie": "session=two",
+        }
+    };
+
+    let send = |uri: String, jar: &CookieJar| {
+        client
+            .send(Request::get(uri).cookie_jar(jar.clone()).body(()).unwrap())
+            .unwrap()
+    };
+
+    send(m1.url(), &jar1);
+    send(m2.url(), &jar2);
+
+    // Each request stored its cookie in its own jar only.
+    let uri = m1.url().parse().unwrap();
+    assert_eq!(client_jar.get_for_uri(&uri).into_iter().count(), 0);
+
+    let m3 = mock!();
+    let m4 = mock!();
+
+    send(m3.url(), &jar1);
+    send(m4.url(), &jar2);
+
+    m3.request().expect_header("cookie", "session=one");
+    m4.request().expect_header("cookie", "session=two");
+}
@@ -0,0 +1,139 @@
+use isahc::{config::DnsCache, prelude::*, HttpClient, Request};
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+use testserver::mock;
+
+/// Collects log output so that curl's debug info can be inspected.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl LogBuffer {
+    fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn dns_cache_can_be_disabled_per_request() {
+    let logs = LogBuffer::default();
+
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer({
+            let logs = logs.clone();
+            move || logs.clone()
+        })
+        .init();
+
+    let m = mock!();
+    let url = format!("http://localhost:{}/", m.addr().port());
+
+    // Close connections after each request so that each request needs to
+    // resolve the host again.
+    let client = HttpClient::builder()
+        .connection_cache_size(0)
+        .build()
+        .unwrap();
+
+    client.get(&url).unwrap();
+    logs.take();
+
+    client.get(&url).unwrap();
+    assert!(logs.take().contains("found in DNS cache"));
+
+    client
+        .send(
+            Request::get(&url)
+                .dns_cache(DnsCache::Disable)
+                .body(())
+                .unwrap(),
+        )
+        .unwrap();
+    assert!(!logs.take().contains("found in DNS cache"));
+
+    client.get(&url).unwrap();
+    client.get(&url).unwrap();
+    assert!(logs.take().contains("found in DNS cache"));
+
+    assert_eq!(m.requests().len(), 5);
+}
+
+// Only Linux routes the whole 127.0.0.0/8 block to the loopback interface by
+// default.
+#[cfg(target_os = "linux")]
+#[test]
+fn dns_shuffle_addresses_spreads_connections_across_addresses() {
+    use isahc::config::ResolveMap;
+    use std::{
+        io::Read,
+        net::{Ipv4Addr, TcpListener},
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    /// Spawn a server that answers every request with an empty response,
+    /// counting the connections it accepts.
+    fn spawn_counting_server(listener: TcpListener) -> Arc<AtomicUsize> {
+        let count = Arc::new(AtomicUsize::new(0));
+
+        thread::spawn({
+            let count = count.clone();
+            move || {
+                for mut stream in listener.incoming().flatten() {
+                    count.fetch_add(1, Ordering::SeqCst);
+
+                    let mut buf = [0; 1024];
+                    let _ = stream.read(&mut buf);
+                    let _ = stream.write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    );
+                }
+            }
+        });
+
+        count
+    }
+
+    let first = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = first.local_addr().unwrap().port();
+    let second = TcpListener::bind(("127.0.0.2", port)).unwrap();
+
+    let first_count = spawn_counting_server(first);
+    let second_count = spawn_counting_server(second);
+
+    let client = HttpClient::builder()
+        .dns_resolve(
+            ResolveMap::new()
+                .add("shuffle.test", port, Ipv4Addr::new(127, 0, 0, 1))
+                .add("shuffle.test", port, Ipv4Addr::new(127, 0, 0, 2)),
+        )
+        .dns_shuffle_addresses(true)
+        .connection_cache_size(0)
+        .build()
+        .unwrap();
+
+    for _ in 0..20 {
+        client
+            .get(format!("http://shuffle.test:{}/", port))
+            .unwrap();
+    }
+
+    assert_eq!(
+        first_count.load(Ordering::SeqCst) + second_count.load(Ordering::SeqCst),
+        20
+    );
+    assert!(first_count.load(Ordering::SeqCst) > 0);
+    assert!(second_count.load(Ordering::SeqCst) > 0);
+}
@@ -1,5 +1,5 @@
 use flate2::{
-    read::{DeflateEncoder, GzEncoder},
+    read::{DeflateEncoder, GzDecoder, GzEncoder},
     Compression,
 };
 use isahc::{prelude::*, Request};
@@ -33,6 +33,40 @@ fn gzip_encoded_response_is_decoded_automatically() {
     assert_eq!(response.body().len(), None);
 }
 
+#[test]
+fn decompressed_response_reports_original_encoding() {
+    let mut body_encoded = Vec::new();
+
+    GzEncoder::new("hello world".as_bytes(), Compression::default())
+        .read_to_end(&mut body_encoded)
+        .unwrap();
+
+    let m = mock! {
+        headers {
+            "Content-Encoding": "gzip",
+        }
+        body: body_encoded.clone(),
+    };
+
+    let mut response = isahc::get(m.url()).unwrap();
+
+    assert!(response.was_decompressed());
+    assert_eq!(response.original_content_encoding(), Some("gzip"));
+    assert_eq!(response.text().unwrap(), "hello world");
+}
+
+#[test]
+fn uncompressed_response_is_not_reported_as_decompressed() {
+    let m = mock! {
+        body: "hello world",
+    };
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert!(!response.was_decompressed());
+    assert_eq!(response.original_content_encoding(), None);
+}
+
 #[test]
 fn request_gzip_without_automatic_decompression() {
     let body = "hello world";
@@ -64,11 +98,47 @@ fn request_gzip_without_automatic_decompression() {
 
     assert_eq!(body_received, body_encoded);
     m.request().expect_header("Accept-Encoding", "gzip");
+    assert!(!response.was_decompressed());
 
     // Response body size should be known.
     assert_eq!(response.body().len(), Some(31));
 }
 
+#[test]
+fn accept_encoding_identity_requests_and_returns_raw_body() {
+    let mut body_encoded = Vec::new();
+
+    GzEncoder::new("hello world".as_bytes(), Compression::default())
+        .read_to_end(&mut body_encoded)
+        .unwrap();
+
+    // Serve a compressed body anyway, as a server is free to do.
+    let m = mock! {
+        headers {
+            "Content-Encoding": "gzip",
+        }
+        body: body_encoded.clone(),
+    };
+
+    let mut response = Request::get(m.url())
+        .accept_encoding_identity(true)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+    let mut body_received = Vec::new();
+    response.body_mut().read_to_end(&mut body_received).unwrap();
+
+    let mut body_decoded = String::new();
+    GzDecoder::new(body_received.as_slice())
+        .read_to_string(&mut body_decoded)
+        .unwrap();
+
+    m.request().expect_header("Accept-Encoding", "identity");
+    assert_eq!(body_decoded, "hello world");
+    assert!(!response.was_decompressed());
+}
+
 #[test]
 fn deflate_encoded_response_is_decoded_automatically() {
     let body = "hello world";
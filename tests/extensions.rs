@@ -0,0 +1,97 @@
+use isahc::{prelude::*, HttpClient, Request};
+use testserver::mock;
+
+#[derive(Clone, Debug, PartialEq)]
+struct RequestId(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Other(&'static str);
+
+#[test]
+fn propagated_request_extension_is_available_on_response() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .propagate_extension::<RequestId>()
+        .extension(RequestId(42))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(
+        response.extensions().get::<RequestId>(),
+        Some(&RequestId(42))
+    );
+}
+
+#[test]
+fn propagated_extension_configured_on_client() {
+    let m = mock!();
+
+    let client = HttpClient::builder()
+        .propagate_extension::<RequestId>()
+        .build()
+        .unwrap();
+
+    let response = client
+        .send(
+            Request::get(m.url())
+                .extension(RequestId(7))
+                .body(())
+                .unwrap(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        response.extensions().get::<RequestId>(),
+        Some(&RequestId(7))
+    );
+}
+
+#[test]
+fn propagated_extensions_configured_on_client_and_request_are_combined() {
+    let m = mock!();
+
+    let client = HttpClient::builder()
+        .propagate_extension::<RequestId>()
+        .build()
+        .unwrap();
+
+    let response = client
+        .send(
+            Request::get(m.url())
+                .propagate_extension::<Other>()
+                .extension(RequestId(7))
+                .extension(Other("hello"))
+                .body(())
+                .unwrap(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        response.extensions().get::<RequestId>(),
+        Some(&RequestId(7))
+    );
+    assert_eq!(response.extensions().get::<Other>(), Some(&Other("hello")));
+}
+
+#[test]
+fn unregistered_request_extensions_are_not_propagated() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .propagate_extension::<RequestId>()
+        .extension(RequestId(1))
+        .extension(Other("hello"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(
+        response.extensions().get::<RequestId>(),
+        Some(&RequestId(1))
+    );
+    assert_eq!(response.extensions().get::<Other>(), None);
+}
@@ -0,0 +1,178 @@
+use futures_lite::future::block_on;
+use isahc::{config::RedirectPolicy, error::ErrorKind, prelude::*, HttpClient, Request};
+use std::{fs, path::Path};
+use testserver::mock;
+
+#[macro_use]
+mod utils;
+
+fn client() -> HttpClient {
+    HttpClient::builder().allow_file_uris(true).build().unwrap()
+}
+
+fn file_uri(path: &Path) -> String {
+    let path = path
+        .to_str()
+        .unwrap()
+        .replace('\\', "/")
+        .replace(' ', "%20");
+
+    if path.starts_with('/') {
+        format!("file://localhost{}", path)
+    } else {
+        format!("file://localhost/{}", path)
+    }
+}
+
+#[test]
+fn file_uri_returns_file_contents() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("hello world.txt");
+    fs::write(&path, "hello from disk").unwrap();
+
+    let mut response = client().get(file_uri(&path)).unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.content_length(), Some(15));
+    assert!(response.headers().contains_key("last-modified"));
+    assert_eq!(response.text().unwrap(), "hello from disk");
+}
+
+#[test]
+fn file_uri_can_be_read_asynchronously() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("large.bin");
+    let contents = vec![42; 1024 * 1024];
+    fs::write(&path, &contents).unwrap();
+
+    block_on(async {
+        let mut response = client().get_async(file_uri(&path)).await.unwrap();
+
+        assert_eq!(response.status(), 200);
+        let mut buf = Vec::new();
+        response.copy_to(&mut buf).await.unwrap();
+
+        assert_eq!(buf, contents);
+    });
+}
+
+#[test]
+fn head_request_for_file_uri_has_no_body() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file.txt");
+    fs::write(&path, "hello").unwrap();
+
+    let mut response = client().head(file_uri(&path)).unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.content_length(), Some(5));
+    assert_eq!(response.text().unwrap(), "");
+}
+
+#[test]
+fn missing_file_returns_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let response = client()
+        .get(file_uri(&dir.path().join("missing.txt")))
+        .unwrap();
+
+    assert_eq!(response.status(), 404);
+}
+
+#[test]
+fn directory_returns_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let response = client().get(file_uri(dir.path())).unwrap();
+
+    assert_eq!(response.status(), 404);
+}
+
+#[test]
+fn file_uri_only_supports_get_and_head() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file.txt");
+    fs::write(&path, "hello").unwrap();
+
+    let response = client().put(file_uri(&path), "new contents").unwrap();
+
+    assert_eq!(response.status(), 405);
+    assert_eq!(response.headers()["allow"], "GET, HEAD");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "hello");
+}
+
+#[test]
+fn file_uri_for_remote_host_is_rejected() {
+    let result = client().get("file://example.org/etc/hosts");
+
+    assert_matches!(result, Err(e) if e == ErrorKind::InvalidRequest);
+}
+
+#[test]
+fn file_uris_are_rejected_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file.txt");
+    fs::write(&path, "secret").unwrap();
+
+    let result = isahc::get(file_uri(&path));
+
+    assert_matches!(result, Err(e) if e == ErrorKind::InvalidRequest);
+}
+
+#[test]
+fn file_uris_can_be_allowed_per_request() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file.txt");
+    fs::write(&path, "hello").unwrap();
+
+    let mut response = Request::get(file_uri(&path))
+        .allow_file_uris(true)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.text().unwrap(), "hello");
+}
+
+#[test]
+fn redirect_to_file_uri_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file.txt");
+    fs::write(&path, "secret").unwrap();
+
+    let m = mock! {
+        status: 302,
+        headers {
+            "Location": file_uri(&path),
+        }
+    };
+
+    let result = Request::get(m.url())
+        .redirect_policy(RedirectPolicy::Follow)
+        .allow_file_uris(true)
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::RedirectRejected);
+}
+
+#[test]
+fn redirect_to_other_schemes_is_refused() {
+    let m = mock! {
+        status: 302,
+        headers {
+            "Location": "ftp://127.0.0.1/file.txt",
+        }
+    };
+
+    let result = Request::get(m.url())
+        .redirect_policy(RedirectPolicy::Follow)
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::RedirectRejected);
+}
@@ -1,10 +1,10 @@
 use futures_lite::future::block_on;
-use isahc::{prelude::*, HttpClient, Request};
+use isahc::{error::ErrorKind, prelude::*, HttpClient, Request, RetryAfter};
 use std::{
     io::{self, Write},
     net::{Shutdown, TcpListener, TcpStream},
     thread,
-    time::Duration,
+    time::{Duration, UNIX_EPOCH},
 };
 use testserver::mock;
 
@@ -193,6 +193,77 @@ fn headers_in_request_builder_must_override_multiple_headers_in_httpclient_build
     m.request().expect_header("X-header", "some-value3");
 }
 
+#[test]
+fn default_headers_can_be_extended_from_an_iterator() {
+    let m = mock!();
+
+    let client = HttpClient::builder()
+        .default_header("X-header", "some-value1")
+        .extend_default_headers(vec![
+            ("X-header", "some-value2"),
+            ("X-other", "other-value"),
+            ("X-third", "third-value"),
+        ])
+        .build()
+        .unwrap();
+
+    client.get(m.url()).unwrap();
+
+    m.request().expect_header("X-header", "some-value1");
+    m.request().expect_header("X-header", "some-value2");
+    m.request().expect_header("X-other", "other-value");
+    m.request().expect_header("X-third", "third-value");
+}
+
+#[test]
+fn extending_default_headers_with_invalid_header_fails_build() {
+    let result = HttpClient::builder()
+        .extend_default_headers(vec![("X-header", "value"), ("bad header", "value")])
+        .build();
+
+    assert_eq!(result.unwrap_err(), ErrorKind::ClientInitialization);
+}
+
+#[test]
+fn raw_status_line_is_preserved() {
+    let m = mock!();
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert_eq!(response.raw_status_line(), Some("HTTP/1.1 200 OK"));
+}
+
+#[test]
+fn nonstandard_status_line_is_accepted_with_alias() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        let mut stream = listener.accept().unwrap().0;
+
+        consume_request_in_background(&stream);
+
+        stream
+            .write_all(b"ICY 200 OK\r\nicy-name: test radio\r\n\r\nhello")
+            .unwrap();
+
+        let _ = stream.shutdown(Shutdown::Write);
+    });
+
+    let mut response = Request::get(url)
+        .http_200_aliases(vec!["ICY"])
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.raw_status_line(), Some("ICY 200 OK"));
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.version(), isahc::http::Version::HTTP_10);
+    assert_eq!(response.headers()["icy-name"], "test radio");
+    assert_eq!(response.text().unwrap(), "hello");
+}
+
 #[test]
 fn trailer_headers() {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
@@ -332,6 +403,240 @@ fn trailer_headers_timeout() {
     );
 }
 
+#[test]
+fn informational_response_headers_are_returned() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        let mut stream = listener.accept().unwrap().0;
+
+        consume_request_in_background(&stream);
+
+        stream
+            .write_all(
+                b"\
+            HTTP/1.1 103 Early Hints\r\n\
+            link: </style.css>; rel=preload; as=style\r\n\
+            \r\n\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 2\r\n\
+            \r\n\
+            OK\
+        ",
+            )
+            .unwrap();
+
+        let _ = stream.shutdown(Shutdown::Write);
+    });
+
+    let mut response = isahc::get(url).unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().unwrap(), "OK");
+    assert!(!response.headers().contains_key("link"));
+
+    let informational = response.informational_headers();
+
+    assert_eq!(informational.len(), 1);
+    assert_eq!(informational[0].0, 103);
+    assert_eq!(
+        informational[0].1["link"],
+        "</style.css>; rel=preload; as=style"
+    );
+}
+
+#[test]
+fn informational_headers_are_empty_without_informational_responses() {
+    let m = mock!();
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert!(response.informational_headers().is_empty());
+}
+
+#[test]
+fn retry_after_delay_seconds() {
+    let m = mock! {
+        status: 429,
+        headers {
+            "Retry-After": "120",
+        }
+    };
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert_eq!(
+        response.retry_after(),
+        Some(RetryAfter::Delay(Duration::from_secs(120)))
+    );
+}
+
+#[test]
+fn retry_after_http_date() {
+    let m = mock! {
+        status: 503,
+        headers {
+            "Retry-After": "Wed, 21 Oct 2015 07:28:00 GMT",
+        }
+    };
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert_eq!(
+        response.retry_after(),
+        Some(RetryAfter::DateTime(
+            UNIX_EPOCH + Duration::from_secs(1_445_412_480)
+        ))
+    );
+}
+
+#[test]
+fn retry_after_malformed() {
+    let m = mock! {
+        status: 503,
+        headers {
+            "Retry-After": "soon",
+        }
+    };
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert_eq!(response.retry_after(), None);
+}
+
+#[test]
+fn if_none_match_quotes_entity_tags() {
+    let m = mock!();
+
+    for (etag, expected) in &[
+        ("abc", r#""abc""#),
+        (r#""abc""#, r#""abc""#),
+        (r#"W/"abc""#, r#"W/"abc""#),
+        ("*", "*"),
+    ] {
+        Request::get(m.url())
+            .if_none_match(etag)
+            .body(())
+            .unwrap()
+            .send()
+            .unwrap();
+
+        m.requests()
+            .last()
+            .unwrap()
+            .expect_header("if-none-match", *expected);
+    }
+}
+
+#[test]
+fn if_modified_since_uses_http_date() {
+    let m = mock!();
+
+    Request::get(m.url())
+        .if_modified_since(UNIX_EPOCH + Duration::from_secs(1_445_412_480))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    m.request()
+        .expect_header("if-modified-since", "Wed, 21 Oct 2015 07:28:00 GMT");
+}
+
+#[test]
+fn conditional_request_not_modified() {
+    let m = mock! {
+        status: 304,
+        headers {
+            "ETag": r#""abc""#,
+        }
+    };
+
+    let response = Request::get(m.url())
+        .if_none_match("abc")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert!(response.is_not_modified());
+    m.request().expect_header("if-none-match", r#""abc""#);
+}
+
+/// Spawn a server that responds with the given number of headers.
+fn spawn_server_with_many_headers(count: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        let mut stream = listener.accept().unwrap().0;
+
+        consume_request_in_background(&stream);
+
+        let mut response = String::from("HTTP/1.1 200 OK\r\ncontent-length: 2\r\n");
+
+        for i in 0..count {
+            response.push_str(&format!("x-header-{}: some value\r\n", i));
+        }
+
+        response.push_str("\r\nOK");
+
+        let _ = stream.write_all(response.as_bytes());
+        let _ = stream.shutdown(Shutdown::Write);
+    });
+
+    url
+}
+
+#[test]
+fn response_exceeding_max_header_size_is_aborted() {
+    let url = spawn_server_with_many_headers(5000);
+
+    let result = Request::get(url)
+        .max_response_header_size(16 * 1024)
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_eq!(
+        result.unwrap_err().kind(),
+        &ErrorKind::ResponseHeadersTooLarge
+    );
+}
+
+#[test]
+fn response_exceeding_max_header_count_is_aborted() {
+    let url = spawn_server_with_many_headers(5000);
+
+    let result = Request::get(url)
+        .max_response_headers(100)
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_eq!(
+        result.unwrap_err().kind(),
+        &ErrorKind::ResponseHeadersTooLarge
+    );
+}
+
+#[test]
+fn response_within_header_limits_is_returned() {
+    let url = spawn_server_with_many_headers(100);
+
+    let mut response = Request::get(url)
+        .max_response_header_size(16 * 1024)
+        .max_response_headers(101)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.headers().len(), 101);
+    assert_eq!(response.text().unwrap(), "OK");
+}
+
 fn consume_request_in_background(stream: &TcpStream) {
     let mut stream = stream.try_clone().unwrap();
 
@@ -340,3 +645,59 @@ fn consume_request_in_background(stream: &TcpStream) {
         let _ = stream.shutdown(Shutdown::Read);
     });
 }
+
+#[test]
+fn header_all_returns_every_value_in_order() {
+    let m = mock! {
+        headers {
+            "Via": "1.1 first",
+            "Via": "1.1 second",
+            "Via": "1.1 third",
+        }
+    };
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert_eq!(
+        response.header_all("VIA"),
+        vec!["1.1 first", "1.1 second", "1.1 third"]
+    );
+    assert_eq!(response.header_str("via"), Some("1.1 first"));
+    assert!(response.header_all("x-missing").is_empty());
+    assert_eq!(response.header_str("x-missing"), None);
+}
+
+#[test]
+fn header_str_returns_none_for_non_ascii_value() {
+    let response = isahc::http::Response::builder()
+        .header("x-name", &b"caf\xe9"[..])
+        .body(())
+        .unwrap();
+
+    assert_eq!(response.header_str("X-Name"), None);
+    assert_eq!(response.header_all("x-name"), vec![&b"caf\xe9"[..]]);
+}
+
+#[test]
+fn minimal_headers_sends_only_explicit_headers() {
+    let m = mock!();
+
+    Request::get(m.url())
+        .minimal_headers(true)
+        .header("x-signature", "abc")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let mut names = m
+        .request()
+        .headers
+        .iter()
+        .map(|(name, _)| name.to_lowercase())
+        .collect::<Vec<_>>();
+    names.sort();
+
+    assert_eq!(names, vec!["host", "x-signature"]);
+    m.request().expect_header("x-signature", "abc");
+}
@@ -1,6 +1,13 @@
 #![cfg(feature = "unstable-interceptors")]
 
-use isahc::HttpClient;
+use isahc::{
+    error::ErrorKind,
+    interceptor::{Context, Interceptor, InterceptorFuture},
+    AsyncBody,
+    HttpClient,
+    Request,
+};
+use std::{error::Error, fmt};
 use testserver::mock;
 
 #[test]
@@ -19,3 +26,45 @@ fn change_http_method_with_interceptor() {
 
     assert_eq!(m.request().method, "HEAD");
 }
+
+#[test]
+fn custom_interceptor_error_is_preserved() {
+    #[derive(Debug)]
+    struct Forbidden;
+
+    impl fmt::Display for Forbidden {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("request forbidden by policy")
+        }
+    }
+
+    impl std::error::Error for Forbidden {}
+
+    struct DenyAll;
+
+    impl Interceptor for DenyAll {
+        type Err = Forbidden;
+
+        fn intercept<'a>(
+            &'a self,
+            _request: Request<AsyncBody>,
+            _ctx: Context<'a>,
+        ) -> InterceptorFuture<'a, Self::Err> {
+            Box::pin(async move { Err(Forbidden) })
+        }
+    }
+
+    let m = mock!();
+
+    let client = HttpClient::builder().interceptor(DenyAll).build().unwrap();
+
+    let error = client.get(m.url()).unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::Interceptor);
+    assert!(error
+        .source()
+        .unwrap()
+        .downcast_ref::<Forbidden>()
+        .is_some());
+    assert!(m.requests().is_empty());
+}
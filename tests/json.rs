@@ -2,6 +2,7 @@
 
 use futures_lite::{future::block_on, io::AsyncRead};
 use isahc::prelude::*;
+use serde::Deserialize;
 use serde_json::Value;
 use std::{
     io,
@@ -43,6 +44,56 @@ fn deserialize_json_async() {
     });
 }
 
+#[test]
+fn deserialize_json_with_custom_function_reports_failed_field() {
+    let m = mock! {
+        body: r#"{
+            "id": 1,
+            "name": 2
+        }"#,
+    };
+
+    let mut response = isahc::get(m.url()).unwrap();
+
+    // Report the field that has the wrong type, instead of just the position
+    // in the body.
+    let result = response.json_with(|de| {
+        let value = Value::deserialize(de).map_err(|e| e.to_string())?;
+
+        let id = value["id"]
+            .as_u64()
+            .ok_or_else(|| String::from("id: expected an integer"))?;
+        let name = value["name"]
+            .as_str()
+            .ok_or_else(|| String::from("name: expected a string"))?;
+
+        Ok((id, name.to_owned()))
+    });
+
+    assert_eq!(result, Err(String::from("name: expected a string")));
+}
+
+#[test]
+fn deserialize_json_with_custom_function_async() {
+    let m = mock! {
+        body: r#"{"foo": "bar"} trailing"#,
+    };
+
+    block_on(async move {
+        let mut response = isahc::get_async(m.url()).await.unwrap();
+
+        let result = response
+            .json_with(|de| {
+                let value = Value::deserialize(&mut *de)?;
+                de.end()?;
+                Ok::<_, serde_json::Error>(value)
+            })
+            .await;
+
+        assert!(result.unwrap_err().is_syntax());
+    });
+}
+
 #[test]
 fn deserialize_json_async_io_error() {
     struct BadReader;
@@ -63,3 +114,52 @@ fn deserialize_json_async_io_error() {
         assert_matches!(response.json::<Value>().await, Err(e) if e.is_io());
     });
 }
+
+#[test]
+fn json_lines_request_body_is_streamed() {
+    let m = mock!();
+
+    let response = isahc::Request::post(m.url())
+        .body(isahc::Body::from_json_lines((0..1000).map(|id| {
+            serde_json::json!({
+                "id": id,
+                "name": format!("item {}", id),
+            })
+        })))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+
+    let request = m.request();
+    request.expect_header("transfer-encoding", "chunked");
+
+    let body = String::from_utf8(request.body.unwrap()).unwrap();
+    assert!(body.ends_with('\n'));
+
+    let lines = body.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1000);
+
+    for (id, line) in lines.into_iter().enumerate() {
+        let value: Value = serde_json::from_str(line).unwrap();
+        assert_eq!(value["id"], id);
+        assert_eq!(value["name"], format!("item {}", id));
+    }
+}
+
+#[test]
+fn json_lines_async_request_body_is_streamed() {
+    let m = mock!();
+
+    block_on(async {
+        isahc::Request::post(m.url())
+            .body(isahc::AsyncBody::from_json_lines(vec![1, 2, 3]))
+            .unwrap()
+            .send_async()
+            .await
+            .unwrap();
+    });
+
+    m.request().expect_body("1\n2\n3\n");
+}
@@ -10,6 +10,17 @@ fn get_request() {
     assert_eq!(m.request().method, "GET");
 }
 
+#[test]
+fn get_request_with_body() {
+    let m = mock!();
+
+    isahc::get_with_body(m.url(), r#"{"query":"isahc"}"#).unwrap();
+
+    assert_eq!(m.request().method, "GET");
+    m.request().expect_header("content-length", "17");
+    m.request().expect_body(r#"{"query":"isahc"}"#);
+}
+
 #[test]
 fn head_request() {
     let m = mock!();
@@ -60,3 +71,76 @@ fn arbitrary_foobar_request() {
 
     assert_eq!(m.request().method, "FOOBAR");
 }
+
+#[test]
+fn options_request() {
+    let m = mock!();
+
+    isahc::options(m.url()).unwrap();
+
+    assert_eq!(m.request().method, "OPTIONS");
+    m.request().expect_body("");
+}
+
+#[test]
+fn options_preflight_request() {
+    let m = mock! {
+        headers {
+            "access-control-allow-methods": "GET, POST",
+        }
+    };
+
+    let response = Request::options(m.url())
+        .header("origin", "https://example.org")
+        .header("access-control-request-method", "POST")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()["access-control-allow-methods"],
+        "GET, POST"
+    );
+
+    assert_eq!(m.request().method, "OPTIONS");
+    m.request()
+        .expect_header("access-control-request-method", "POST");
+    m.request().expect_body("");
+    assert!(m.request().get_header("content-length").next().is_none());
+    assert!(m.request().get_header("transfer-encoding").next().is_none());
+}
+
+#[test]
+fn trace_request() {
+    let m = mock!();
+
+    isahc::trace(m.url()).unwrap();
+
+    assert_eq!(m.request().method, "TRACE");
+    m.request().expect_body("");
+}
+
+#[test]
+fn options_asterisk_form_request() {
+    let m = mock!();
+
+    let mut parts = m.url().parse::<http::Uri>().unwrap().into_parts();
+    parts.path_and_query = Some(http::uri::PathAndQuery::from_static("*"));
+
+    isahc::options(http::Uri::from_parts(parts).unwrap()).unwrap();
+
+    assert_eq!(m.request().method, "OPTIONS");
+    assert_eq!(m.request().url, "*");
+}
+
+#[test]
+fn delete_request_with_body() {
+    let m = mock!();
+
+    futures_lite::future::block_on(isahc::delete_with_body_async(m.url(), "id=1")).unwrap();
+
+    assert_eq!(m.request().method, "DELETE");
+    m.request().expect_body("id=1");
+}
@@ -1,5 +1,5 @@
 use isahc::{prelude::*, HttpClient, Request};
-use std::{io, time::Duration};
+use std::{io, sync::mpsc, time::Duration};
 use testserver::mock;
 
 #[test]
@@ -34,3 +34,184 @@ fn enabling_metrics_causes_metrics_to_be_collected() {
     assert_eq!(metrics.download_progress().0, 11);
     assert!(metrics.total_time() > Duration::default());
 }
+
+#[test]
+fn metrics_snapshot_outlives_response() {
+    let m = mock! {
+        body: "hello world",
+    };
+
+    let client = HttpClient::builder().metrics(true).build().unwrap();
+
+    let mut response = client.get(m.url()).unwrap();
+
+    io::copy(response.body_mut(), &mut io::sink()).unwrap();
+
+    let snapshot = response.metrics().unwrap().snapshot();
+
+    drop(response);
+
+    assert_eq!(snapshot.download_progress().0, 11);
+    assert!(snapshot.total_time() > Duration::default());
+    assert!(snapshot.total_time() >= snapshot.transfer_start_time());
+}
+
+#[test]
+fn body_lengths_are_counted_without_metrics() {
+    let m = mock! {
+        body: "a".repeat(5000),
+    };
+
+    let mut response = isahc::post(m.url(), vec![b'b'; 3000]).unwrap();
+
+    assert!(response.metrics().is_none());
+    assert_eq!(response.request_body_len(), Some(3000));
+
+    response.consume().unwrap();
+
+    assert_eq!(response.response_body_len(), Some(5000));
+}
+
+#[test]
+fn body_lengths_are_unknown_for_constructed_responses() {
+    let response = http::Response::new(());
+
+    assert_eq!(response.request_body_len(), None);
+    assert_eq!(response.response_body_len(), None);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn metrics_snapshot_serializes_to_json() {
+    let m = mock! {
+        body: "hello world",
+    };
+
+    let mut response = Request::put(m.url())
+        .metrics(true)
+        .body("hello server")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    response.consume().unwrap();
+
+    let snapshot = response.metrics().unwrap().snapshot();
+    let json = serde_json::to_value(snapshot).unwrap();
+
+    assert_eq!(json["upload_bytes"], 12);
+    assert_eq!(json["download_bytes"], 11);
+
+    for key in &[
+        "upload_total_bytes",
+        "upload_speed",
+        "download_total_bytes",
+        "download_speed",
+        "name_lookup_time",
+        "connect_time",
+        "secure_connect_time",
+        "transfer_start_time",
+        "transfer_time",
+        "total_time",
+        "redirect_time",
+    ] {
+        assert!(json[key].is_number(), "missing key {}", key);
+    }
+
+    assert_eq!(json["total_time"], snapshot.total_time().as_secs_f64());
+}
+
+#[test]
+fn completion_callback_is_called_once_per_request_with_metrics() {
+    let m = mock! {
+        body: "hello world",
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let tx = std::sync::Mutex::new(tx);
+
+    let client = HttpClient::builder()
+        .on_request_complete(move |metrics, request| {
+            tx.lock()
+                .unwrap()
+                .send((
+                    request.method.clone(),
+                    request.uri.clone(),
+                    metrics.download_progress(),
+                    metrics.total_time(),
+                ))
+                .unwrap();
+        })
+        .build()
+        .unwrap();
+
+    let mut response = client.get(m.url()).unwrap();
+    response.text().unwrap();
+
+    let (method, uri, download_progress, total_time) =
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    assert_eq!(method, "GET");
+    assert_eq!(uri.to_string(), m.url());
+    assert_eq!(download_progress, (11, 11));
+    assert!(total_time > Duration::from_secs(0));
+
+    // The callback is also called if the body is never consumed.
+    drop(client.post(m.url(), "hello").unwrap());
+
+    let (method, ..) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(method, "POST");
+
+    drop(client);
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn completion_callback_is_called_once_for_redirected_request() {
+    let m2 = mock!();
+    let location = m2.url();
+
+    let m1 = mock! {
+        status: 302,
+        headers {
+            "Location": location,
+        }
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let tx = std::sync::Mutex::new(tx);
+
+    let client = HttpClient::builder()
+        .redirect_policy(isahc::config::RedirectPolicy::Follow)
+        .on_request_complete(move |_, request| {
+            tx.lock().unwrap().send(request.uri.clone()).unwrap();
+        })
+        .build()
+        .unwrap();
+
+    client.get(m1.url()).unwrap().consume().unwrap();
+
+    let uri = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(uri.to_string(), m2.url());
+
+    drop(client);
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn panicking_completion_callback_does_not_break_client() {
+    let m = mock!();
+
+    let client = HttpClient::builder()
+        .on_request_complete(|_, _| panic!("oops"))
+        .build()
+        .unwrap();
+
+    for _ in 0..3 {
+        let mut response = client.get(m.url()).unwrap();
+        response.consume().unwrap();
+        drop(response);
+    }
+
+    assert_eq!(m.requests().len(), 3);
+}
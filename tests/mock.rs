@@ -0,0 +1,103 @@
+#![cfg(feature = "mock")]
+
+use futures_lite::future::block_on;
+use isahc::{error::ErrorKind, mock::MockTransport, prelude::*, HttpClient, Response};
+
+#[test]
+fn registered_response_is_returned_for_matching_uri() {
+    let mock = MockTransport::new()
+        .respond(
+            "GET",
+            "http://example.org/a",
+            Response::builder()
+                .status(200)
+                .header("content-type", "text/plain")
+                .body("hello a")
+                .unwrap(),
+        )
+        .respond(
+            "GET",
+            "http://example.org/b",
+            Response::builder().status(404).body("not here").unwrap(),
+        );
+
+    let client = HttpClient::builder().transport(mock).build().unwrap();
+
+    let mut response = client.get("http://example.org/a").unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "text/plain");
+    assert_eq!(response.text().unwrap(), "hello a");
+
+    let mut response = client.get("http://example.org/b").unwrap();
+    assert_eq!(response.status(), 404);
+    assert_eq!(response.text().unwrap(), "not here");
+
+    // Responses can be returned more than once.
+    let mut response = client.get("http://example.org/a").unwrap();
+    assert_eq!(response.text().unwrap(), "hello a");
+}
+
+#[test]
+fn responses_are_matched_by_method() {
+    let mock = MockTransport::new().respond(
+        "POST",
+        "http://example.org/",
+        Response::builder().status(201).body("").unwrap(),
+    );
+
+    let client = HttpClient::builder().transport(mock).build().unwrap();
+
+    assert_eq!(
+        client.post("http://example.org/", "data").unwrap().status(),
+        201
+    );
+
+    let error = client.get("http://example.org/").unwrap_err();
+    assert_eq!(error.kind(), &ErrorKind::ConnectionFailed);
+}
+
+#[test]
+fn unmatched_request_fails_with_connection_error() {
+    let client = HttpClient::builder()
+        .transport(MockTransport::new())
+        .build()
+        .unwrap();
+
+    let error = block_on(client.get_async("http://example.org/nope")).unwrap_err();
+
+    assert_eq!(error.kind(), &ErrorKind::ConnectionFailed);
+}
+
+#[test]
+fn mock_redirects_are_followed() {
+    let mock = MockTransport::new()
+        .respond(
+            "GET",
+            "http://example.org/old",
+            Response::builder()
+                .status(301)
+                .header("location", "/new")
+                .body("")
+                .unwrap(),
+        )
+        .respond(
+            "GET",
+            "http://example.org/new",
+            Response::builder().body("moved").unwrap(),
+        );
+
+    let client = HttpClient::builder()
+        .transport(mock)
+        .redirect_policy(isahc::config::RedirectPolicy::Follow)
+        .build()
+        .unwrap();
+
+    let mut response = client.get("http://example.org/old").unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().unwrap(), "moved");
+    assert_eq!(
+        response.effective_uri().unwrap().to_string(),
+        "http://example.org/new"
+    );
+}
@@ -0,0 +1,68 @@
+use futures_lite::{future::block_on, StreamExt};
+use isahc::{prelude::*, Request};
+use testserver::mock;
+
+#[test]
+fn byteranges_response_is_parsed_into_parts() {
+    let m = mock! {
+        status: 206,
+        headers {
+            "Content-Type": "multipart/byteranges; boundary=THIS_STRING_SEPARATES",
+        }
+        body: "\
+--THIS_STRING_SEPARATES\r\n\
+Content-Type: text/plain\r\n\
+Content-Range: bytes 0-4/26\r\n\
+\r\n\
+abcde\r\n\
+--THIS_STRING_SEPARATES\r\n\
+Content-Type: text/plain\r\n\
+Content-Range: bytes 20-25/26\r\n\
+\r\n\
+uvwxyz\r\n\
+--THIS_STRING_SEPARATES--\r\n",
+    };
+
+    block_on(async {
+        let parts = Request::get(m.url())
+            .header("Range", "bytes=0-4,20-25")
+            .body(())
+            .unwrap()
+            .send_async()
+            .await
+            .unwrap()
+            .multipart()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].headers()["content-range"], "bytes 0-4/26");
+        assert_eq!(parts[0].headers()["content-type"], "text/plain");
+        assert_eq!(parts[0].body(), b"abcde");
+        assert_eq!(parts[1].headers()["content-range"], "bytes 20-25/26");
+        assert_eq!(parts[1].body(), b"uvwxyz");
+    });
+
+    m.request().expect_header("range", "bytes=0-4,20-25");
+}
+
+#[test]
+fn non_multipart_response_produces_error() {
+    let m = mock! {
+        headers {
+            "Content-Type": "text/plain",
+        }
+        body: "hello",
+    };
+
+    block_on(async {
+        let mut parts = isahc::get_async(m.url()).await.unwrap().multipart();
+
+        assert_eq!(
+            parts.next().await.unwrap().unwrap_err().kind(),
+            &isahc::error::ErrorKind::ProtocolViolation
+        );
+        assert!(parts.next().await.is_none());
+    });
+}
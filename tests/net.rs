@@ -1,4 +1,10 @@
-use isahc::{config::IpVersion, error::ErrorKind, prelude::*, Request};
+use futures_lite::future::{block_on, zip};
+use isahc::{
+    config::{IpVersion, VersionNegotiation},
+    error::ErrorKind,
+    prelude::*,
+    Request,
+};
 use std::{
     io::{self, Read, Write},
     net::{Ipv4Addr, Ipv6Addr, Shutdown, TcpListener, TcpStream, ToSocketAddrs},
@@ -137,3 +143,1134 @@ fn any_ip_version_uses_ipv4_or_ipv6() {
         assert!(response.local_addr().unwrap().is_ipv4());
     }
 }
+
+/// Read a single request head from the given stream.
+fn read_request_head(stream: &mut TcpStream) -> io::Result<()> {
+    let mut head = Vec::new();
+    let mut byte = [0];
+
+    while !head.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte)? == 0 {
+            break;
+        }
+        head.push(byte[0]);
+    }
+
+    Ok(())
+}
+
+/// Spawn a server that closes the first `n` requests it receives without
+/// sending a response, and then responds to any requests after that.
+fn spawn_server_closing_first_requests(n: usize) -> String {
+    let server = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let url = format!("http://{}", server.local_addr().unwrap());
+
+    thread::spawn(move || {
+        for (i, client) in server.incoming().enumerate() {
+            let mut client = client.unwrap();
+            read_request_head(&mut client).unwrap();
+
+            if i >= n {
+                client
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length:2\r\n\r\nOK")
+                    .unwrap();
+            }
+
+            let _ = client.shutdown(Shutdown::Both);
+        }
+    });
+
+    url
+}
+
+#[test]
+fn request_is_retried_when_pooled_connection_closed_by_server() {
+    let server = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let url = format!("http://{}", server.local_addr().unwrap());
+
+    thread::spawn(move || {
+        // Serve the first request and keep the connection alive, but then
+        // close the pooled connection when the second request arrives.
+        let (mut client, _) = server.accept().unwrap();
+        read_request_head(&mut client).unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length:3\r\n\r\none")
+            .unwrap();
+        read_request_head(&mut client).unwrap();
+        drop(client);
+
+        // Serve the retry on a new connection.
+        let (mut client, _) = server.accept().unwrap();
+        read_request_head(&mut client).unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length:3\r\n\r\ntwo")
+            .unwrap();
+    });
+
+    let client = isahc::HttpClient::new().unwrap();
+
+    assert_eq!(client.get(&url).unwrap().text().unwrap(), "one");
+    assert_eq!(client.get(&url).unwrap().text().unwrap(), "two");
+}
+
+#[test]
+fn request_is_retried_when_connection_closed_without_response() {
+    let url = spawn_server_closing_first_requests(1);
+
+    let mut response = Request::put(url).body("hello").unwrap().send().unwrap();
+
+    assert_eq!(response.text().unwrap(), "OK");
+}
+
+#[test]
+fn request_is_only_retried_once() {
+    let url = spawn_server_closing_first_requests(2);
+
+    let result = isahc::get(url);
+
+    assert_matches!(result, Err(e) if e == ErrorKind::ProtocolViolation);
+}
+
+#[test]
+fn request_is_not_retried_when_disabled() {
+    let url = spawn_server_closing_first_requests(1);
+
+    let client = isahc::HttpClient::builder()
+        .retry_on_connection_reset(false)
+        .build()
+        .unwrap();
+
+    assert_matches!(client.get(url), Err(e) if e == ErrorKind::ProtocolViolation);
+}
+
+#[test]
+fn non_idempotent_request_is_not_retried() {
+    let url = spawn_server_closing_first_requests(1);
+
+    let result = isahc::post(url, "hello");
+
+    assert_matches!(result, Err(e) if e == ErrorKind::ProtocolViolation);
+}
+
+#[test]
+fn request_with_streaming_body_is_not_retried() {
+    let url = spawn_server_closing_first_requests(1);
+
+    let result = Request::put(url)
+        .body(isahc::Body::from_reader("hello".as_bytes()))
+        .unwrap()
+        .send();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn request_is_not_retried_after_part_of_response_received() {
+    let server = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let url = format!("http://{}", server.local_addr().unwrap());
+    let connections = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    thread::spawn({
+        let connections = connections.clone();
+
+        move || {
+            for client in server.incoming() {
+                let mut client = client.unwrap();
+                connections.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                read_request_head(&mut client).unwrap();
+
+                // Send part of the response headers, and then reset the
+                // connection by closing it without reading the request body.
+                client.write_all(b"HTTP/1.1 200 OK\r\n").unwrap();
+                thread::sleep(std::time::Duration::from_millis(100));
+                drop(client);
+            }
+        }
+    });
+
+    let result = Request::put(url).body("hello").unwrap().send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::Io);
+    assert_eq!(connections.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[test]
+fn connections_are_reused_by_default() {
+    let m = mock!();
+    let client = isahc::HttpClient::new().unwrap();
+
+    let first = client.get(m.url()).unwrap().local_addr().unwrap();
+    let second = client.get(m.url()).unwrap().local_addr().unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn first_request_is_not_on_a_reused_connection() {
+    let m = mock!();
+    let client = isahc::HttpClient::new().unwrap();
+
+    assert!(!client.get(m.url()).unwrap().connection_reused());
+    assert!(client.get(m.url()).unwrap().connection_reused());
+}
+
+#[test]
+fn warm_up_opens_connections_for_later_requests() {
+    let m = mock!();
+    let client = isahc::HttpClient::new().unwrap();
+
+    block_on(client.warm_up(vec![m.url().parse().unwrap()])).unwrap();
+
+    let response = client.get(m.url()).unwrap();
+
+    assert!(response.connection_reused());
+    assert_eq!(m.requests().len(), 2);
+    assert_eq!(m.requests()[0].method, "HEAD");
+}
+
+#[test]
+fn warm_up_reports_connection_errors() {
+    let client = isahc::HttpClient::new().unwrap();
+    let uri = "http://localhost:1".parse().unwrap();
+
+    let error = block_on(client.warm_up(vec![uri])).unwrap_err();
+
+    assert_eq!(error.kind(), &ErrorKind::ConnectionFailed);
+}
+
+#[test]
+fn connections_older_than_max_age_are_not_reused() {
+    let m = mock!();
+    let client = isahc::HttpClient::builder()
+        .max_connection_age(std::time::Duration::from_secs(1))
+        .build()
+        .unwrap();
+
+    let first = client.get(m.url()).unwrap().local_addr().unwrap();
+
+    thread::sleep(std::time::Duration::from_millis(1100));
+
+    let second = client.get(m.url()).unwrap().local_addr().unwrap();
+
+    assert_ne!(first, second);
+}
+
+#[test]
+fn sub_second_max_connection_age_is_rounded_up() {
+    let m = mock!();
+    let client = isahc::HttpClient::builder()
+        .max_connection_age(std::time::Duration::from_millis(500))
+        .build()
+        .unwrap();
+
+    let first = client.get(m.url()).unwrap().local_addr().unwrap();
+    let second = client.get(m.url()).unwrap().local_addr().unwrap();
+
+    assert_eq!(first, second);
+
+    thread::sleep(std::time::Duration::from_millis(1100));
+
+    let third = client.get(m.url()).unwrap().local_addr().unwrap();
+
+    assert_ne!(first, third);
+}
+
+#[cfg(feature = "http2")]
+#[test]
+fn connections_are_not_reused_across_incompatible_http_versions() {
+    let server = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let url = format!("http://{}", server.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    thread::spawn(move || {
+        for (i, client) in server.incoming().enumerate() {
+            let mut client = client.unwrap();
+            let tx = tx.clone();
+
+            thread::spawn(move || {
+                loop {
+                    let mut preface = [0; 14];
+
+                    if client.read_exact(&mut preface).is_err() {
+                        break;
+                    }
+
+                    tx.send((i, preface)).unwrap();
+
+                    // We don't speak HTTP/2, so just hang up.
+                    if &preface == b"PRI * HTTP/2.0" {
+                        break;
+                    }
+
+                    read_request_head(&mut client).unwrap();
+                    client
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length:2\r\n\r\nOK")
+                        .unwrap();
+                }
+            });
+        }
+    });
+
+    let client = isahc::HttpClient::new().unwrap();
+
+    let mut response = client
+        .send(
+            Request::get(&url)
+                .version_negotiation(VersionNegotiation::http11())
+                .body(())
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(response.text().unwrap(), "OK");
+
+    // The HTTP/1.1 connection is still alive, but must not be used for an
+    // HTTP/2 request.
+    let _ = client.send(
+        Request::get(&url)
+            .version_negotiation(VersionNegotiation::http2())
+            .body(())
+            .unwrap(),
+    );
+
+    let timeout = std::time::Duration::from_secs(5);
+    assert_eq!(rx.recv_timeout(timeout).unwrap(), (0, *b"GET / HTTP/1.1"));
+    assert_eq!(rx.recv_timeout(timeout).unwrap(), (1, *b"PRI * HTTP/2.0"));
+}
+
+#[cfg(feature = "http2")]
+#[test]
+fn http2_connections_are_not_reused_for_http11_requests() {
+    let (url, connections) = spawn_h2c_server(1);
+    let client = isahc::HttpClient::new().unwrap();
+
+    let mut response = client
+        .send(
+            Request::get(&url)
+                .version_negotiation(VersionNegotiation::http2())
+                .body(())
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(response.text().unwrap(), "OK");
+
+    // The server only speaks HTTP/2, so this request fails, but it must do so
+    // over a new connection.
+    let _ = client.send(
+        Request::get(&url)
+            .version_negotiation(VersionNegotiation::http11())
+            .timeout(std::time::Duration::from_secs(5))
+            .body(())
+            .unwrap(),
+    );
+
+    assert_eq!(connections.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[test]
+fn connection_is_not_reused_after_close_connection() {
+    let m = mock!();
+    let client = isahc::HttpClient::new().unwrap();
+
+    let first = client
+        .send(
+            Request::get(m.url())
+                .close_connection(true)
+                .body(())
+                .unwrap(),
+        )
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let second = client.get(m.url()).unwrap().local_addr().unwrap();
+
+    assert_ne!(first, second);
+}
+
+#[test]
+fn close_connection_false_overrides_client_default() {
+    let m = mock!();
+    let client = isahc::HttpClient::builder()
+        .connection_cache_size(0)
+        .build()
+        .unwrap();
+
+    let request = || {
+        Request::get(m.url())
+            .close_connection(false)
+            .body(())
+            .unwrap()
+    };
+
+    let first = client.send(request()).unwrap().local_addr().unwrap();
+    let second = client.send(request()).unwrap().local_addr().unwrap();
+
+    assert_eq!(first, second);
+}
+
+/// Spawn a minimal HTTP/2 server with prior knowledge that responds to every
+/// request without a body with `200 OK`, and never responds to requests with
+/// a body. Responses on a connection are held back until `batch_size` requests
+/// without a body are waiting for one. Returns the URL of the server and a
+/// counter of connections.
+#[cfg(feature = "http2")]
+fn spawn_h2c_server(
+    batch_size: usize,
+) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+    use std::sync::{atomic::AtomicUsize, atomic::Ordering, Arc};
+
+    const DATA: u8 = 0x0;
+    const HEADERS: u8 = 0x1;
+    const SETTINGS: u8 = 0x4;
+    const GOAWAY: u8 = 0x7;
+    const END_STREAM: u8 = 0x1;
+    const END_HEADERS: u8 = 0x4;
+    const ACK: u8 = 0x1;
+
+    fn write_frame(stream: &mut TcpStream, kind: u8, flags: u8, id: u32, payload: &[u8]) {
+        let len = (payload.len() as u32).to_be_bytes();
+        let mut frame = vec![len[1], len[2], len[3], kind, flags];
+        frame.extend_from_slice(&id.to_be_bytes());
+        frame.extend_from_slice(payload);
+        let _ = stream.write_all(&frame);
+    }
+
+    let server = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let url = format!("http://{}", server.local_addr().unwrap());
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    thread::spawn({
+        let connections = connections.clone();
+
+        move || {
+            for client in server.incoming() {
+                let mut client = client.unwrap();
+                connections.fetch_add(1, Ordering::SeqCst);
+
+                thread::spawn(move || {
+                    let mut preface = [0; 24];
+                    client.read_exact(&mut preface).unwrap();
+                    write_frame(&mut client, SETTINGS, 0, 0, &[]);
+
+                    let mut header = [0; 9];
+                    let mut waiting = Vec::new();
+
+                    while client.read_exact(&mut header).is_ok() {
+                        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]);
+                        let id = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+                        let mut payload = vec![0; len as usize];
+                        client.read_exact(&mut payload).unwrap();
+
+                        match header[3] {
+                            SETTINGS if header[4] & ACK == 0 => {
+                                write_frame(&mut client, SETTINGS, ACK, 0, &[]);
+                            }
+                            HEADERS if header[4] & END_STREAM != 0 => {
+                                waiting.push(id);
+
+                                if waiting.len() < batch_size {
+                                    continue;
+                                }
+
+                                for id in waiting.drain(..) {
+                                    // 0x88 is `:status: 200` in the HPACK static table.
+                                    write_frame(&mut client, HEADERS, END_HEADERS, id, &[0x88]);
+                                    write_frame(&mut client, DATA, END_STREAM, id, b"OK");
+                                }
+                            }
+                            GOAWAY => break,
+                            _ => {}
+                        }
+                    }
+                });
+            }
+        }
+    });
+
+    (url, connections)
+}
+
+/// Send a request whose body fails to be read, followed by a normal request
+/// with the same client, and return the number of connections used.
+#[cfg(feature = "http2")]
+fn connections_used_after_request_body_error(close_on_body_error: bool) -> usize {
+    struct FailingReader;
+
+    impl futures_lite::AsyncRead for FailingReader {
+        fn poll_read(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            _buf: &mut [u8],
+        ) -> std::task::Poll<io::Result<usize>> {
+            std::task::Poll::Ready(Err(io::ErrorKind::ConnectionAborted.into()))
+        }
+    }
+
+    let (url, connections) = spawn_h2c_server(1);
+
+    let client = isahc::HttpClient::builder()
+        .version_negotiation(VersionNegotiation::http2())
+        .close_on_body_error(close_on_body_error)
+        .build()
+        .unwrap();
+
+    assert_eq!(client.get(&url).unwrap().text().unwrap(), "OK");
+
+    let result = block_on(
+        client.send_async(
+            Request::put(&url)
+                .body(isahc::AsyncBody::from_reader(FailingReader))
+                .unwrap(),
+        ),
+    );
+    assert!(result.is_err());
+
+    assert_eq!(client.get(&url).unwrap().text().unwrap(), "OK");
+
+    connections.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+// Curl always closes HTTP/1.x connections after a failed transfer, but keeps
+// HTTP/2 connections open after one of their streams fails.
+#[cfg(feature = "http2")]
+#[test]
+fn connection_is_not_reused_after_request_body_error() {
+    assert_eq!(connections_used_after_request_body_error(true), 2);
+}
+
+#[cfg(feature = "http2")]
+#[test]
+fn connection_is_reused_after_request_body_error_if_close_on_body_error_is_disabled() {
+    assert_eq!(connections_used_after_request_body_error(false), 1);
+}
+
+#[test]
+fn haproxy_protocol_sends_proxy_header_before_request() {
+    let server = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let url = format!("http://{}", server.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut client, peer) = server.accept().unwrap();
+        let mut line = Vec::new();
+        let mut byte = [0];
+
+        while !line.ends_with(b"\r\n") {
+            if client.read(&mut byte).unwrap() == 0 {
+                break;
+            }
+            line.push(byte[0]);
+        }
+
+        read_request_head(&mut client).unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length:0\r\n\r\n")
+            .unwrap();
+
+        tx.send((String::from_utf8(line).unwrap(), peer)).unwrap();
+    });
+
+    let response = Request::get(url)
+        .haproxy_protocol(true)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+
+    let (line, peer) = rx.recv().unwrap();
+    let fields = line.trim_end().split(' ').collect::<Vec<_>>();
+
+    assert_eq!(fields[0], "PROXY");
+    assert_eq!(fields[1], "TCP4");
+    assert_eq!(fields[2], "127.0.0.1");
+    assert_eq!(fields[4], peer.port().to_string());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn agent_thread_uses_configured_name_and_stack_size() {
+    fn thread_names() -> Vec<String> {
+        std::fs::read_dir("/proc/self/task")
+            .unwrap()
+            .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path().join("comm")).ok())
+            .map(|name| name.trim_end().to_owned())
+            .collect()
+    }
+
+    let m = mock!();
+
+    let client = isahc::HttpClient::builder()
+        .agent_thread_name("custom-agent")
+        .agent_thread_stack_size(4 * 1024 * 1024)
+        .build()
+        .unwrap();
+
+    assert!(thread_names().iter().any(|name| name == "custom-agent"));
+
+    // The client still works with a custom stack size.
+    assert_eq!(client.get(m.url()).unwrap().status(), 200);
+}
+
+#[test]
+fn idle_connection_with_http2_keepalive_remains_usable() {
+    let m = mock!();
+    let client = isahc::HttpClient::builder()
+        .http2_keepalive(std::time::Duration::from_secs(1))
+        .build()
+        .unwrap();
+
+    client.get(m.url()).unwrap().consume().unwrap();
+
+    // Stay idle long enough for keepalive probes to be sent.
+    thread::sleep(std::time::Duration::from_millis(2500));
+
+    let response = client.get(m.url()).unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert!(response.connection_reused());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn requests_succeed_with_tcp_user_timeout() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .tcp_user_timeout(std::time::Duration::from_secs(10))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(m.requests().len(), 1);
+}
+
+#[test]
+fn requests_succeed_with_signals_enabled() {
+    let m = mock!();
+
+    Request::get(m.url())
+        .use_signals(true)
+        .timeout(std::time::Duration::from_secs(5))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(m.requests().len(), 1);
+}
+
+#[test]
+fn request_tag_is_attached_to_response() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .tag("fetch-widgets")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.tag(), Some("fetch-widgets"));
+}
+
+#[test]
+fn request_tag_is_attached_to_errors() {
+    // Find a port with nothing listening on it.
+    let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let error = Request::get(format!("http://127.0.0.1:{}", port))
+        .tag("fetch-widgets")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap_err();
+
+    assert_eq!(error, ErrorKind::ConnectionFailed);
+    assert_eq!(error.tag(), Some("fetch-widgets"));
+    assert!(error.to_string().starts_with("[fetch-widgets] "));
+}
+
+#[test]
+fn dot_segments_in_path_are_normalized_by_default() {
+    let m = mock!();
+
+    Request::get(format!("{}a/../b", m.url()))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(m.request().url, "/b");
+}
+
+#[test]
+fn path_as_is_sends_dot_segments_verbatim() {
+    let m = mock!();
+
+    Request::get(format!("{}a/../b", m.url()))
+        .path_as_is(true)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(m.request().url, "/a/../b");
+}
+
+#[test]
+fn alpn_restricted_to_http11_overrides_http2_negotiation() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .version_negotiation(VersionNegotiation::http2())
+        .alpn_protocols(vec!["http/1.1"])
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.version(), http::Version::HTTP_11);
+    assert_eq!(m.requests().len(), 1);
+}
+
+#[test]
+fn negotiated_alpn_is_none_without_tls() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .alpn_protocols(vec!["h2", "http/1.1"])
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.negotiated_alpn(), None);
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn negotiated_alpn_is_protocol_selected_by_server() {
+    use isahc::config::CaCertificate;
+    use testserver::tls::{self, TlsServer};
+
+    let server = TlsServer::new();
+
+    let response = Request::get(server.url())
+        .ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .alpn_protocols(vec!["h2", "http/1.1"])
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.negotiated_alpn(), Some("http/1.1"));
+}
+
+#[test]
+fn higher_priority_requests_are_started_first_when_connections_are_limited() {
+    let m = mock! {
+        delay: 100ms,
+    };
+
+    let client = isahc::HttpClient::builder()
+        .max_connections(1)
+        .build()
+        .unwrap();
+
+    let request = |path: &str, priority: u8| {
+        client.send_async(
+            Request::get(format!("{}{}", m.url(), path))
+                .priority(priority)
+                .body(())
+                .unwrap(),
+        )
+    };
+
+    // The first request occupies the only connection while the others wait.
+    let (first, (low, high)) = block_on(zip(
+        request("first", 0),
+        zip(request("low", 1), request("high", 10)),
+    ));
+
+    first.unwrap();
+    low.unwrap();
+    high.unwrap();
+
+    let urls = m.requests().into_iter().map(|r| r.url).collect::<Vec<_>>();
+    assert_eq!(urls, vec!["/first", "/high", "/low"]);
+}
+
+#[cfg(feature = "http2")]
+#[test]
+fn http2_requests_are_multiplexed_when_connections_are_limited() {
+    // The server only responds once both requests are waiting, so they have to
+    // be executed at the same time.
+    let (url, connections) = spawn_h2c_server(2);
+
+    let client = isahc::HttpClient::builder()
+        .max_connections(1)
+        .build()
+        .unwrap();
+
+    let request = || {
+        client.send_async(
+            Request::get(&url)
+                .version_negotiation(VersionNegotiation::http2())
+                .timeout(std::time::Duration::from_secs(5))
+                .body(())
+                .unwrap(),
+        )
+    };
+
+    let (first, second) = block_on(zip(request(), request()));
+
+    assert_eq!(first.unwrap().status(), 200);
+    assert_eq!(second.unwrap().status(), 200);
+    assert_eq!(connections.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[test]
+fn stats_report_pending_requests_when_connections_are_limited() {
+    let m = mock! {
+        delay: 500ms,
+    };
+
+    let client = isahc::HttpClient::builder()
+        .max_connections(1)
+        .build()
+        .unwrap();
+
+    assert_eq!(client.stats(), Default::default());
+
+    let threads = (0..3)
+        .map(|_| {
+            let client = client.clone();
+            let url = m.url();
+            thread::spawn(move || client.get(url).unwrap().consume().unwrap())
+        })
+        .collect::<Vec<_>>();
+
+    let wait_for = |condition: &dyn Fn(isahc::ClientStats) -> bool| {
+        let start = std::time::Instant::now();
+
+        while !condition(client.stats()) {
+            assert!(start.elapsed() < std::time::Duration::from_secs(5));
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+    };
+
+    // Requests that curl has not picked up yet are briefly counted as pending
+    // too, so wait for the first one to become active.
+    wait_for(&|stats| stats.active() == 1 && stats.pending() > 0);
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    wait_for(&|stats| stats.active() == 0 && stats.pending() == 0);
+}
+
+#[test]
+fn time_spent_waiting_for_a_connection_counts_toward_timeout() {
+    let m = mock! {
+        delay: 1s,
+    };
+
+    let client = isahc::HttpClient::builder()
+        .max_connections(1)
+        .build()
+        .unwrap();
+
+    let first = thread::spawn({
+        let client = client.clone();
+        let url = m.url();
+        move || client.get(url).unwrap().consume().unwrap()
+    });
+
+    while client.stats().active() == 0 {
+        thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    // Only requests with a priority wait in the client's own queue.
+    let start = std::time::Instant::now();
+    let result = Request::get(m.url())
+        .priority(1)
+        .timeout(std::time::Duration::from_millis(300))
+        .body(())
+        .map_err(isahc::Error::from)
+        .and_then(|request| client.send(request));
+
+    assert_matches!(result, Err(e) if e == ErrorKind::Timeout);
+    assert!(start.elapsed() < std::time::Duration::from_millis(900));
+
+    first.join().unwrap();
+
+    assert_eq!(m.requests().len(), 1);
+}
+
+#[test]
+fn dropped_requests_are_removed_while_waiting_for_a_connection() {
+    let m = mock! {
+        delay: 2s,
+    };
+
+    let client = isahc::HttpClient::builder()
+        .max_connections(1)
+        .build()
+        .unwrap();
+
+    let first = thread::spawn({
+        let client = client.clone();
+        let url = m.url();
+        move || client.get(url).unwrap().consume().unwrap()
+    });
+
+    while client.stats().active() == 0 {
+        thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    // Only requests with a priority wait in the client's own queue.
+    let mut second = client.send_async(Request::get(m.url()).priority(1).body(()).unwrap());
+    assert!(block_on(futures_lite::future::poll_once(&mut second)).is_none());
+
+    while client.stats().pending() == 0 {
+        thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    drop(second);
+
+    while client.stats().pending() > 0 {
+        thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    // The queue was cleaned up before the active request completed.
+    assert_eq!(client.stats().active(), 1);
+
+    first.join().unwrap();
+
+    assert_eq!(m.requests().len(), 1);
+}
+
+#[test]
+fn map_error_transforms_errors_returned_by_client() {
+    let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let m = mock!();
+
+    let client = isahc::HttpClient::builder()
+        .map_error({
+            let calls = calls.clone();
+            move |error| {
+                calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                error.with_message("backend unavailable")
+            }
+        })
+        .build()
+        .unwrap();
+
+    client.get(m.url()).unwrap();
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    let error = client.get("http://127.0.0.1:1").unwrap_err();
+
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!(error.kind(), &ErrorKind::ConnectionFailed);
+    assert!(
+        error
+            .to_string()
+            .starts_with("failed to connect to the server: backend unavailable")
+    );
+}
+
+#[test]
+fn cancel_all_aborts_active_and_queued_requests() {
+    // A server that accepts connections but never responds.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        let mut streams = Vec::new();
+
+        for stream in listener.incoming() {
+            streams.push(stream.unwrap());
+        }
+    });
+
+    let client = isahc::HttpClient::builder()
+        .max_connections(2)
+        .build()
+        .unwrap();
+
+    let threads = (0..3)
+        .map(|_| {
+            let client = client.clone();
+            let url = url.clone();
+            thread::spawn(move || client.get(url).unwrap_err())
+        })
+        .collect::<Vec<_>>();
+
+    let start = std::time::Instant::now();
+
+    while client.stats().active() + client.stats().pending() < 3 {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let start = std::time::Instant::now();
+    client.cancel_all();
+
+    for thread in threads {
+        assert_eq!(thread.join().unwrap().kind(), &ErrorKind::Canceled);
+    }
+
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+    // The client can still be used afterwards.
+    let m = mock!();
+    client.get(m.url()).unwrap();
+    assert_eq!(m.requests().len(), 1);
+}
+
+#[test]
+fn altsvc_cache_file_is_loaded_and_saved() {
+    let m = mock! {
+        headers {
+            "Alt-Svc": "h2=\":443\"; ma=3600",
+        }
+    };
+
+    let entry = "h1 example.org 443 h2 example.org 443 \"20371231 00:00:00\" 0 0\n";
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), entry).unwrap();
+
+    let client = isahc::HttpClient::builder()
+        .altsvc_cache_file(file.path())
+        .build()
+        .unwrap();
+
+    client.get(m.url()).unwrap().consume().unwrap();
+
+    // The cache is saved once the request handle is cleaned up, which is
+    // guaranteed once the client is dropped.
+    drop(client);
+
+    let contents = std::fs::read_to_string(file.path()).unwrap();
+
+    assert!(contents.starts_with("# Your alt-svc cache."));
+    assert!(contents.contains(entry));
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn altsvc_cache_file_saves_learned_alternative_services() {
+    use isahc::config::CaCertificate;
+    use testserver::tls::{self, TlsServer};
+
+    let server = TlsServer::with_response(
+        "HTTP/1.1 200 OK\r\n\
+         alt-svc: h2=\":8443\"; ma=3600\r\n\
+         content-length: 0\r\n\
+         connection: close\r\n\r\n",
+    );
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+
+    let client = isahc::HttpClient::builder()
+        .ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .altsvc_cache_file(file.path())
+        .build()
+        .unwrap();
+
+    client.get(server.url()).unwrap().consume().unwrap();
+    drop(client);
+
+    let contents = std::fs::read_to_string(file.path()).unwrap();
+    let entry = format!("h1 localhost {} h2 localhost 8443 ", server.addr().port());
+
+    assert!(contents.contains(&entry), "{}", contents);
+}
+
+#[test]
+fn building_clients_concurrently_is_safe() {
+    let barrier = std::sync::Arc::new(std::sync::Barrier::new(16));
+
+    let threads = (0..16)
+        .map(|_| {
+            let barrier = barrier.clone();
+
+            thread::spawn(move || {
+                barrier.wait();
+                isahc::HttpClient::new().unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn debug_callback_receives_headers_and_data() {
+    use isahc::DebugInfoType;
+    use std::sync::{Arc, Mutex};
+
+    let m = mock! {
+        body: "hello world",
+    };
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let client = isahc::HttpClient::builder()
+        .debug_callback({
+            let events = events.clone();
+            move |kind, data| events.lock().unwrap().push((kind, data.to_vec()))
+        })
+        .build()
+        .unwrap();
+
+    let mut response = client.post(m.url(), "ping").unwrap();
+    assert_eq!(response.text().unwrap(), "hello world");
+
+    let events = events.lock().unwrap();
+    let received = |kind| {
+        events
+            .iter()
+            .filter(|(k, _)| *k == kind)
+            .flat_map(|(_, data)| data.iter().copied())
+            .collect::<Vec<u8>>()
+    };
+
+    assert!(
+        String::from_utf8(received(DebugInfoType::HeaderOut))
+            .unwrap()
+            .starts_with("POST / HTTP/1.1\r\n")
+    );
+    assert!(
+        String::from_utf8(received(DebugInfoType::HeaderIn))
+            .unwrap()
+            .starts_with("HTTP/1.1 200")
+    );
+    assert_eq!(received(DebugInfoType::DataOut), b"ping");
+    assert_eq!(received(DebugInfoType::DataIn), b"hello world");
+    assert!(!received(DebugInfoType::Text).is_empty());
+}
+
+#[test]
+fn panicking_debug_callback_does_not_break_client() {
+    let m = mock!();
+    let client = isahc::HttpClient::builder()
+        .debug_callback(|_, _| panic!("debug callback panicked"))
+        .build()
+        .unwrap();
+
+    assert_eq!(client.get(m.url()).unwrap().status(), 200);
+
+    // The agent thread is still alive to serve later requests.
+    assert_eq!(client.get(m.url()).unwrap().status(), 200);
+}
@@ -1,6 +1,93 @@
-use isahc::{prelude::*, Request};
+use futures_lite::{
+    future::block_on,
+    io::{AsyncReadExt, AsyncWriteExt},
+};
+use isahc::{
+    auth::{Authentication, Credentials},
+    config::{CaCertificate, RedirectPolicy, SslOption},
+    error::ErrorKind,
+    http::HeaderMap,
+    prelude::*,
+    HttpClient,
+    Request,
+};
+use std::{
+    io::{Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::mpsc,
+    thread,
+};
 use testserver::{mock, socks4::Socks4Server};
 
+#[cfg(all(unix, not(target_os = "macos")))]
+use testserver::tls::{self, TlsServer};
+
+#[macro_use]
+mod utils;
+
+fn read_request_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut byte = [0];
+
+    while !head.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte).unwrap_or(0) == 0 {
+            break;
+        }
+        head.push(byte[0]);
+    }
+
+    String::from_utf8(head).unwrap()
+}
+
+/// Spawn an HTTP proxy that accepts a single connection, and reports the heads
+/// of each request received over it. `CONNECT` requests are accepted, and if
+/// `tunnel` is true, any request received in the tunnel afterwards is answered
+/// directly. Otherwise the connection is closed right after the tunnel opens.
+fn spawn_recording_proxy(tunnel: bool) -> (http::Uri, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let uri = format!("http://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut stream = listener.accept().unwrap().0;
+
+        loop {
+            let head = read_request_head(&mut stream);
+
+            if head.is_empty() {
+                break;
+            }
+
+            let is_connect = head.starts_with("CONNECT ");
+            tx.send(head).unwrap();
+
+            if is_connect {
+                stream
+                    .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                    .unwrap();
+
+                if !tunnel {
+                    break;
+                }
+            } else {
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .unwrap();
+            }
+        }
+    });
+
+    (uri, rx)
+}
+
+fn proxy_token_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-proxy-token", "secret".parse().unwrap());
+    headers
+}
+
 #[test]
 fn no_proxy() {
     let m = mock!();
@@ -95,3 +182,423 @@ fn proxy_blacklist_works() {
 
     assert_eq!(m.requests().len(), 1);
 }
+
+#[test]
+fn proxy_for_scheme_routes_schemes_separately() {
+    let (proxy, heads) = spawn_recording_proxy(false);
+
+    // Our test server is upstream for plain HTTP requests.
+    let m = mock!();
+
+    let client = HttpClient::builder()
+        .proxy(Some("http://127.0.0.2:1234/".parse().unwrap()))
+        .proxy_for_scheme("https", Some(proxy))
+        .proxy_for_scheme("http", None)
+        .build()
+        .unwrap();
+
+    client.get(m.url()).unwrap();
+
+    // The tunnel is opened, but the TLS handshake will fail since the fake
+    // proxy closes the connection right away.
+    let _ = client.get("https://127.0.0.2:1234/");
+
+    assert_eq!(m.requests().len(), 1);
+    assert_eq!(m.request().url, "/");
+
+    let connect = heads.recv().unwrap().to_lowercase();
+    assert!(connect.starts_with("connect 127.0.0.2:1234 "));
+}
+
+#[test]
+fn request_proxy_takes_precedence_over_proxy_for_scheme() {
+    // Our test server is upstream, reached directly as set on the request.
+    let m = mock!();
+
+    let client = HttpClient::builder()
+        .proxy_for_scheme("http", Some("http://127.0.0.2:1234/".parse().unwrap()))
+        .build()
+        .unwrap();
+
+    let response = client
+        .send(Request::get(m.url()).proxy(None).body(()).unwrap())
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(m.requests().len(), 1);
+}
+
+#[test]
+fn proxy_for_scheme_is_chosen_again_after_redirect_to_another_scheme() {
+    let (proxy, heads) = spawn_recording_proxy(false);
+
+    // Our test server is reached directly, and redirects to HTTPS.
+    let m = mock! {
+        status: 302,
+        headers {
+            "location": "https://127.0.0.2:1234/",
+        }
+    };
+
+    let client = HttpClient::builder()
+        .proxy(Some("http://127.0.0.2:1234/".parse().unwrap()))
+        .proxy_for_scheme("https", Some(proxy))
+        .proxy_for_scheme("http", None)
+        .redirect_policy(RedirectPolicy::Follow)
+        .build()
+        .unwrap();
+
+    // The tunnel is opened, but the TLS handshake will fail since the fake
+    // proxy closes the connection right away.
+    let _ = client.get(m.url());
+
+    assert_eq!(m.requests().len(), 1);
+
+    let connect = heads.recv().unwrap().to_lowercase();
+    assert!(connect.starts_with("connect 127.0.0.2:1234 "));
+}
+
+#[test]
+fn proxy_headers_are_sent_on_connect() {
+    let (proxy, heads) = spawn_recording_proxy(false);
+
+    // The tunnel is opened, but the TLS handshake will fail since the fake
+    // proxy closes the connection right away.
+    let _ = Request::get("https://127.0.0.2:1234/")
+        .proxy(proxy)
+        .proxy_headers(proxy_token_headers(), true)
+        .body(())
+        .unwrap()
+        .send();
+
+    let connect = heads.recv().unwrap().to_lowercase();
+    assert!(connect.starts_with("connect 127.0.0.2:1234 "));
+    assert!(connect.contains("x-proxy-token: secret\r\n"));
+}
+
+#[test]
+fn proxy_headers_are_sent_once_on_plain_http_requests() {
+    let (proxy, heads) = spawn_recording_proxy(true);
+
+    Request::get("http://127.0.0.2:1234/")
+        .proxy(proxy)
+        .proxy_headers(proxy_token_headers(), false)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let head = heads.recv().unwrap().to_lowercase();
+    assert!(head.starts_with("get http://127.0.0.2:1234/ "));
+    assert_eq!(head.matches("x-proxy-token: secret\r\n").count(), 1);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn proxy_headers_are_not_sent_to_origin_without_proxy() {
+    let server = TlsServer::new();
+
+    Request::get(server.url())
+        .proxy(None)
+        .proxy_headers(proxy_token_headers(), false)
+        .ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let head = server.connections()[0].request.to_lowercase();
+    assert!(head.starts_with("get / "));
+    assert!(!head.contains("x-proxy-token"));
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn proxy_headers_are_not_sent_to_origin_in_proxy_blacklist() {
+    let server = TlsServer::new();
+
+    Request::get(server.url())
+        .proxy(Some("http://127.0.0.2:1234/".parse().unwrap()))
+        .proxy_blacklist(Some(String::from("localhost")))
+        .proxy_headers(proxy_token_headers(), false)
+        .ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let head = server.connections()[0].request.to_lowercase();
+    assert!(head.starts_with("get / "));
+    assert!(!head.contains("x-proxy-token"));
+}
+
+#[test]
+#[cfg(feature = "unstable-raw-handle")]
+fn connect_only_proxy_headers_are_not_sent_on_tunneled_request() {
+    let (proxy, heads) = spawn_recording_proxy(true);
+
+    Request::get("http://127.0.0.2:1234/")
+        .proxy(proxy)
+        .proxy_headers(proxy_token_headers(), true)
+        .with_raw_handle(|easy| easy.http_proxy_tunnel(true))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let connect = heads.recv().unwrap().to_lowercase();
+    assert!(connect.starts_with("connect 127.0.0.2:1234 "));
+    assert!(connect.contains("x-proxy-token: secret\r\n"));
+
+    let tunneled = heads.recv().unwrap().to_lowercase();
+    assert!(tunneled.starts_with("get / "));
+    assert!(!tunneled.contains("x-proxy-token"));
+}
+
+#[test]
+fn proxy_ssl_options_are_ignored_for_http_proxies() {
+    let m = mock!();
+    let (proxy, requests) = spawn_recording_proxy(false);
+
+    Request::get(m.url())
+        .proxy(Some(proxy))
+        .proxy_ssl_ca_certificate(CaCertificate::file("missing-proxy-ca.pem"))
+        .proxy_ssl_options(SslOption::DANGER_ACCEPT_INVALID_CERTS)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert!(requests.recv().unwrap().starts_with("GET "));
+}
+
+#[test]
+fn missing_proxy_ca_certificate_fails_https_proxy_connection() {
+    let m = mock!();
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let proxy = format!("https://{}", listener.local_addr().unwrap());
+    let dir = tempfile::tempdir().unwrap();
+
+    let result = Request::get(m.url())
+        .proxy(Some(proxy.parse().unwrap()))
+        .proxy_ssl_ca_certificate(CaCertificate::file(dir.path().join("missing.pem")))
+        .timeout(std::time::Duration::from_secs(5))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::BadServerCertificate);
+    assert!(m.requests().is_empty());
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn proxy_ca_certificate_is_used_to_verify_https_proxy() {
+    let proxy = TlsServer::new();
+
+    let response = Request::get("http://127.0.0.2:1234/")
+        .proxy(Some(proxy.url().parse().unwrap()))
+        .proxy_ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .timeout(std::time::Duration::from_secs(5))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+
+    let head = proxy.connections()[0].request.to_lowercase();
+    assert!(head.starts_with("get http://127.0.0.2:1234/ "));
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn https_proxy_is_not_trusted_without_proxy_ca_certificate() {
+    let proxy = TlsServer::new();
+
+    let result = Request::get("http://127.0.0.2:1234/")
+        .proxy(Some(proxy.url().parse().unwrap()))
+        .ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .timeout(std::time::Duration::from_secs(5))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::BadServerCertificate);
+    assert!(proxy.connections().is_empty());
+}
+
+/// Spawn a TCP server that echoes back everything it receives on a single
+/// connection.
+fn spawn_echo_server() -> String {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    thread::spawn(move || {
+        let mut stream = listener.accept().unwrap().0;
+        let mut buf = [0; 1024];
+
+        loop {
+            match stream.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(len) => stream.write_all(&buf[..len]).unwrap(),
+            }
+        }
+    });
+
+    addr
+}
+
+/// Spawn an HTTP proxy that accepts a single `CONNECT` request and forwards
+/// bytes in both directions between the client and the requested host.
+fn spawn_forwarding_proxy() -> (http::Uri, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let uri = format!("http://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut client = listener.accept().unwrap().0;
+        let head = read_request_head(&mut client);
+        let target = head.split_whitespace().nth(1).unwrap().to_owned();
+        tx.send(head).unwrap();
+
+        let mut upstream = TcpStream::connect(target).unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .unwrap();
+
+        let mut client_reader = client.try_clone().unwrap();
+        let mut upstream_writer = upstream.try_clone().unwrap();
+
+        thread::spawn(move || std::io::copy(&mut client_reader, &mut upstream_writer));
+        let _ = std::io::copy(&mut upstream, &mut client);
+    });
+
+    (uri, rx)
+}
+
+#[test]
+fn connect_tunnel_exchanges_raw_bytes() {
+    let echo = spawn_echo_server();
+    let (proxy, heads) = spawn_forwarding_proxy();
+
+    let client = HttpClient::builder().proxy(Some(proxy)).build().unwrap();
+
+    block_on(async {
+        let mut tunnel = client.connect_async(echo.as_str()).await.unwrap();
+
+        tunnel.write_all(b"hello tunnel").await.unwrap();
+
+        let mut buf = [0; 12];
+        tunnel.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello tunnel");
+
+        tunnel.write_all(b"again").await.unwrap();
+
+        let mut buf = [0; 5];
+        tunnel.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"again");
+    });
+
+    assert!(heads
+        .recv()
+        .unwrap()
+        .starts_with(&format!("CONNECT {} HTTP/1.1\r\n", echo)));
+}
+
+#[test]
+fn connect_tunnel_uses_proxy_for_scheme() {
+    let echo = spawn_echo_server();
+    let (proxy, heads) = spawn_forwarding_proxy();
+
+    let client = HttpClient::builder()
+        .proxy_for_scheme("http", Some(proxy))
+        .build()
+        .unwrap();
+
+    block_on(async {
+        let mut tunnel = client.connect_async(echo.as_str()).await.unwrap();
+
+        tunnel.write_all(b"ping").await.unwrap();
+
+        let mut buf = [0; 4];
+        tunnel.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    });
+
+    assert!(heads
+        .recv()
+        .unwrap()
+        .starts_with(&format!("CONNECT {} HTTP/1.1\r\n", echo)));
+}
+
+#[test]
+fn connect_tunnel_rejected_by_proxy_returns_error() {
+    let m = mock! {
+        status: 403,
+    };
+    let proxy = m.url().parse::<http::Uri>().unwrap();
+
+    let client = HttpClient::builder().proxy(Some(proxy)).build().unwrap();
+
+    let result = block_on(client.connect_async("127.0.0.1:1"));
+
+    assert!(result.is_err());
+    assert_eq!(m.request().method, "CONNECT");
+}
+
+#[test]
+fn proxy_rejecting_credentials_returns_proxy_authentication_error() {
+    let m = mock! {
+        status: 407,
+        headers {
+            "proxy-authenticate": "Basic realm=\"proxy\"",
+        }
+    };
+    let proxy = m.url().parse::<http::Uri>().unwrap();
+
+    let result = Request::get("https://127.0.0.1:1/")
+        .proxy(proxy)
+        .proxy_authentication(Authentication::basic())
+        .proxy_credentials(Credentials::new("clark", "wrong"))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::ProxyAuthenticationFailed);
+    assert_eq!(m.request().method, "CONNECT");
+}
+
+#[test]
+fn connect_tunnel_requiring_proxy_authentication_returns_proxy_authentication_error() {
+    let m = mock! {
+        status: 407,
+    };
+    let proxy = m.url().parse::<http::Uri>().unwrap();
+
+    let client = HttpClient::builder().proxy(Some(proxy)).build().unwrap();
+
+    let result = block_on(client.connect_async("127.0.0.1:1"));
+
+    assert_matches!(result, Err(e) if e == ErrorKind::ProxyAuthenticationFailed);
+}
+
+#[test]
+fn plain_http_proxy_authentication_response_is_returned_unaltered() {
+    let m = mock! {
+        status: 407,
+    };
+    let proxy = m.url().parse::<http::Uri>().unwrap();
+
+    // Without a tunnel, the proxy's response is returned as-is.
+    let response = Request::get("http://127.0.0.1:1/")
+        .proxy(proxy)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 407);
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "unstable-raw-handle")]
+
+use isahc::{prelude::*, Request};
+use testserver::mock;
+
+#[test]
+fn raw_handle_hook_is_applied_last() {
+    let m = mock!();
+
+    Request::get(m.url())
+        .with_raw_handle(|easy| {
+            // Custom headers take precedence over the curl user agent option,
+            // so replace the headers set by Isahc first.
+            easy.http_headers(curl::easy::List::new())?;
+            easy.useragent("custom-agent/1.0")
+        })
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    m.request().expect_header("user-agent", "custom-agent/1.0");
+}
+
+#[test]
+fn raw_handle_hook_error_fails_request() {
+    let m = mock!();
+
+    let result = Request::get(m.url())
+        .with_raw_handle(|_| Err(curl::Error::new(curl_sys::CURLE_UNKNOWN_OPTION)))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert!(result.is_err());
+    assert!(m.requests().is_empty());
+}
@@ -1,4 +1,10 @@
-use isahc::{config::RedirectPolicy, prelude::*, Body, HttpClient, Request};
+use isahc::{
+    config::{PostRedirectPolicy, RedirectPolicy},
+    prelude::*,
+    Body,
+    HttpClient,
+    Request,
+};
 use test_case::test_case;
 use testserver::mock;
 
@@ -23,6 +29,20 @@ fn response_301_no_follow() {
     assert!(!m.requests().is_empty());
 }
 
+#[test]
+fn effective_uri_is_set_without_redirects() {
+    let m = mock!();
+
+    let response = isahc::get(format!("{}a/../b/./c", m.url())).unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.effective_uri().unwrap().to_string(),
+        format!("{}b/c", m.url())
+    );
+    assert_eq!(m.request().url, "/b/c");
+}
+
 #[test]
 fn response_301_auto_follow() {
     let m2 = mock! {
@@ -144,6 +164,59 @@ fn redirect_also_sends_post(status: u16) {
     assert_eq!(m2.request().method, "POST");
 }
 
+#[test_case(PostRedirectPolicy::Spec, "GET")]
+#[test_case(PostRedirectPolicy::PreservePost, "POST")]
+fn post_redirect_policy_controls_method_after_302(policy: PostRedirectPolicy, method: &str) {
+    let m2 = mock!();
+    let location = m2.url();
+
+    let m1 = mock! {
+        status: 302,
+        headers {
+            "Location": location,
+        }
+    };
+
+    let response = Request::post(m1.url())
+        .redirect_policy(RedirectPolicy::Follow)
+        .post_redirect_policy(policy)
+        .body("hello")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(m1.request().method, "POST");
+    assert_eq!(m2.request().method, method);
+
+    if method == "POST" {
+        m2.request().expect_body("hello");
+    }
+}
+
+#[test]
+fn preserve_post_still_follows_303_with_get() {
+    let m2 = mock!();
+    let location = m2.url();
+
+    let m1 = mock! {
+        status: 303,
+        headers {
+            "Location": location,
+        }
+    };
+
+    Request::post(m1.url())
+        .redirect_policy(RedirectPolicy::Follow)
+        .post_redirect_policy(PostRedirectPolicy::PreservePost)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(m2.request().method, "GET");
+}
+
 // Issue #250
 #[test]
 fn redirect_with_response_body() {
@@ -284,6 +357,47 @@ fn auto_referer_sets_expected_header() {
     m3.request().expect_header("Referer", m2.url());
 }
 
+#[test]
+fn referer_sets_expected_header() {
+    let m = mock!();
+
+    Request::get(m.url())
+        .referer("http://example.org/")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    m.request().expect_header("Referer", "http://example.org/");
+}
+
+#[test]
+fn referer_is_updated_by_auto_referer_after_redirect() {
+    let m2 = mock!();
+
+    let m1 = {
+        let location = m2.url();
+        mock! {
+            status: 301,
+            headers {
+                "Location": location,
+            }
+        }
+    };
+
+    Request::get(m1.url())
+        .redirect_policy(RedirectPolicy::Follow)
+        .auto_referer()
+        .referer("http://example.org/")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    m1.request().expect_header("Referer", "http://example.org/");
+    m2.request().expect_header("Referer", m1.url());
+}
+
 #[test]
 #[ignore = "testserver does not support non-ASCII headers yet"]
 fn redirect_with_unencoded_utf8_bytes_in_location() {
@@ -316,3 +430,66 @@ fn redirect_with_unencoded_utf8_bytes_in_location() {
     assert!(!m1.requests().is_empty());
     assert!(!m2.requests().is_empty());
 }
+
+#[test]
+fn redirect_rejected_by_on_redirect_returns_error() {
+    let m = mock! {
+        status: 302,
+        headers {
+            "Location": "http://169.254.169.254/latest/meta-data/",
+        }
+    };
+
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen_clone = seen.clone();
+
+    let result = Request::get(m.url())
+        .redirect_policy(RedirectPolicy::Follow)
+        .on_redirect(move |from, to| {
+            seen_clone
+                .lock()
+                .unwrap()
+                .push((from.to_string(), to.to_string()));
+
+            !to.host().unwrap_or_default().starts_with("169.254.")
+        })
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == isahc::error::ErrorKind::RedirectRejected);
+    assert_eq!(m.requests().len(), 1);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![(
+            m.url(),
+            "http://169.254.169.254/latest/meta-data/".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn redirect_allowed_by_on_redirect_is_followed() {
+    let m2 = mock! {
+        body: "ok",
+    };
+    let location = m2.url();
+
+    let m1 = mock! {
+        status: 302,
+        headers {
+            "Location": location,
+        }
+    };
+
+    let mut response = Request::get(m1.url())
+        .redirect_policy(RedirectPolicy::Follow)
+        .on_redirect(|_, _| true)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.text().unwrap(), "ok");
+    assert_eq!(m2.requests().len(), 1);
+}
@@ -1,10 +1,16 @@
 use futures_lite::{future::block_on, AsyncRead};
-use isahc::{prelude::*, AsyncBody, Body, Request};
+use isahc::{http::HeaderMap, prelude::*, AsyncBody, Body, Request};
 use std::{
     error::Error,
-    io::{self, Read},
+    io::{self, Read, Write},
+    net::TcpListener,
     pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
+    thread,
 };
 use test_case::test_case;
 use testserver::mock;
@@ -94,6 +100,53 @@ fn content_length_header_takes_precedence_over_body_objects_length(method: &str)
     m.request().expect_body("abc"); // truncated to 3 bytes
 }
 
+#[test]
+fn request_body_can_be_written_from_another_thread() {
+    let m = mock!();
+    let (mut writer, body) = Body::channel();
+
+    let writer_thread = std::thread::spawn(move || {
+        for i in 0..100 {
+            writeln!(writer, "chunk {}", i).unwrap();
+        }
+    });
+
+    Request::put(m.url()).body(body).unwrap().send().unwrap();
+    writer_thread.join().unwrap();
+
+    let expected = (0..100).map(|i| format!("chunk {}\n", i)).collect::<String>();
+
+    m.request().expect_header("transfer-encoding", "chunked");
+    m.request().expect_body(expected);
+}
+
+#[test]
+fn async_request_body_can_be_written_concurrently() {
+    use futures_lite::io::AsyncWriteExt;
+
+    let m = mock!();
+    let (mut writer, body) = AsyncBody::channel();
+
+    block_on(async {
+        let producer = async move {
+            for i in 0..100 {
+                writer
+                    .write_all(format!("chunk {}\n", i).as_bytes())
+                    .await
+                    .unwrap();
+            }
+        };
+
+        let request = Request::put(m.url()).body(body).unwrap();
+        let ((), response) = futures_lite::future::zip(producer, request.send_async()).await;
+        response.unwrap();
+    });
+
+    let expected = (0..100).map(|i| format!("chunk {}\n", i)).collect::<String>();
+
+    m.request().expect_body(expected);
+}
+
 #[test]
 fn upload_from_bad_reader_returns_error_with_original_cause() {
     let m = mock!();
@@ -121,6 +174,39 @@ fn upload_from_bad_reader_returns_error_with_original_cause() {
     );
 }
 
+#[test]
+fn content_length_header_disagreeing_with_body_length_returns_error() {
+    let m = mock!();
+
+    let result = Request::post(m.url())
+        .header("content-length", "5")
+        .body("hello world")
+        .unwrap()
+        .send();
+
+    assert_matches!(&result, Err(e) if e.kind() == isahc::error::ErrorKind::RequestBodyLengthMismatch);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Content-Length 5 disagrees with body length 11"));
+
+    // Nothing should have been sent.
+    assert!(m.requests().is_empty());
+}
+
+#[test]
+fn upload_from_reader_shorter_than_declared_length_returns_descriptive_error() {
+    let m = mock!();
+
+    let result = isahc::put(m.url(), Body::from_reader_sized(&b"hello"[..], 10));
+
+    assert_matches!(&result, Err(e) if e.kind() == isahc::error::ErrorKind::RequestBodyLengthMismatch);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("declared length 10, produced 5"));
+}
+
 #[test]
 fn upload_from_bad_async_reader_returns_error_with_original_cause() {
     let m = mock!();
@@ -152,3 +238,172 @@ fn upload_from_bad_async_reader_returns_error_with_original_cause() {
         io::ErrorKind::UnexpectedEof
     );
 }
+
+#[test]
+fn trailers_are_sent_after_chunked_body() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+        let mut stream = listener.accept().unwrap().0;
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+
+        // Read until the end of the last chunk and the trailer section.
+        while !request.windows(5).any(|w| w == b"\r\n0\r\n") || !request.ends_with(b"\r\n\r\n") {
+            let len = stream.read(&mut buf).unwrap();
+            assert!(len > 0, "connection closed before trailer was received");
+            request.extend_from_slice(&buf[..len]);
+        }
+
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .unwrap();
+
+        String::from_utf8(request).unwrap()
+    });
+
+    let response = Request::put(url)
+        .header("Trailer", "Digest")
+        .request_trailers(|| {
+            let mut trailers = HeaderMap::new();
+            trailers.insert("Digest", "md5=rL0Y20zC+Fzt72VPzMSk2A==".parse().unwrap());
+            trailers
+        })
+        .body(Body::from_reader("foo".as_bytes()))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+
+    let request = server.join().unwrap();
+
+    assert!(request.contains("transfer-encoding: chunked\r\n"));
+    assert!(request.ends_with("3\r\nfoo\r\n0\r\ndigest: md5=rL0Y20zC+Fzt72VPzMSk2A==\r\n\r\n"));
+}
+
+#[test]
+fn trailers_are_not_generated_for_body_of_known_size() {
+    let m = mock!();
+    let invoked = Arc::new(AtomicBool::new(false));
+
+    Request::put(m.url())
+        .request_trailers({
+            let invoked = invoked.clone();
+            move || {
+                invoked.store(true, Ordering::SeqCst);
+                HeaderMap::new()
+            }
+        })
+        .body("foo")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    m.request().expect_body("foo");
+    assert!(!invoked.load(Ordering::SeqCst));
+}
+
+#[test]
+fn content_type_is_guessed_from_file_contents_when_enabled() {
+    let m = mock!();
+
+    let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR";
+    let mut file = tempfile::Builder::new().suffix(".png").tempfile().unwrap();
+    file.write_all(png).unwrap();
+
+    Request::post(m.url())
+        .guess_content_type(true)
+        .body(Body::from(file.reopen().unwrap()))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    m.request().expect_header("content-type", "image/png");
+    m.request().expect_body(&png[..]);
+}
+
+#[test]
+fn content_type_is_not_guessed_by_default() {
+    let m = mock!();
+
+    Request::put(m.url())
+        .body(&b"%PDF-1.7"[..])
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(m.request().get_header("content-type").count(), 0);
+}
+
+#[test]
+fn explicit_content_type_is_not_replaced_by_guess() {
+    let m = mock!();
+
+    Request::post(m.url())
+        .guess_content_type(true)
+        .header("content-type", "application/octet-stream")
+        .body(&b"%PDF-1.7"[..])
+        .unwrap()
+        .send()
+        .unwrap();
+
+    m.request()
+        .expect_header("content-type", "application/octet-stream");
+}
+
+#[test]
+fn reusable_body_can_be_sent_many_times() {
+    let m = mock!();
+
+    let body = Body::from("MyVariableOne=ValueOne&MyVariableTwo=ValueTwo")
+        .reusable()
+        .unwrap();
+
+    for _ in 0..3 {
+        Request::post(m.url()).body(body.body()).unwrap().send().unwrap();
+    }
+
+    let requests = m.requests();
+
+    assert_eq!(requests.len(), 3);
+
+    for request in requests {
+        request.expect_header("content-length", "45");
+        request.expect_body("MyVariableOne=ValueOne&MyVariableTwo=ValueTwo");
+    }
+}
+
+#[test]
+fn reader_body_is_not_reusable() {
+    assert!(Body::from_reader(io::empty()).reusable().is_none());
+
+    let body = AsyncBody::from_reader(futures_lite::io::empty());
+    assert!(body.reusable().is_none());
+}
+
+#[test]
+fn unknown_length_body_fails_when_content_length_required() {
+    let m = mock!();
+
+    let result = Request::post(m.url())
+        .require_content_length(true)
+        .body(Body::from_reader("hello".as_bytes()))
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == isahc::error::ErrorKind::RequestBodyLengthRequired);
+    assert!(m.requests().is_empty());
+
+    // Bodies of known length are still sent.
+    Request::post(m.url())
+        .require_content_length(true)
+        .body(Body::from_reader_sized("hello".as_bytes(), 5))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    m.request().expect_header("content-length", "5");
+    m.request().expect_body("hello");
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "request-compression")]
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use isahc::{config::Encoding, prelude::*, Body, Request};
+use std::io::Read;
+use testserver::mock;
+
+fn payload() -> String {
+    let mut payload = String::from("[");
+
+    for i in 0..1000 {
+        if i > 0 {
+            payload.push(',');
+        }
+        payload.push_str(&format!(r#"{{"id":{},"name":"item","enabled":true}}"#, i));
+    }
+
+    payload.push(']');
+    payload
+}
+
+#[test]
+fn gzip_compressed_request_body_round_trip() {
+    let m = mock!();
+    let payload = payload();
+
+    Request::post(m.url())
+        .header("content-type", "application/json")
+        .request_body_compression(Encoding::Gzip)
+        .body(payload.clone())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let request = m.request();
+    let body = request.body.clone().unwrap();
+
+    request.expect_header("content-encoding", "gzip");
+    request.expect_header("content-length", body.len().to_string());
+    assert!(body.len() < payload.len());
+
+    let mut decoded = String::new();
+    GzDecoder::new(&body[..]).read_to_string(&mut decoded).unwrap();
+    assert_eq!(decoded, payload);
+}
+
+#[test]
+fn deflate_compressed_streaming_request_body_round_trip() {
+    let m = mock!();
+    let payload = payload();
+
+    Request::put(m.url())
+        .header("content-length", payload.len())
+        .request_body_compression(Encoding::Deflate)
+        .body(Body::from_reader_sized(
+            std::io::Cursor::new(payload.clone()),
+            payload.len() as u64,
+        ))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let request = m.request();
+
+    request.expect_header("content-encoding", "deflate");
+    request.expect_header("transfer-encoding", "chunked");
+    assert_eq!(request.get_header("content-length").count(), 0);
+
+    let mut decoded = String::new();
+    ZlibDecoder::new(&request.body.unwrap()[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, payload);
+}
+
+#[test]
+fn request_without_body_is_not_compressed() {
+    let m = mock!();
+
+    Request::get(m.url())
+        .request_body_compression(Encoding::Gzip)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(m.request().get_header("content-encoding").count(), 0);
+}
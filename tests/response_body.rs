@@ -1,5 +1,9 @@
-use futures_lite::{future::block_on, io::AsyncReadExt};
-use isahc::prelude::*;
+use futures_lite::{
+    future::block_on,
+    io::{AsyncBufReadExt, AsyncReadExt},
+    StreamExt,
+};
+use isahc::{prelude::*, Request};
 use std::{io, io::Read};
 use testserver::mock;
 
@@ -35,6 +39,69 @@ fn large_response_body() {
     assert_eq!(response_text, body);
 }
 
+#[test]
+fn large_response_body_with_large_initial_window_size() {
+    let m = mock! {
+        body: "0123456789abcdef".repeat(256 * 1024),
+    };
+
+    let mut response = Request::get(m.url())
+        .request_initial_window_size(8 * 1024 * 1024)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+    let response_text = response.text().unwrap();
+
+    assert_eq!(response_text.len(), 4 * 1024 * 1024);
+    assert!(response_text == "0123456789abcdef".repeat(256 * 1024));
+}
+
+#[test]
+fn large_response_body_with_large_download_buffer() {
+    let m = mock! {
+        body: "0123456789abcdef".repeat(256 * 1024),
+    };
+
+    let mut response = Request::get(m.url())
+        .download_buffer_size(1024 * 1024)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+    let response_text = response.text().unwrap();
+
+    assert_eq!(response_text.len(), 4 * 1024 * 1024);
+    assert!(response_text == "0123456789abcdef".repeat(256 * 1024));
+}
+
+#[test]
+fn text_without_charset_is_decoded_with_default_charset() {
+    let m = mock! {
+        headers {
+            "content-type": "text/plain",
+        }
+        body: &b"price: 5\x80"[..],
+    };
+
+    let client = isahc::HttpClient::builder()
+        .default_charset("windows-1252")
+        .build()
+        .unwrap();
+
+    assert_eq!(client.get(m.url()).unwrap().text().unwrap(), "price: 5€");
+
+    // The charset of the response still takes precedence.
+    let m = mock! {
+        headers {
+            "content-type": "text/plain; charset=utf-8",
+        }
+        body: &b"price: 5\x80"[..],
+    };
+
+    assert_eq!(client.get(m.url()).unwrap().text().unwrap(), "price: 5\u{FFFD}");
+}
+
 #[test]
 fn response_body_with_content_length_knows_its_size() {
     let m = mock! {
@@ -58,6 +125,29 @@ fn response_body_with_chunked_encoding_has_unknown_size() {
     assert_eq!(response.body().len(), None);
 }
 
+#[test]
+fn content_length_is_reported_for_sized_response_body() {
+    let m = mock! {
+        body: "hello world",
+    };
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert_eq!(response.content_length(), Some(11));
+}
+
+#[test]
+fn content_length_is_not_reported_for_chunked_response_body() {
+    let m = mock! {
+        body: "hello world",
+        transfer_encoding: true,
+    };
+
+    let response = isahc::get(m.url()).unwrap();
+
+    assert_eq!(response.content_length(), None);
+}
+
 // See issue #64.
 #[test]
 fn dropping_client_does_not_abort_response_transfer() {
@@ -133,3 +223,113 @@ fn consume_unread_response_body_async() {
         assert_matches!(response.body_mut().read(&mut buf).await, Ok(0));
     });
 }
+
+#[cfg(feature = "bytes")]
+#[test]
+fn response_body_as_shared_bytes() {
+    let m = mock! {
+        body: "hello world",
+    };
+
+    let body = isahc::get(m.url()).unwrap().bytes_shared().unwrap();
+    let copy = body.clone();
+
+    assert_eq!(body, "hello world");
+    assert_eq!(copy, "hello world");
+    assert_eq!(body.as_ptr(), copy.as_ptr());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn response_body_can_be_streamed_into_bounded_channel() {
+    let body = "abcdefghij".repeat(10_000);
+    let expected = body.clone();
+    let m = mock! {
+        body: body.clone(),
+    };
+
+    block_on(async {
+        let (sender, receiver) = isahc::async_channel::bounded::<io::Result<bytes::Bytes>>(1);
+        let response = isahc::get_async(m.url()).await.unwrap();
+
+        let consumer = async move {
+            let mut chunks = Vec::new();
+
+            while let Ok(chunk) = receiver.recv().await {
+                chunks.push(chunk.unwrap());
+            }
+
+            chunks
+        };
+
+        let ((), chunks) =
+            futures_lite::future::zip(response.stream_to_channel(sender), consumer).await;
+
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat(), expected.as_bytes());
+    });
+}
+
+#[test]
+fn ndjson_response_body_can_be_read_line_by_line() {
+    let m = mock! {
+        body: "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n",
+    };
+
+    block_on(async {
+        let response = isahc::get_async(m.url()).await.unwrap();
+        let lines = response
+            .into_body()
+            .lines()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(lines, vec![r#"{"id":1}"#, r#"{"id":2}"#, r#"{"id":3}"#]);
+    });
+}
+
+#[test]
+fn response_body_can_be_copied_to_file_async() {
+    let m = mock! {
+        body: "hello world\n".repeat(10_000),
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("download.txt");
+
+    let written = block_on(async {
+        isahc::get_async(m.url())
+            .await
+            .unwrap()
+            .copy_to_file(&path)
+            .await
+            .unwrap()
+    });
+
+    assert_eq!(written, 120_000);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "hello world\n".repeat(10_000)
+    );
+}
+
+#[test]
+fn copy_to_file_async_returns_error_if_file_cannot_be_created() {
+    let m = mock! {
+        body: "hello world",
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing").join("download.txt");
+
+    let result = block_on(async {
+        isahc::get_async(m.url())
+            .await
+            .unwrap()
+            .copy_to_file(&path)
+            .await
+    });
+
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+}
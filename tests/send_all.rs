@@ -0,0 +1,128 @@
+use futures_lite::{future::block_on, StreamExt};
+use isahc::{prelude::*, HttpClient, Request};
+use testserver::mock;
+
+#[test]
+fn send_all_yields_all_responses() {
+    let m = mock! {
+        body: "hello",
+    };
+
+    let client = HttpClient::builder().max_connections(4).build().unwrap();
+    let requests = (0..10).map(|i| {
+        Request::get(format!("{}{}", m.url(), i))
+            .body(())
+            .unwrap()
+    });
+
+    let bodies = block_on(async {
+        let mut responses = client.send_all_async(requests);
+        let mut bodies = Vec::new();
+
+        while let Some(response) = responses.next().await {
+            bodies.push(response.unwrap().text().await.unwrap());
+        }
+
+        bodies
+    });
+
+    assert_eq!(bodies.len(), 10);
+    assert!(bodies.iter().all(|body| body == "hello"));
+
+    let mut urls = m.requests().into_iter().map(|r| r.url).collect::<Vec<_>>();
+    urls.sort();
+    assert_eq!(urls, (0..10).map(|i| format!("/{}", i)).collect::<Vec<_>>());
+}
+
+#[test]
+fn send_all_respects_max_connections() {
+    let m = mock! {
+        delay: 10ms,
+    };
+
+    let client = HttpClient::builder().max_connections(2).build().unwrap();
+    let requests = (0..10).map(|_| Request::get(m.url()).body(()).unwrap());
+
+    let results = block_on(client.send_all_async(requests).collect::<Vec<_>>());
+
+    assert_eq!(results.len(), 10);
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(m.requests().len(), 10);
+}
+
+#[test]
+fn send_all_failed_request_does_not_stop_others() {
+    let m = mock!();
+
+    let client = HttpClient::new().unwrap();
+    let requests = vec![
+        Request::get(m.url()).body(()).unwrap(),
+        Request::get("http://127.0.0.1:1/").body(()).unwrap(),
+        Request::get(m.url()).body(()).unwrap(),
+    ];
+
+    let results = block_on(client.send_all_async(requests).collect::<Vec<_>>());
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 2);
+    assert_eq!(m.requests().len(), 2);
+}
+
+#[test]
+fn send_all_with_no_requests_is_empty() {
+    let client = HttpClient::new().unwrap();
+
+    let results = block_on(
+        client
+            .send_all_async(Vec::<Request<()>>::new())
+            .collect::<Vec<_>>(),
+    );
+
+    assert!(results.is_empty());
+}
+
+#[test]
+fn send_all_ordered_yields_responses_in_request_order() {
+    let slow = mock! {
+        delay: 300ms,
+        body: "first",
+    };
+    let fast = mock! {
+        body: "second",
+    };
+
+    let client = HttpClient::new().unwrap();
+    let requests = || {
+        vec![
+            Request::get(slow.url()).body(()).unwrap(),
+            Request::get(fast.url()).body(()).unwrap(),
+        ]
+    };
+
+    // Sanity check that the second request really does complete first.
+    let unordered = block_on(async {
+        let mut responses = client.send_all_async(requests());
+        let mut bodies = Vec::new();
+
+        while let Some(response) = responses.next().await {
+            bodies.push(response.unwrap().text().await.unwrap());
+        }
+
+        bodies
+    });
+
+    assert_eq!(unordered, vec!["second", "first"]);
+
+    let ordered = block_on(async {
+        let mut responses = client.send_all_ordered_async(requests());
+        let mut bodies = Vec::new();
+
+        while let Some(response) = responses.next().await {
+            bodies.push(response.unwrap().text().await.unwrap());
+        }
+
+        bodies
+    });
+
+    assert_eq!(ordered, vec!["first", "second"]);
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "sse")]
+
+use futures_lite::{future::block_on, StreamExt};
+use isahc::prelude::*;
+use std::time::Duration;
+use testserver::mock;
+
+#[test]
+fn event_stream_is_parsed_into_events() {
+    let m = mock! {
+        headers {
+            "Content-Type": "text/event-stream",
+        }
+        body: "\
+: this is a comment\n\
+retry: 2500\n\
+\n\
+id: 1\n\
+event: update\n\
+data: first line\n\
+data: second line\n\
+\n\
+data: no type\n\
+\n\
+id: 2\n\
+retry: 1000\n\
+data: {\"done\":true}\n\
+\n",
+    };
+
+    block_on(async {
+        let mut events = isahc::get_async(m.url()).await.unwrap().events();
+
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.id(), Some("1"));
+        assert_eq!(event.event(), "update");
+        assert_eq!(event.data(), "first line\nsecond line");
+        assert_eq!(event.retry(), None);
+        assert_eq!(events.reconnection_time(), Some(Duration::from_millis(2500)));
+
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.id(), Some("1"));
+        assert_eq!(event.event(), "message");
+        assert_eq!(event.data(), "no type");
+
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.id(), Some("2"));
+        assert_eq!(event.data(), r#"{"done":true}"#);
+        assert_eq!(event.retry(), Some(Duration::from_secs(1)));
+
+        assert!(events.next().await.is_none());
+    });
+}
@@ -0,0 +1,220 @@
+use isahc::{config::CaCertificate, error::ErrorKind, prelude::*, HttpClient, Request};
+use testserver::mock;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+use testserver::tls::{self, TlsServer};
+
+#[macro_use]
+mod utils;
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn ca_certificate_dir_is_used_to_verify_server() {
+    let server = TlsServer::new();
+
+    let response = Request::get(server.url())
+        .ssl_ca_certificate(CaCertificate::dir(tls::ca_certificate_dir()))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(server.connections().len(), 1);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn missing_ca_certificate_file_returns_bad_server_certificate() {
+    let server = TlsServer::new();
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.pem");
+
+    let result = Request::get(server.url())
+        .ssl_ca_certificate(CaCertificate::file(missing))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::BadServerCertificate);
+    assert!(server.connections().is_empty());
+}
+
+#[test]
+fn missing_ca_certificate_dir_returns_bad_server_certificate() {
+    let m = mock!();
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing");
+
+    let result = Request::get(m.url())
+        .ssl_ca_certificate(CaCertificate::dir(missing))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::BadServerCertificate);
+    assert!(m.requests().is_empty());
+}
+
+#[test]
+fn peer_certificates_are_not_available_without_tls() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .collect_certificate_info(true)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.peer_certificates(), None);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn peer_certificates_contain_server_certificate() {
+    let server = TlsServer::new();
+
+    let response = Request::get(server.url())
+        .ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .collect_certificate_info(true)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let certificates = response.peer_certificates().unwrap();
+
+    assert_eq!(certificates[0], tls::server_certificate_der());
+}
+
+#[test]
+fn tls_info_is_not_available_without_tls() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .collect_certificate_info(true)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.tls_info(), None);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn tls_info_is_available_over_tls() {
+    let server = TlsServer::new();
+
+    let response = Request::get(server.url())
+        .ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let info = response.tls_info().unwrap();
+
+    assert!(info.version().starts_with("TLSv1."), "{}", info.version());
+    assert!(!info.cipher().is_empty());
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn session_reuse_with_ssl_session_cache(enabled: bool) -> Vec<bool> {
+    let server = TlsServer::new();
+
+    let client = HttpClient::builder()
+        .ssl_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .ssl_session_cache(enabled)
+        .build()
+        .unwrap();
+
+    // The server closes each connection, so every request needs a new TLS
+    // handshake.
+    for _ in 0..2 {
+        client.get(server.url()).unwrap().consume().unwrap();
+    }
+
+    server
+        .connections()
+        .into_iter()
+        .map(|connection| connection.session_reused)
+        .collect()
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn tls_sessions_are_resumed_by_default() {
+    assert_eq!(session_reuse_with_ssl_session_cache(true), vec![false, true]);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn tls_sessions_are_not_resumed_if_ssl_session_cache_is_disabled() {
+    assert_eq!(session_reuse_with_ssl_session_cache(false), vec![false, false]);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn test_server_certificate_is_not_trusted_by_default() {
+    let server = TlsServer::new();
+
+    let result = Request::get(server.url()).body(()).unwrap().send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::BadServerCertificate);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn extra_ca_certificate_is_trusted() {
+    let server = TlsServer::new();
+    let dir = tempfile::tempdir().unwrap();
+    let empty = dir.path().join("empty.pem");
+    std::fs::write(&empty, "").unwrap();
+
+    let client = HttpClient::builder()
+        .ssl_extra_ca_certificate(CaCertificate::file(empty))
+        .ssl_extra_ca_certificate(CaCertificate::file(tls::ca_certificate_path()))
+        .build()
+        .unwrap();
+
+    let response = client.get(server.url()).unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(server.connections().len(), 1);
+}
+
+#[test]
+#[cfg(all(unix, not(target_os = "macos")))]
+fn missing_extra_ca_certificate_returns_bad_server_certificate() {
+    let server = TlsServer::new();
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.pem");
+
+    let result = Request::get(server.url())
+        .ssl_extra_ca_certificate(CaCertificate::file(missing))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == ErrorKind::BadServerCertificate);
+    assert!(server.connections().is_empty());
+}
+
+#[test]
+fn missing_extra_ca_certificate_is_ignored_without_tls() {
+    let m = mock!();
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.pem");
+
+    let response = Request::get(m.url())
+        .ssl_extra_ca_certificate(CaCertificate::file(missing))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(m.requests().len(), 1);
+}
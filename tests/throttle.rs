@@ -0,0 +1,46 @@
+use isahc::{config::ThrottlePolicy, HttpClient};
+use std::time::{Duration, Instant};
+use testserver::mock;
+
+#[test]
+fn overloaded_server_slows_subsequent_requests() {
+    let m = mock! {
+        status: 429,
+        headers {
+            "Retry-After": "1",
+        }
+    };
+
+    let client = HttpClient::builder()
+        .throttle_policy(ThrottlePolicy::new())
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    assert_eq!(client.get(m.url()).unwrap().status(), 429);
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+    let start = Instant::now();
+    assert_eq!(client.get(m.url()).unwrap().status(), 429);
+    assert!(start.elapsed() >= Duration::from_millis(900));
+
+    assert_eq!(m.requests().len(), 2);
+}
+
+#[test]
+fn successful_responses_are_not_throttled() {
+    let m = mock!();
+
+    let client = HttpClient::builder()
+        .throttle_policy(ThrottlePolicy::new())
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+
+    for _ in 0..3 {
+        assert_eq!(client.get(m.url()).unwrap().status(), 200);
+    }
+
+    assert!(start.elapsed() < Duration::from_millis(500));
+}
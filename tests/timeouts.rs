@@ -1,8 +1,9 @@
-use isahc::{prelude::*, Request};
+use futures_lite::{future::block_on, AsyncReadExt};
+use isahc::{prelude::*, HttpClient, Request};
 use std::{
     io::{self, Cursor, Read},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use testserver::mock;
 
@@ -60,3 +61,200 @@ fn timeout_during_response_body_produces_error() {
         std::io::ErrorKind::TimedOut
     );
 }
+
+#[test]
+fn headers_timeout_is_reached_if_headers_are_slow() {
+    let m = mock! {
+        delay: 3s,
+    };
+
+    let result = Request::get(m.url())
+        .headers_timeout(Duration::from_millis(500))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == isahc::error::ErrorKind::Timeout);
+}
+
+#[test]
+fn headers_timeout_does_not_apply_to_response_body() {
+    struct SlowReader;
+
+    impl Read for SlowReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            thread::sleep(Duration::from_secs(2));
+            Ok(0)
+        }
+    }
+
+    let m = mock! {
+        body_reader: Cursor::new(vec![0; 100_000]).chain(SlowReader),
+    };
+
+    let mut response = Request::get(m.url())
+        .headers_timeout(Duration::from_millis(500))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Reading the body takes longer than the timeout, but should still succeed.
+    assert_eq!(response.copy_to(std::io::sink()).unwrap(), 100_000);
+}
+
+#[test]
+fn idle_timeout_is_reached_if_response_body_stalls() {
+    struct SlowReader;
+
+    impl Read for SlowReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            thread::sleep(Duration::from_secs(2));
+            Ok(0)
+        }
+    }
+
+    let m = mock! {
+        body_reader: Cursor::new(vec![0; 100_000]).chain(SlowReader),
+    };
+
+    let mut response = isahc::get(m.url())
+        .unwrap()
+        .with_idle_timeout(Duration::from_millis(500));
+
+    let mut buf = Vec::new();
+    let error = response.body_mut().read_to_end(&mut buf).unwrap_err();
+
+    // The data that was sent should have been read before timing out.
+    assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    assert!(!buf.is_empty());
+}
+
+#[test]
+fn idle_timeout_is_reached_if_async_response_body_stalls() {
+    struct SlowReader;
+
+    impl Read for SlowReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            thread::sleep(Duration::from_secs(2));
+            Ok(0)
+        }
+    }
+
+    let m = mock! {
+        body_reader: Cursor::new(vec![0; 100_000]).chain(SlowReader),
+    };
+
+    block_on(async {
+        let mut response = isahc::get_async(m.url())
+            .await
+            .unwrap()
+            .with_idle_timeout(Duration::from_millis(500));
+
+        let mut buf = Vec::new();
+        let error = response.body_mut().read_to_end(&mut buf).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(!buf.is_empty());
+    });
+}
+
+#[test]
+fn request_succeeds_before_future_deadline() {
+    let m = mock!();
+
+    let response = Request::get(m.url())
+        .deadline(Instant::now() + Duration::from_secs(10))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+}
+
+#[test]
+fn request_errors_if_deadline_is_reached() {
+    let m = mock! {
+        delay: 1s,
+    };
+
+    let result = Request::get(m.url())
+        .deadline(Instant::now() + Duration::from_millis(500))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == isahc::error::ErrorKind::Timeout);
+}
+
+#[test]
+fn request_with_elapsed_deadline_times_out_without_being_sent() {
+    let m = mock!();
+    let deadline = Instant::now();
+
+    thread::sleep(Duration::from_millis(10));
+
+    let result = Request::get(m.url())
+        .deadline(deadline)
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == isahc::error::ErrorKind::Timeout);
+    assert!(m.requests().is_empty());
+}
+
+#[test]
+fn shorter_timeout_takes_precedence_over_deadline() {
+    let m = mock! {
+        delay: 1s,
+    };
+
+    let result = Request::get(m.url())
+        .deadline(Instant::now() + Duration::from_secs(10))
+        .timeout(Duration::from_millis(500))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == isahc::error::ErrorKind::Timeout);
+}
+
+#[test]
+fn request_can_opt_out_of_client_timeout() {
+    let m = mock! {
+        delay: 1s,
+    };
+
+    let client = HttpClient::builder()
+        .timeout(Duration::from_millis(500))
+        .build()
+        .unwrap();
+
+    let result = client.get(m.url());
+    assert_matches!(result, Err(e) if e == isahc::error::ErrorKind::Timeout);
+
+    let response = client
+        .send(Request::get(m.url()).no_timeout().body(()).unwrap())
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(m.requests().len(), 2);
+}
+
+#[test]
+fn deadline_applies_when_timeout_is_disabled() {
+    let m = mock! {
+        delay: 1s,
+    };
+
+    let result = Request::get(m.url())
+        .no_timeout()
+        .deadline(Instant::now() + Duration::from_millis(500))
+        .body(())
+        .unwrap()
+        .send();
+
+    assert_matches!(result, Err(e) if e == isahc::error::ErrorKind::Timeout);
+}
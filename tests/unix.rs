@@ -40,3 +40,39 @@ fn send_request_to_unix_socket() {
 
     assert_eq!(response.text().unwrap(), "success\n");
 }
+
+#[test]
+#[cfg(target_os = "linux")]
+#[rustfmt::skip]
+fn send_request_to_abstract_unix_socket() {
+    use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+
+    let name = format!("isahc-test-{}", std::process::id());
+    let addr = SocketAddr::from_abstract_name(&name).unwrap();
+    let listener = UnixListener::bind_addr(&addr).unwrap();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = stream.try_clone().unwrap();
+
+        thread::spawn(move || {
+            io::copy(&mut reader, &mut io::sink()).unwrap();
+        });
+
+        stream.write_all(b"\
+            HTTP/1.1 200 OK\r\n\
+            Content-Length: 8\r\n\
+            \r\n\
+            success\n\
+        ").unwrap();
+    });
+
+    let mut response = Request::get("http://localhost")
+        .abstract_unix_socket(name)
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    assert_eq!(response.text().unwrap(), "success\n");
+}
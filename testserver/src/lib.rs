@@ -5,6 +5,11 @@ mod response;
 
 pub mod socks4;
 
+// The TLS server uses OpenSSL, which is only guaranteed to be available where
+// curl is built against it.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod tls;
+
 pub use mock::Mock;
 pub use request::Request;
 pub use responder::Responder;
@@ -0,0 +1,272 @@
+//! A minimal HTTPS server for tests that need a real TLS handshake.
+//!
+//! The server presents a certificate for `localhost` and `127.0.0.1` issued by
+//! a test certificate authority, which is not trusted by default. Every
+//! connection receives a single canned response and is then closed.
+
+#![allow(unsafe_code)]
+
+use std::{
+    ffi::CString,
+    io::{self, Read},
+    net::{SocketAddr, TcpListener, TcpStream},
+    os::{raw::c_void, unix::io::AsRawFd},
+    path::PathBuf,
+    ptr,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// Get the path of a file in the test certificate directory.
+fn cert_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("certs").join(name)
+}
+
+/// Path to the PEM certificate of the test certificate authority.
+pub fn ca_certificate_path() -> PathBuf {
+    cert_path("ca.pem")
+}
+
+/// Path to a directory containing the test certificate authority in the
+/// hashed layout expected by OpenSSL.
+pub fn ca_certificate_dir() -> PathBuf {
+    cert_path("ca-dir")
+}
+
+/// The DER encoding of the certificate presented by the server.
+pub fn server_certificate_der() -> Vec<u8> {
+    std::fs::read(cert_path("server.der")).unwrap()
+}
+
+/// Information about a connection received by a [`TlsServer`].
+#[derive(Clone, Debug)]
+pub struct Connection {
+    /// The head of the request received over the connection.
+    pub request: String,
+
+    /// Whether the TLS session was resumed from an earlier connection.
+    pub session_reused: bool,
+}
+
+/// A TLS server that answers every connection with the same response.
+pub struct TlsServer {
+    addr: SocketAddr,
+    connections: Arc<Mutex<Vec<Connection>>>,
+}
+
+struct Context(*mut openssl_sys::SSL_CTX);
+
+unsafe impl Send for Context {}
+unsafe impl Sync for Context {}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe {
+            openssl_sys::SSL_CTX_free(self.0);
+        }
+    }
+}
+
+impl TlsServer {
+    /// Start a server that responds with `200 OK` and an empty body.
+    pub fn new() -> Self {
+        Self::with_response("HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+    }
+
+    /// Start a server that writes the given raw response on each connection.
+    pub fn with_response(response: impl Into<String>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(Mutex::new(Vec::new()));
+        let context = Arc::new(create_context());
+        let response = Arc::new(response.into());
+
+        thread::spawn({
+            let connections = connections.clone();
+
+            move || {
+                for stream in listener.incoming().flatten() {
+                    let context = context.clone();
+                    let connections = connections.clone();
+                    let response = response.clone();
+
+                    thread::spawn(move || {
+                        let _ = handle(&context, stream, response.as_bytes(), &connections);
+                    });
+                }
+            }
+        });
+
+        Self {
+            addr,
+            connections,
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get an HTTPS URL for the server, using a host name that matches the
+    /// server certificate.
+    pub fn url(&self) -> String {
+        format!("https://localhost:{}/", self.addr.port())
+    }
+
+    /// Get all connections that completed a TLS handshake and sent a request.
+    pub fn connections(&self) -> Vec<Connection> {
+        self.connections.lock().unwrap().clone()
+    }
+}
+
+impl Default for TlsServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn create_context() -> Context {
+    openssl_sys::init();
+
+    unsafe {
+        let context = Context(openssl_sys::SSL_CTX_new(openssl_sys::TLS_server_method()));
+        assert!(!context.0.is_null());
+
+        let chain = CString::new(cert_path("server.pem").to_str().unwrap()).unwrap();
+        let key = CString::new(cert_path("server.key").to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            openssl_sys::SSL_CTX_use_certificate_chain_file(context.0, chain.as_ptr()),
+            1
+        );
+        assert_eq!(
+            openssl_sys::SSL_CTX_use_PrivateKey_file(
+                context.0,
+                key.as_ptr(),
+                openssl_sys::SSL_FILETYPE_PEM
+            ),
+            1
+        );
+
+        openssl_sys::SSL_CTX_set_alpn_select_cb__fixed_rust(
+            context.0,
+            Some(select_alpn),
+            ptr::null_mut(),
+        );
+
+        context
+    }
+}
+
+/// Select HTTP/1.1 if the client offers it, which is the only protocol the
+/// server speaks.
+unsafe extern "C" fn select_alpn(
+    _ssl: *mut openssl_sys::SSL,
+    out: *mut *const u8,
+    outlen: *mut u8,
+    inbuf: *const u8,
+    inlen: u32,
+    _arg: *mut c_void,
+) -> i32 {
+    const HTTP11: &[u8] = b"\x08http/1.1";
+
+    let mut selected = ptr::null_mut();
+
+    if openssl_sys::SSL_select_next_proto(
+        &mut selected,
+        outlen,
+        HTTP11.as_ptr(),
+        HTTP11.len() as u32,
+        inbuf,
+        inlen,
+    ) == openssl_sys::OPENSSL_NPN_NEGOTIATED
+    {
+        *out = selected;
+        openssl_sys::SSL_TLSEXT_ERR_OK
+    } else {
+        openssl_sys::SSL_TLSEXT_ERR_NOACK
+    }
+}
+
+/// An accepted TLS session over a TCP stream.
+struct Session {
+    ssl: *mut openssl_sys::SSL,
+    _stream: TcpStream,
+}
+
+impl Read for Session {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = unsafe { openssl_sys::SSL_read(self.ssl, buf.as_mut_ptr() as _, buf.len() as _) };
+
+        if len > 0 {
+            Ok(len as usize)
+        } else {
+            Ok(0)
+        }
+    }
+}
+
+impl Session {
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        let len = unsafe { openssl_sys::SSL_write(self.ssl, buf.as_ptr() as _, buf.len() as _) };
+
+        if len as usize == buf.len() {
+            Ok(())
+        } else {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        unsafe {
+            openssl_sys::SSL_shutdown(self.ssl);
+            openssl_sys::SSL_free(self.ssl);
+        }
+    }
+}
+
+fn handle(
+    context: &Context,
+    stream: TcpStream,
+    response: &[u8],
+    connections: &Mutex<Vec<Connection>>,
+) -> io::Result<()> {
+    let mut session = unsafe {
+        let ssl = openssl_sys::SSL_new(context.0);
+        // The socket is closed when the stream is dropped, not by OpenSSL.
+        let bio = openssl_sys::BIO_new_socket(stream.as_raw_fd(), 0);
+        openssl_sys::SSL_set_bio(ssl, bio, bio);
+
+        let session = Session {
+            ssl,
+            _stream: stream,
+        };
+
+        if openssl_sys::SSL_accept(ssl) != 1 {
+            return Err(io::ErrorKind::ConnectionAborted.into());
+        }
+
+        session
+    };
+
+    let mut head = Vec::new();
+    let mut byte = [0];
+
+    while !head.ends_with(b"\r\n\r\n") {
+        if session.read(&mut byte)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        head.push(byte[0]);
+    }
+
+    // Record the connection before responding, so that it is visible as soon
+    // as the client has a response.
+    connections.lock().unwrap().push(Connection {
+        request: String::from_utf8_lossy(&head).into_owned(),
+        session_reused: unsafe { openssl_sys::SSL_session_reused(session.ssl) == 1 },
+    });
+
+    session.write_all(response)
+}